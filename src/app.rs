@@ -1,54 +1,84 @@
-use std::error::Error;
-
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+use crate::http::ScoreServer;
 use crate::{
-    Queens,
+    date::Date,
+    league::{ArchiveSummary, Leaderboard},
+    partnership::Partnerships,
+    replay::Replay,
+    save,
+    scoreboard::Scoreboard,
+    session::{Action, PlayersState, Session, SessionError},
+    share::{self, SharedGame},
+    state::AppState,
+    storage::{EframeReader, EframeStorage, StorageBackend, StorageRead as _, keys},
     ui::{
-        HandBuilderGUI,
-        hands::{HandsHistoric, PendingHand},
+        HandBuilderGUI, PlayersEditor, SubstitutionEditor,
+        bids::BidsView,
+        heatmap::HeatmapView,
+        league::contract_summaries_ui,
+        partnership::{head_to_head_ui, pairs_ui},
+        qr::QrWindow,
+        recovery::{RecoveryAction, recovery_ui},
+        replay::ReplayView,
+        seasons::{SeasonAction, SeasonsView},
+        share::{ShareAction, ShareDialog, SharedAction, shared_game_ui},
+        tournament::TournamentView,
     },
 };
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    storage::{StorageChoice, StorageError},
+    ui::storage::StorageView,
+};
+#[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+use crate::{
+    sync::SyncSession,
+    ui::sync::{SyncAction, SyncDialog},
+};
 use egui::vec2;
 use egui_extras::{Column, TableBuilder};
 use log::{debug, error};
-use whist_game::{
-    Players, PlayersBuilder,
-    contracts::{Contract, default_contracts},
-};
+use whist_game::{Players, PlayersBuilder};
 
-#[derive(serde::Deserialize, serde::Serialize)]
-#[serde(default)]
+#[derive(Default)]
 pub struct WhistApp {
-    pub players_state: PlayersState,
-    pub player_field: String,
-    pub contracts: Vec<Contract>,
-    pub hand_builder: Option<HandBuilderGUI>,
-    pub current_contract_idx: usize,
-    pub pending: bool,
-    pub historic: HandsHistoric,
-    pub hand_detail: Option<usize>,
-}
-
-impl Default for WhistApp {
-    fn default() -> Self {
-        let mut contracts = default_contracts();
-        let queens_contract = Contract {
-            name: "Queens".to_owned(),
-            max_bid: None,
-            contractors_kind: 1..=3,
-            gamemode: Box::new(Queens),
-        };
-        contracts.push(queens_contract);
-        Self {
-            contracts,
-            players_state: Default::default(),
-            player_field: Default::default(),
-            hand_builder: Default::default(),
-            current_contract_idx: Default::default(),
-            pending: Default::default(),
-            historic: Default::default(),
-            hand_detail: Default::default(),
-        }
-    }
+    /// Everything saved, the window only adding views over it.
+    pub state: AppState,
+    pub hand_ui: HandBuilderGUI,
+    pub players_editor: Option<PlayersEditor>,
+    pub substitution_editor: Option<SubstitutionEditor>,
+    pub show_stats: bool,
+    pub leaderboard: Option<Leaderboard>,
+    pub leaderboard_selection: Option<String>,
+    /// Figures of the storage, shown with the leaderboard and the stats.
+    pub archive_summary: Option<ArchiveSummary>,
+    pub seasons_view: Option<SeasonsView>,
+    pub tournament_view: Option<TournamentView>,
+    pub show_settlement: bool,
+    pub salvage_error: Option<String>,
+    pub export_status: Option<String>,
+    /// Game opened from a link, shown read-only until imported.
+    pub shared: Option<SharedGame>,
+    pub share_dialog: Option<ShareDialog>,
+    pub qr_window: Option<QrWindow>,
+    pub replay_view: Option<ReplayView>,
+    pub heatmap_view: Option<HeatmapView>,
+    pub bids_view: Option<BidsView>,
+    /// Where the state is saved, eframe's storage when `None`.
+    pub backend: Option<Box<dyn StorageBackend>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub storage_choice: StorageChoice,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub storage_view: Option<StorageView>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub show_spectator: bool,
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    pub score_server: Option<ScoreServer>,
+    /// Room joined, saved as [`AppState::sync_saved`].
+    #[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+    pub sync: Option<SyncSession>,
+    #[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+    pub sync_dialog: Option<SyncDialog>,
 }
 
 impl WhistApp {
@@ -82,68 +112,645 @@ impl WhistApp {
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        if let Some(storage) = cc.storage {
-            eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default()
-        } else {
-            Default::default()
+        let Some(storage) = cc.storage else {
+            return Default::default();
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        let choice = StorageChoice::remembered(storage);
+        #[cfg(not(target_arch = "wasm32"))]
+        let backend = choice.open().unwrap_or_else(|e| {
+            error!("{e}");
+            None
+        });
+        // Documents are kept in their own browser storage entries.
+        #[cfg(target_arch = "wasm32")]
+        let backend = crate::storage::BrowserStorage::new()
+            .map(|backend| Box::new(backend) as Box<dyn StorageBackend>);
+        let state = match &backend {
+            Some(backend) if backend.get(keys::APP).is_some() => AppState::load(backend.as_ref()),
+            // A new backend starts from what eframe's storage holds.
+            _ => {
+                let mut state = AppState::load(&EframeReader(storage));
+                state.snapshot_requested |= backend.is_some();
+                state
+            }
+        };
+        let mut app = Self {
+            state,
+            ..Default::default()
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        if backend.is_some() {
+            app.storage_choice = choice;
+        }
+        app.backend = backend;
+        #[cfg(target_arch = "wasm32")]
+        match share::take_from_page() {
+            Some(Ok(shared)) => app.shared = Some(shared),
+            Some(Err(e)) => error!("{e}"),
+            None => {}
         }
+        app
     }
 
+    /// Runs `f` on the chosen backend, or on eframe's storage by default.
+    fn on_backend(
+        &mut self,
+        storage: &mut dyn eframe::Storage,
+        f: impl FnOnce(&mut AppState, &mut dyn StorageBackend),
+    ) {
+        match self.backend.as_mut() {
+            Some(backend) => f(&mut self.state, backend.as_mut()),
+            None => f(&mut self.state, &mut EframeStorage(storage)),
+        }
+    }
+
+    /// Closes every view, keeping the state and where it is saved.
+    fn reset_ui(&mut self) {
+        let Self {
+            state,
+            backend,
+            #[cfg(not(target_arch = "wasm32"))]
+            storage_choice,
+            ..
+        } = std::mem::take(self);
+        *self = Self {
+            state,
+            backend,
+            #[cfg(not(target_arch = "wasm32"))]
+            storage_choice,
+            ..Default::default()
+        };
+    }
+
+    /// Starts a new game, keeping everything that outlives a single game.
     pub fn reset_game(&mut self) {
-        *self = Default::default();
+        self.state.reset_game();
+        self.reset_ui();
     }
 
-    pub fn select_players_ui(&mut self, ui: &mut egui::Ui) {
-        let mut state = std::mem::take(&mut self.players_state);
-        match &mut state {
-            PlayersState::Building(players_builder) => {
-                let mut should_build = false;
-                ui.horizontal(|ui| {
-                    ui.label("Add a new player:");
-                    let response = ui.text_edit_singleline(&mut self.player_field);
-                    let enter_pressed =
-                        response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
-
-                    let button_clicked = ui
-                        .add_enabled(players_builder.players.len() < 4, egui::Button::new("Add"))
-                        .on_disabled_hover_text("Already 4 players")
-                        .clicked();
-
-                    if enter_pressed || button_clicked {
-                        let player_name = std::mem::take(&mut self.player_field);
-
-                        match players_builder.add_player(&player_name) {
-                            Ok(4) => {
-                                should_build = true;
-                            }
-                            Ok(_) => {}
-                            Err(e) => {
-                                error!("{e}");
-                            }
-                        }
+    /// Stores the current game in the archive and starts a new one.
+    pub fn finish_game(&mut self) {
+        if self.state.finish_game() {
+            self.reset_ui();
+        }
+    }
+
+    /// Asks the storage for its figures over the archive, if it has any.
+    fn refresh_archive_summary(&mut self) {
+        self.archive_summary = self
+            .backend
+            .as_ref()
+            .and_then(|backend| backend.archive_summary());
+    }
+
+    pub fn recovery_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(quarantine) = &self.state.quarantine else {
+            return;
+        };
+        match recovery_ui(
+            ui,
+            quarantine,
+            self.salvage_error.as_deref(),
+            &mut self.export_status,
+        ) {
+            Some(RecoveryAction::Salvage) => {
+                match save::salvage(&quarantine.raw, quarantine.format) {
+                    Ok(mut state) => {
+                        state.roster = std::mem::take(&mut self.state.roster);
+                        state.snapshot_requested = true;
+                        self.state = state;
+                        self.reset_ui();
                     }
-                    response.request_focus();
+                    Err(e) => self.salvage_error = Some(e.to_string()),
+                }
+            }
+            Some(RecoveryAction::Discard) => {
+                self.state.quarantine = None;
+                self.salvage_error = None;
+                self.export_status = None;
+                self.state.snapshot_requested = true;
+            }
+            None => {}
+        }
+    }
+
+    /// Saves to `choice` from now on, opening the save it holds if any.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn switch_storage(
+        &mut self,
+        choice: StorageChoice,
+        storage: &mut dyn eframe::Storage,
+    ) -> Result<(), StorageError> {
+        let backend = choice.open()?;
+        choice.remember(storage);
+
+        let existing = match &backend {
+            Some(backend) => backend
+                .get(keys::APP)
+                .is_some()
+                .then(|| AppState::load(backend.as_ref())),
+            None => {
+                let reader = EframeReader(storage);
+                reader
+                    .get(keys::APP)
+                    .is_some()
+                    .then(|| AppState::load(&reader))
+            }
+        };
+        match existing {
+            Some(state) => {
+                self.state = state;
+                self.reset_ui();
+            }
+            None => self.state.snapshot_requested = true,
+        }
+        self.backend = backend;
+        self.storage_choice = choice;
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn storage_ui(&mut self, ui: &egui::Ui, storage: &mut dyn eframe::Storage) {
+        let Some(view) = self.storage_view.as_mut() else {
+            return;
+        };
+        let resp = view.ui(ui, &self.storage_choice);
+        if let Some(choice) = resp.inner {
+            self.storage_view = None;
+            // Whatever was not saved yet belongs to the location being left.
+            self.on_backend(storage, AppState::save_snapshot);
+            if let Err(e) = self.switch_storage(choice, storage) {
+                error!("{e}");
+            }
+        } else if resp.should_close() {
+            self.storage_view = None;
+        }
+    }
+
+    /// Link opening the current game, `None` before the players are set or,
+    /// natively, until the address of the web app is known.
+    fn share_link(&self) -> Option<String> {
+        let players = self.state.session.players_state.players()?;
+        #[cfg(target_arch = "wasm32")]
+        let base = share::page_url().unwrap_or_default();
+        #[cfg(not(target_arch = "wasm32"))]
+        let base = Some(self.state.web_url.clone()).filter(|url| !url.is_empty())?;
+        share::link(&base, &players.names(), &self.state.session.historic)
+            .inspect_err(|e| error!("{e}"))
+            .ok()
+    }
+
+    pub fn share_ui(&mut self, ui: &egui::Ui) {
+        if let Some(window) = self.qr_window.as_mut()
+            && !window.show(ui.ctx())
+        {
+            self.qr_window = None;
+        }
+        let Some(dialog) = self.share_dialog.as_mut() else {
+            return;
+        };
+        let resp = dialog.ui(ui);
+        match resp.inner {
+            Some(ShareAction::Open(link)) => match share::decode(&link) {
+                Ok(shared) => {
+                    self.shared = Some(shared);
+                    self.share_dialog = None;
+                }
+                Err(e) => dialog.error = Some(e.to_string()),
+            },
+            Some(ShareAction::ShowQrCode) => {
+                if let Some(link) = &dialog.link {
+                    self.qr_window = Some(QrWindow::new(link));
+                }
+                self.share_dialog = None;
+            }
+            Some(ShareAction::SetWebUrl(web_url)) => {
+                self.state.web_url = web_url;
+                self.state.snapshot_requested = true;
+                let link = self.share_link();
+                if let Some(dialog) = self.share_dialog.as_mut() {
+                    dialog.link = link;
+                }
+            }
+            None => {
+                if resp.should_close() {
+                    self.share_dialog = None;
+                }
+            }
+        }
+    }
+
+    /// Starts a new game from a shared one, archiving the current game.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the shared players cannot be seated, leaving the current game
+    /// as it was.
+    pub fn import_shared(&mut self, shared: SharedGame) -> Result<(), SessionError> {
+        let names: [String; 4] = shared
+            .names
+            .try_into()
+            .map_err(|names: Vec<String>| SessionError::InvalidPlayer(names.join(", ")))?;
+        let mut session = Session {
+            historic: shared.historic,
+            ..Default::default()
+        };
+        session.apply(Action::SetPlayers {
+            names: names.clone(),
+            origin: [0, 1, 2, 3],
+        })?;
+        // The current game is replaced as is, finishing it is the user's call.
+        self.reset_game();
+        self.state.session = session;
+        self.state.remember_players(&names);
+        Ok(())
+    }
+
+    pub fn shared_game_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(shared) = &self.shared else {
+            return;
+        };
+        let missing = shared.missing_contracts(&self.state.session.contracts);
+        match shared_game_ui(ui, shared, &missing) {
+            Some(SharedAction::Import) => {
+                let shared = self.shared.take().expect("Is not None");
+                if let Err(e) = self.import_shared(shared) {
+                    error!("{e}");
+                }
+            }
+            Some(SharedAction::Close) => self.shared = None,
+            None => {}
+        }
+    }
+
+    /// Second native window showing the scoreboard, redrawn with the main one.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spectator_viewport(&mut self, ctx: &egui::Context) {
+        let Some(players) = self.state.session.players_state.players() else {
+            return;
+        };
+        let board = Scoreboard::new(&players.names(), &self.state.session.historic);
+        let close = ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("spectator"),
+            egui::ViewportBuilder::default()
+                .with_title("Whist Points scoreboard")
+                .with_inner_size([960.0, 540.0]),
+            |ui, _class| {
+                egui::CentralPanel::default().show_inside(ui, |ui| {
+                    crate::ui::spectator::scoreboard_ui(ui, &board);
                 });
+                ui.ctx().input(|i| i.viewport().close_requested())
+            },
+        );
+        if close {
+            self.show_spectator = false;
+        }
+    }
 
-                player_grid(ui, players_builder);
+    /// Feeds the scoreboard server and shows where to reach it.
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    fn score_server_ui(&mut self, ui: &egui::Ui) {
+        let Some(server) = self.score_server.as_ref() else {
+            return;
+        };
+        if let Some(players) = self.state.session.players_state.players() {
+            server.update(Scoreboard::new(
+                &players.names(),
+                &self.state.session.historic,
+            ));
+        }
+        let url = server.url();
+        let mut open = true;
+        egui::Window::new("Scoreboard server")
+            .open(&mut open)
+            .resizable(false)
+            .show(ui.ctx(), |ui| {
+                ui.label("Open this address on any device of the same network:");
+                ui.hyperlink(&url);
+                if ui.button("Show QR code").clicked() {
+                    self.qr_window = Some(QrWindow::new(&url));
+                }
+            });
+        if !open {
+            self.score_server = None;
+        }
+    }
 
-                if should_build {
-                    state = state.build().expect("Exactly 4 players set");
-                    if let PlayersState::Playing(players) = &state {
-                        self.hand_builder = Some(HandBuilderGUI::new(players.clone()));
-                    }
+    /// Exchanges changes with the other devices of the room, before they are
+    /// persisted.
+    #[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+    fn sync_ui(&mut self, ui: &egui::Ui) {
+        if self.sync.is_none()
+            && let Some(saved) = self.state.sync_saved.clone()
+        {
+            self.sync = Some(SyncSession::resume(saved, &self.state));
+        }
+        if let Some(session) = self.sync.as_mut() {
+            session.step(&mut self.state);
+            let saved = session.saved();
+            if self.state.sync_saved.as_ref() != Some(&saved) {
+                self.state.sync_saved = Some(saved);
+                self.state.snapshot_requested = true;
+            }
+            // Changes of the others come without any input here.
+            ui.ctx()
+                .request_repaint_after(std::time::Duration::from_millis(200));
+        }
+        let Some(dialog) = self.sync_dialog.as_mut() else {
+            return;
+        };
+        let resp = dialog.ui(ui, self.sync.as_ref());
+        match resp.inner {
+            Some(SyncAction::Connect { url, room }) => {
+                self.sync = Some(SyncSession::connect(&url, &room, &self.state));
+            }
+            Some(SyncAction::Disconnect) => {
+                self.sync = None;
+                self.state.sync_saved = None;
+                self.state.snapshot_requested = true;
+            }
+            Some(SyncAction::KeepConflict(idx)) => {
+                if let Some(session) = self.sync.as_mut() {
+                    session.replica.keep_conflict(idx, &mut self.state);
                 }
             }
+            Some(SyncAction::DropConflict(idx)) => {
+                if let Some(session) = self.sync.as_mut() {
+                    session.replica.drop_conflict(idx);
+                }
+            }
+            None => {
+                if resp.should_close() {
+                    self.sync_dialog = None;
+                }
+            }
+        }
+    }
+
+    pub fn replay_ui(&mut self, ui: &egui::Ui) {
+        if let Some(view) = self.replay_view.as_mut()
+            && !view.show(ui.ctx())
+        {
+            self.replay_view = None;
+        }
+    }
+
+    pub fn heatmap_ui(&mut self, ui: &egui::Ui) {
+        let Some(view) = self.heatmap_view.as_mut() else {
+            return;
+        };
+        let names = self
+            .session
+            .players_state
+            .players()
+            .map(Players::names)
+            .unwrap_or_default();
+        if !view.show(
+            ui.ctx(),
+            &self.state.session.historic,
+            &names,
+            &self.state.archive,
+            &self.state.seasons,
+        ) {
+            self.heatmap_view = None;
+        }
+    }
+
+    pub fn bids_ui(&mut self, ui: &egui::Ui) {
+        let Some(view) = self.bids_view.as_mut() else {
+            return;
+        };
+        let names = self
+            .session
+            .players_state
+            .players()
+            .map(Players::names)
+            .unwrap_or_default();
+        if !view.show(
+            ui.ctx(),
+            &self.state.session.historic,
+            &names,
+            &self.state.archive,
+            &self.state.seasons,
+            &self.state.session.contracts,
+        ) {
+            self.bids_view = None;
+        }
+    }
+
+    pub fn leaderboard_ui(&mut self, ui: &egui::Ui) {
+        let Some(board) = &self.leaderboard else {
+            return;
+        };
+        let resp = crate::ui::league::leaderboard_ui(
+            ui,
+            board,
+            self.archive_summary.as_ref(),
+            &mut self.leaderboard_selection,
+        );
+        if resp.should_close() {
+            self.leaderboard = None;
+        }
+    }
+
+    pub fn players_editor_ui(&mut self, ui: &egui::Ui) {
+        let Some(editor) = self.players_editor.as_mut() else {
+            return;
+        };
+        let resp = editor.ui(ui);
+        if resp.inner {
+            let PlayersEditor { names, origin } = self.players_editor.take().expect("Is not None");
+            if let Err(e) = self.state.apply(Action::SetPlayers { names, origin }) {
+                error!("{e}");
+            }
+        } else if resp.should_close() {
+            self.players_editor = None;
+        }
+    }
+
+    pub fn substitution_ui(&mut self, ui: &egui::Ui) {
+        let Some(editor) = self.substitution_editor.as_mut() else {
+            return;
+        };
+        let names = self
+            .session
+            .players_state
+            .players()
+            .expect("Builder phase finished")
+            .names();
+        let earliest =
+            std::array::from_fn(|seat| self.state.session.historic.earliest_substitution(seat));
+        let resp = editor.ui(ui, &names, &earliest, self.state.session.historic.len());
+        if resp.inner {
+            let SubstitutionEditor {
+                seat,
+                name,
+                from_hand,
+            } = self.substitution_editor.take().expect("Is not None");
+            if let Err(e) = self.state.apply(Action::Substitute {
+                seat,
+                name,
+                from_hand,
+            }) {
+                error!("{e}");
+            }
+        } else if resp.should_close() {
+            self.substitution_editor = None;
+        }
+    }
 
-            PlayersState::Playing(_) => {}
+    pub fn seasons_ui(&mut self, ui: &egui::Ui) {
+        let Some(view) = self.seasons_view.as_mut() else {
+            return;
+        };
+        let resp = view.ui(ui, &self.state.seasons, &self.state.archive);
+        if resp.inner.is_some() {
+            self.state.snapshot_requested = true;
+        }
+        match resp.inner {
+            Some(SeasonAction::Create(season)) => {
+                self.state.seasons.push(season);
+                view.selected = self.state.seasons.len() - 1;
+            }
+            Some(SeasonAction::Close(idx)) => {
+                if let Some(season) = self.state.seasons.get_mut(idx) {
+                    season.close(Date::today(), self.state.archive.len());
+                }
+                *view = SeasonsView::new(&self.state.seasons);
+                view.selected = idx;
+            }
+            None => {
+                if resp.should_close() {
+                    self.seasons_view = None;
+                }
+            }
+        }
+    }
+
+    pub fn settlement_ui(&mut self, ui: &egui::Ui) {
+        if !self.show_settlement {
+            return;
+        }
+        let names = self
+            .session
+            .players_state
+            .players()
+            .expect("Builder phase finished")
+            .names();
+        let resp = crate::ui::settlement::settlement_ui(
+            ui,
+            &mut self.state.settlement,
+            &self.state.session.historic.totals(),
+            &names,
+        );
+        if resp.should_close() {
+            self.show_settlement = false;
+        }
+    }
+
+    pub fn stats_ui(&mut self, ui: &egui::Ui) {
+        if !self.show_stats {
+            return;
+        }
+        let names = self
+            .session
+            .players_state
+            .players()
+            .expect("Builder phase finished")
+            .names();
+        let stats = self.state.session.historic.person_stats(&names);
+        let partnerships = Partnerships::new(&self.state.session.historic, &names);
+        let resp = egui::Modal::new("stats".into()).show(ui.ctx(), |ui| {
+            egui::Grid::new("stats_grid").striped(true).show(ui, |ui| {
+                ui.label("Player");
+                ui.label("Hands");
+                ui.label("Points");
+                ui.label("Contracts");
+                ui.label("Won");
+                ui.end_row();
+                for person in &stats {
+                    ui.label(&person.name);
+                    ui.label(format!("{}", person.hands));
+                    ui.label(format!("{}", person.points));
+                    ui.label(format!("{}", person.contracts));
+                    ui.label(format!("{}", person.contracts_won));
+                    ui.end_row();
+                }
+            });
+            egui::CollapsingHeader::new("Partnerships").show(ui, |ui| pairs_ui(ui, &partnerships));
+            egui::CollapsingHeader::new("Head to head")
+                .show(ui, |ui| head_to_head_ui(ui, &partnerships));
+            if let Some(summary) = &self.archive_summary {
+                egui::CollapsingHeader::new("Contracts of the archive")
+                    .show(ui, |ui| contract_summaries_ui(ui, &summary.contracts));
+            }
+            egui::Sides::new().show(
+                ui,
+                |_| {},
+                |ui| {
+                    if ui.button("Ok").clicked() {
+                        ui.close();
+                    }
+                },
+            );
+        });
+        if resp.should_close() {
+            self.show_stats = false;
+        }
+    }
+
+    pub fn select_players_ui(&mut self, ui: &mut egui::Ui) {
+        let PlayersState::Building(players_builder) = &self.state.session.players_state else {
+            return;
+        };
+        let mut added = None;
+        ui.horizontal(|ui| {
+            ui.label("Add a new player:");
+            let response = ui.text_edit_singleline(&mut self.state.player_field);
+            let enter_pressed =
+                response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            let button_clicked = ui
+                .add_enabled(players_builder.players.len() < 4, egui::Button::new("Add"))
+                .on_disabled_hover_text("Already 4 players")
+                .clicked();
+
+            if enter_pressed || button_clicked {
+                added = Some(std::mem::take(&mut self.state.player_field));
+            }
+            response.request_focus();
+        });
+
+        let known: Vec<&String> = self
+            .roster
+            .iter()
+            .filter(|name| !players_builder.players.iter().any(|p| &p.name == *name))
+            .collect();
+        if !known.is_empty() && players_builder.players.len() < 4 {
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Known players:");
+                for name in known {
+                    if ui.small_button(name).clicked() {
+                        added = Some(name.clone());
+                    }
+                }
+            });
+        }
+
+        player_grid(ui, players_builder);
+
+        if let Some(player_name) = added
+            && let Err(e) = self.state.apply(Action::AddPlayer(player_name))
+        {
+            error!("{e}");
         }
-        self.players_state = state;
     }
 
     pub fn select_gamemode_ui(&mut self, ui: &mut egui::Ui) {
-        let contracts = &self.contracts;
+        let contracts = &self.state.session.contracts;
         let current_contract_name = contracts
-            .get(self.current_contract_idx)
+            .get(self.state.current_contract_idx)
             .expect("Index should be inbound")
             .name
             .clone();
@@ -151,7 +758,11 @@ impl WhistApp {
             .selected_text(current_contract_name)
             .show_ui(ui, |ui| {
                 for (idx, contract) in contracts.iter().enumerate() {
-                    ui.selectable_value(&mut self.current_contract_idx, idx, contract.name.clone());
+                    ui.selectable_value(
+                        &mut self.state.current_contract_idx,
+                        idx,
+                        contract.name.clone(),
+                    );
                 }
             });
     }
@@ -172,20 +783,24 @@ impl WhistApp {
             .stick_to_bottom(true)
             .max_scroll_height(max_scroll_height)
             .header(headers_height, |mut header| {
-                for name in &self
+                let names = self
+                    .session
                     .players_state
                     .players()
                     .expect("Builder phase finished")
-                    .names()
-                {
+                    .names();
+                for (seat, name) in names.iter().enumerate() {
+                    let title = self.state.session.historic.seat_header(seat, name);
                     header.col(|ui| {
-                        ui.add(egui::Label::new(name).truncate());
+                        ui.add(egui::Label::new(title).truncate());
                         // ui.add(egui::Separator::default().grow(5.0));
                     });
                 }
             })
             .body(|mut body| {
-                for (row_index, (_, scores)) in (&self.historic).into_iter().enumerate() {
+                for (row_index, (_, scores)) in
+                    (&self.state.session.historic).into_iter().enumerate()
+                {
                     body.row(headers_height, |mut row| {
                         for score in scores {
                             row.col(|ui| {
@@ -193,165 +808,310 @@ impl WhistApp {
                             });
                         }
                         if row.response().clicked() {
-                            self.hand_detail = Some(row_index);
+                            self.state.hand_detail = Some(row_index);
                         }
                     });
                 }
             });
     }
 
-    fn handle_new_hand(&mut self, ui: &egui::Ui) {
-        if self.pending
-            && let Ok(resp) = self
-                .hand_builder
-                .as_mut()
-                .expect("Hand builder exists if player state is 'Playing'")
-                .ui(
-                    ui,
-                    self.players_state
-                        .players()
-                        .expect("Builder phase finished"),
-                )
+    /// Entries of the "Game" menu, about the game being played.
+    fn game_menu(&mut self, ui: &mut egui::Ui) {
+        if ui.button("Reset game").clicked() {
+            (*self).reset_game();
+        }
+        let can_edit =
+            !self.state.session.players_state.is_building() && self.state.session.draft.is_none();
+        if ui
+            .add_enabled(can_edit, egui::Button::new("Finish game"))
+            .clicked()
         {
-            if let Some(hand) = resp.inner {
-                match hand {
-                    PendingHand::Classical(result) => match result {
-                        Ok(hand) => {
-                            if let Ok(scores) = hand.get_scores() {
-                                self.players_state
-                                    .players_mut()
-                                    .expect("Builder phase finished")
-                                    .update_score(&scores)
-                                    .expect("Non zero score sum should not be possible");
-                                self.historic.push(hand.as_recap(scores));
-                            } else {
-                                error!("Error : Wrong Score");
-                            }
-                        }
-                        Err(e) => error!("{e}"),
-                    },
-                    PendingHand::Custom => {
-                        let recap = self
-                            .hand_builder
-                            .as_mut()
-                            .expect("Hand builder exists if player state is 'Playing'")
-                            .custom_hand_recap()
-                            .expect("All conditions checked");
-
-                        self.players_state
-                            .players_mut()
-                            .expect("Builder phase finished")
-                            .update_score(&recap.scores)
-                            .expect("Non zero score sum should not be possible");
-
-                        self.historic.push(recap);
-                    }
-                }
-            } else if resp.should_close() {
-                self.pending = false;
-            }
+            self.finish_game();
+        }
+        if ui
+            .add_enabled(can_edit, egui::Button::new("Edit players"))
+            .clicked()
+        {
+            let names = self
+                .session
+                .players_state
+                .players()
+                .expect("Builder phase finished")
+                .names();
+            self.players_editor = Some(PlayersEditor::new(&names));
+        }
+        if ui
+            .add_enabled(can_edit, egui::Button::new("Substitute"))
+            .clicked()
+        {
+            self.substitution_editor =
+                Some(SubstitutionEditor::new(self.state.session.historic.len()));
+        }
+        if ui
+            .add_enabled(
+                !self.state.session.players_state.is_building(),
+                egui::Button::new("Stats"),
+            )
+            .clicked()
+        {
+            self.show_stats = true;
+            self.refresh_archive_summary();
+        }
+        if ui
+            .add_enabled(
+                !self.state.session.players_state.is_building(),
+                egui::Button::new("Settlement"),
+            )
+            .clicked()
+        {
+            self.show_settlement = true;
+        }
+        if ui.button("Share").clicked() {
+            self.share_dialog = Some(ShareDialog::new(self.share_link(), &self.state.web_url));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui
+            .add_enabled(
+                !self.state.session.players_state.is_building(),
+                egui::Button::selectable(self.show_spectator, "Spectator window"),
+            )
+            .clicked()
+        {
+            self.show_spectator = !self.show_spectator;
+        }
+        #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+        if ui
+            .add(egui::Button::selectable(
+                self.score_server.is_some(),
+                "Scoreboard server",
+            ))
+            .clicked()
+        {
+            self.score_server = match self.score_server.take() {
+                Some(_stopped) => None,
+                None => ScoreServer::start_on_lan()
+                    .inspect_err(|e| error!("{e}"))
+                    .ok(),
+            };
+        }
+        #[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+        if ui.button("Sync devices").clicked() {
+            self.sync_dialog = Some(SyncDialog::default());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button("Storage").clicked() {
+            self.storage_view = Some(StorageView::new(&self.storage_choice));
         }
     }
-}
 
-impl eframe::App for WhistApp {
-    /// Called by the framework to save state before shutdown.
-    fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
+    /// Entries of the "League" menu, about the archived games.
+    fn league_menu(&mut self, ui: &mut egui::Ui) {
+        if ui.button("Leaderboard").clicked() {
+            // Ratings come from the storage's tables when it has some.
+            let board = self
+                .backend
+                .as_ref()
+                .and_then(|backend| backend.leaderboard())
+                .unwrap_or_else(|| Leaderboard::new(&self.state.archive));
+            self.leaderboard = Some(board);
+            self.refresh_archive_summary();
+        }
+        if ui.button("Seasons").clicked() {
+            self.seasons_view = Some(SeasonsView::new(&self.state.seasons));
+        }
+        if ui.button("Contracts").clicked() {
+            self.heatmap_view = Some(HeatmapView::default());
+        }
+        if ui.button("Bids").clicked() {
+            self.bids_view = Some(BidsView::default());
+        }
+        if ui.button("Tournament").clicked() {
+            self.state.tournament.get_or_insert_default();
+            self.tournament_view = Some(TournamentView::default());
+        }
+        ui.menu_button("Replay", |ui| {
+            if let Some(players) = self.state.session.players_state.players()
+                && ui
+                    .add_enabled(
+                        !self.state.session.historic.is_empty(),
+                        egui::Button::new("Current game"),
+                    )
+                    .clicked()
+            {
+                let replay = Replay::new(&players.names(), &self.state.session.historic);
+                self.replay_view = Some(ReplayView::new("current game".to_owned(), replay));
+            }
+            for game in self.state.archive.iter().rev() {
+                let title = format!("{} · {}", game.finished_on, game.players.join(", "));
+                if ui.button(&title).clicked() {
+                    self.replay_view = Some(ReplayView::new(title, Replay::from_archive(game)));
+                }
+            }
+        });
     }
 
-    /// Called each time the UI needs repainting, which may be many times per second.
-    fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame) {
-        egui::Panel::top("top_panel").show_inside(ui, |ui| {
-            egui::MenuBar::new().ui(ui, |ui| {
-                if ui.button("Reset game").clicked() {
-                    (*self).reset_game();
-                }
-            });
+    fn menu_bar(&mut self, ui: &mut egui::Ui) {
+        egui::MenuBar::new().ui(ui, |ui| {
+            ui.menu_button("Game", |ui| self.game_menu(ui));
+            ui.menu_button("League", |ui| self.league_menu(ui));
         });
+    }
 
-        egui::CentralPanel::default().show_inside(ui, |ui| {
-            ui.heading("Whist Points");
-            ui.separator();
+    /// Shows the dialogs opened from the menus, those about the game being
+    /// played only once its players are set.
+    fn modals_ui(&mut self, ui: &mut egui::Ui) {
+        self.leaderboard_ui(ui);
+        self.replay_ui(ui);
+        self.heatmap_ui(ui);
+        self.bids_ui(ui);
+        self.seasons_ui(ui);
+        self.share_ui(ui);
+        if !self.state.session.players_state.is_building() {
+            self.players_editor_ui(ui);
+            self.substitution_ui(ui);
+            self.stats_ui(ui);
+            self.settlement_ui(ui);
+        }
+    }
 
-            if self.players_state.is_building() {
-                self.select_players_ui(ui);
-                return;
-            }
+    /// Contract choice and hand recording next to the dealer.
+    fn hand_columns_ui(&mut self, ui: &mut egui::Ui) {
+        #[expect(clippy::indexing_slicing)]
+        ui.columns(2, |columns| {
+            egui::Frame::group(columns[0].style())
+                .stroke(egui::Stroke::NONE)
+                .show(&mut columns[0], |ui| {
+                    self.select_gamemode_ui(ui);
 
-            self.score_table_ui(ui);
-            ui.separator();
-
-            #[expect(clippy::indexing_slicing)]
-            ui.columns(2, |columns| {
-                egui::Frame::group(columns[0].style())
-                    .stroke(egui::Stroke::NONE)
-                    .show(&mut columns[0], |ui| {
-                        self.select_gamemode_ui(ui);
-
-                        if let Some(row_idx) = self.hand_detail {
-                            let resp = self.historic.show_hand(
-                                ui,
-                                row_idx,
-                                &self
-                                    .players_state
-                                    .players()
-                                    .expect("Builder phase finished")
-                                    .names(),
-                            );
-                            if resp.should_close() {
-                                self.hand_detail = None;
-                            }
+                    if let Some(row_idx) = self.state.hand_detail {
+                        let resp = self.state.session.historic.show_hand(
+                            ui,
+                            row_idx,
+                            &self
+                                .session
+                                .players_state
+                                .players()
+                                .expect("Builder phase finished")
+                                .names(),
+                        );
+                        if resp.should_close() {
+                            self.state.hand_detail = None;
                         }
+                    }
 
-                        if ui.button("New hand").clicked() {
-                            self.pending = true;
-                            self.hand_builder
-                                .as_mut()
-                                .expect("Hand builder exists if player state is 'Playing'")
-                                .new_hand(
-                                    self.contracts
-                                        .get(self.current_contract_idx)
-                                        .expect("Inbound")
-                                        .clone(),
-                                );
-                            debug!("{}", self.current_contract_idx);
+                    if ui.button("New hand").clicked() {
+                        if let Err(e) = self
+                            .state
+                            .apply(Action::StartHand(self.state.current_contract_idx))
+                        {
+                            error!("{e}");
                         }
+                        debug!("{}", self.state.current_contract_idx);
+                    }
 
-                        self.handle_new_hand(ui);
+                    self.hand_ui.record_ui(ui, &mut self.state.session);
 
-                        if ui.button("Remove last hand").clicked() {
-                            self.historic.remove_last();
-                        }
-                    });
+                    if ui.button("Remove last hand").clicked()
+                        && let Err(e) = self.state.apply(Action::RemoveLastHand)
+                    {
+                        error!("{e}");
+                    }
+                });
 
-                columns[1].with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                    ui.vertical(|ui| {
-                        let dealer = self
-                            .players_state
-                            .players()
-                            .expect("Builder phase finished")
-                            .names()
-                            .get(self.historic.len() % 4)
-                            .expect("Always 4 players")
-                            .clone();
-                        ui.label(format!("Dealer: {dealer}"));
-                        ui.label(format!("Game played: {}", self.historic.len()));
-                    });
+            columns[1].with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                ui.vertical(|ui| {
+                    let dealer = self
+                        .session
+                        .players_state
+                        .players()
+                        .expect("Builder phase finished")
+                        .names()
+                        .get(self.state.session.historic.len() % 4)
+                        .expect("Always 4 players")
+                        .clone();
+                    ui.label(format!("Dealer: {dealer}"));
+                    ui.label(format!(
+                        "Game played: {}",
+                        self.state.session.historic.len()
+                    ));
                 });
             });
+        });
+    }
 
-            ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
-                ui.label(
-                    egui::RichText::new(format!("Version: {}", env!("CARGO_PKG_VERSION")))
-                        .size(10.0),
-                );
-                powered_by_egui_and_eframe(ui);
-            });
+    fn central_panel(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Whist Points");
+        ui.separator();
+
+        if self.state.quarantine.is_some() {
+            self.recovery_ui(ui);
+            return;
+        }
+
+        self.modals_ui(ui);
+
+        if self.shared.is_some() {
+            self.shared_game_ui(ui);
+            return;
+        }
+
+        if let Some(view) = self.tournament_view.as_mut() {
+            let tournament = self.state.tournament.get_or_insert_default();
+            let leave = egui::ScrollArea::vertical()
+                .show(ui, |ui| view.ui(ui, tournament))
+                .inner;
+            if leave {
+                self.tournament_view = None;
+            }
+            return;
+        }
+
+        if self.state.session.players_state.is_building() {
+            self.select_players_ui(ui);
+            return;
+        }
+
+        self.score_table_ui(ui);
+        ui.separator();
+        self.hand_columns_ui(ui);
+
+        ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
+            ui.label(
+                egui::RichText::new(format!("Version: {}", env!("CARGO_PKG_VERSION"))).size(10.0),
+            );
+            powered_by_egui_and_eframe(ui);
         });
     }
 }
 
+impl eframe::App for WhistApp {
+    /// Called by the framework to save state before shutdown.
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.on_backend(storage, AppState::save_snapshot);
+    }
+
+    /// Called each time the UI needs repainting, which may be many times per second.
+    fn ui(&mut self, ui: &mut egui::Ui, frame: &mut eframe::Frame) {
+        egui::Panel::top("top_panel").show_inside(ui, |ui| self.menu_bar(ui));
+        egui::CentralPanel::default().show_inside(ui, |ui| self.central_panel(ui));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.show_spectator {
+            self.spectator_viewport(ui.ctx());
+        }
+        #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+        self.score_server_ui(ui);
+        #[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+        self.sync_ui(ui);
+
+        if let Some(storage) = frame.storage_mut() {
+            #[cfg(not(target_arch = "wasm32"))]
+            self.storage_ui(ui, storage);
+            self.on_backend(storage, AppState::persist);
+        }
+    }
+}
+
 fn powered_by_egui_and_eframe(ui: &mut egui::Ui) {
     ui.horizontal(|ui| {
         ui.spacing_mut().item_spacing.x = 0.0;
@@ -377,45 +1137,3 @@ fn player_grid(ui: &mut egui::Ui, players_builder: &PlayersBuilder) {
             }
         });
 }
-
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-pub enum PlayersState {
-    Building(PlayersBuilder),
-    Playing(Players),
-}
-
-impl Default for PlayersState {
-    fn default() -> Self {
-        Self::Building(PlayersBuilder::default())
-    }
-}
-
-impl PlayersState {
-    fn build(self) -> Result<Self, Box<dyn Error>> {
-        match self {
-            Self::Building(builder) => {
-                let players = builder.build()?;
-                Ok(Self::Playing(players))
-            }
-            Self::Playing(_) => Err("Players already set".into()),
-        }
-    }
-
-    fn is_building(&self) -> bool {
-        matches!(self, Self::Building(_))
-    }
-
-    fn players(&self) -> Option<&Players> {
-        match self {
-            Self::Playing(players) => Some(players),
-            Self::Building(_) => None,
-        }
-    }
-
-    fn players_mut(&mut self) -> Option<&mut Players> {
-        match self {
-            Self::Playing(players) => Some(players),
-            Self::Building(_) => None,
-        }
-    }
-}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{date::Date, historic::HandsHistoric};
+
+/// A hand of a finished game, detached from the `whist_game` types so that
+/// archives stay readable whatever the contract implementations become.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedHand {
+    pub gamemode_name: String,
+    pub scores: [i16; 4],
+    /// Seat and collected tricks of every contractor.
+    pub contractors: Vec<(usize, u8)>,
+    pub bid: Option<u8>,
+    /// Who was sitting at each seat during this hand.
+    pub occupants: [String; 4],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedGame {
+    pub finished_on: Date,
+    /// Player credited with the final result of each seat.
+    pub players: [String; 4],
+    pub totals: [i16; 4],
+    pub hands: Vec<ArchivedHand>,
+}
+
+impl ArchivedGame {
+    pub fn new(historic: &HandsHistoric, names: &[String], finished_on: Date) -> Self {
+        let hands: Vec<ArchivedHand> = historic
+            .into_iter()
+            .enumerate()
+            .map(|(hand_idx, (hand, _))| ArchivedHand {
+                gamemode_name: hand.gamemode_name.clone(),
+                scores: hand.scores,
+                contractors: hand
+                    .contractors_tricks
+                    .iter()
+                    .map(|(id, tricks)| (id.idx(), tricks.get()))
+                    .collect(),
+                bid: hand.bid.map(|bid| bid.get()),
+                occupants: std::array::from_fn(|seat| {
+                    let current = names.get(seat).map(String::as_str).unwrap_or_default();
+                    historic.occupant(seat, hand_idx, current).to_owned()
+                }),
+            })
+            .collect();
+
+        // A substituted seat is credited to whoever played most of its hands.
+        let players = std::array::from_fn(|seat| {
+            let mut counts: Vec<(&str, usize)> = vec![];
+            for hand in &hands {
+                let name = hand.occupants.get(seat).expect("4 seats").as_str();
+                match counts.iter_mut().find(|(n, _)| *n == name) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((name, 1)),
+                }
+            }
+            counts
+                .iter()
+                .max_by_key(|(_, count)| *count)
+                .map(|(name, _)| (*name).to_owned())
+                .unwrap_or_else(|| names.get(seat).cloned().unwrap_or_default())
+        });
+
+        Self {
+            finished_on,
+            players,
+            totals: historic.totals(),
+            hands,
+        }
+    }
+
+    pub fn positions(&self) -> [usize; 4] {
+        positions(&self.totals)
+    }
+}
+
+/// Finishing position of each seat for the given totals, starting at 1. Tied
+/// seats share the best position.
+pub fn positions(totals: &[i16; 4]) -> [usize; 4] {
+    std::array::from_fn(|seat| {
+        let total = totals.get(seat).expect("4 seats");
+        1 + totals.iter().filter(|other| *other > total).count()
+    })
+}
@@ -0,0 +1,186 @@
+//! How accurately and how boldly each player bids.
+
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use whist_game::contracts::Contract;
+
+use crate::{
+    archive::{ArchivedGame, ArchivedHand},
+    date::Date,
+    heatmap::Scope,
+    historic::HandsHistoric,
+    league::Season,
+    session::HandDraft,
+};
+
+/// Bids of one player, as a contractor of contracts with a bid.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BidStats {
+    pub name: String,
+    pub bids: usize,
+    /// Bids reached or exceeded.
+    pub made: usize,
+    /// Tricks above the bid, over the bids made.
+    pub overtricks: u32,
+    /// Tricks missing, over the bids failed.
+    pub undertricks: u32,
+    /// Bids at the highest value the contract allows.
+    pub at_max: usize,
+    /// Number of bids by tricks collected minus tricks bid.
+    pub distribution: BTreeMap<i16, usize>,
+    /// Sum of the position of each bid within its contract's range, from 0
+    /// (lowest) to 1 (highest).
+    boldness: f64,
+}
+
+impl BidStats {
+    /// Mean tricks above the bid when it is made, `None` if never made.
+    pub fn mean_overtricks(&self) -> Option<f64> {
+        (self.made > 0).then(|| f64::from(self.overtricks) / self.made as f64)
+    }
+
+    /// Mean tricks missing when the bid fails, `None` if never failed.
+    pub fn mean_undertricks(&self) -> Option<f64> {
+        let failed = self.bids - self.made;
+        (failed > 0).then(|| f64::from(self.undertricks) / failed as f64)
+    }
+
+    /// Share of the bids made, `None` before any bid.
+    pub fn success_rate(&self) -> Option<f64> {
+        (self.bids > 0).then(|| self.made as f64 / self.bids as f64)
+    }
+
+    /// Share of the bids at the contract's maximum, `None` before any bid.
+    pub fn max_bid_rate(&self) -> Option<f64> {
+        (self.bids > 0).then(|| self.at_max as f64 / self.bids as f64)
+    }
+
+    /// Mean position of the bids within their contract's range, from 0 when
+    /// always bidding the minimum to 1 when always bidding the maximum.
+    pub fn aggressiveness(&self) -> Option<f64> {
+        (self.bids > 0).then(|| self.boldness / self.bids as f64)
+    }
+}
+
+/// Bid statistics of everyone who contracted a bid.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BidReport {
+    /// People in the order they first bid.
+    pub players: Vec<BidStats>,
+    /// Bid range of each contract with a bid.
+    ranges: Vec<(String, RangeInclusive<u8>)>,
+}
+
+impl BidReport {
+    fn with_contracts(contracts: &[Contract]) -> Self {
+        let ranges = contracts
+            .iter()
+            .filter_map(|contract| {
+                let range = HandDraft::new(contract.clone()).bid_range()?;
+                Some((contract.name.clone(), range))
+            })
+            .collect();
+        Self {
+            players: vec![],
+            ranges,
+        }
+    }
+
+    /// Bids of the game being played, `names` being the current seats.
+    pub fn from_historic(
+        historic: &HandsHistoric,
+        names: &[String],
+        contracts: &[Contract],
+    ) -> Self {
+        let mut report = Self::with_contracts(contracts);
+        for hand in &ArchivedGame::new(historic, names, Date::today()).hands {
+            report.add_hand(hand);
+        }
+        report
+    }
+
+    /// Bids of the games within `scope`.
+    pub(crate) fn new(
+        scope: Scope,
+        historic: &HandsHistoric,
+        names: &[String],
+        archive: &[ArchivedGame],
+        seasons: &[Season],
+        contracts: &[Contract],
+    ) -> Self {
+        let current = ArchivedGame::new(historic, names, Date::today());
+        let mut report = Self::with_contracts(contracts);
+        for game in scope.games(&current, archive, seasons) {
+            for hand in &game.hands {
+                report.add_hand(hand);
+            }
+        }
+        report
+    }
+
+    /// Hands of contracts no longer listed are left out, their bid range
+    /// being unknown.
+    fn add_hand(&mut self, hand: &ArchivedHand) {
+        let Some(bid) = hand.bid else {
+            return;
+        };
+        let Some((_, range)) = self
+            .ranges
+            .iter()
+            .find(|(name, _)| *name == hand.gamemode_name)
+        else {
+            return;
+        };
+        let (min, max) = (*range.start(), *range.end());
+        let boldness = if max > min {
+            f64::from(bid.saturating_sub(min)) / f64::from(max - min)
+        } else {
+            1.0
+        };
+        // Contractors of a bid share the tricks of their team.
+        let tricks = hand
+            .contractors
+            .iter()
+            .map(|(_, tricks)| *tricks)
+            .max()
+            .unwrap_or(0);
+        let difference = i16::from(tricks) - i16::from(bid);
+
+        for &(seat, _) in &hand.contractors {
+            let Some(name) = hand.occupants.get(seat) else {
+                continue;
+            };
+            let idx = match self.players.iter().position(|p| p.name == *name) {
+                Some(idx) => idx,
+                None => {
+                    self.players.push(BidStats {
+                        name: name.clone(),
+                        ..Default::default()
+                    });
+                    self.players.len() - 1
+                }
+            };
+            let stats = self.players.get_mut(idx).expect("Known player");
+            stats.bids += 1;
+            stats.boldness += boldness;
+            if bid >= max {
+                stats.at_max += 1;
+            }
+            if difference >= 0 {
+                stats.made += 1;
+                stats.overtricks += u32::from(difference.unsigned_abs());
+            } else {
+                stats.undertricks += u32::from(difference.unsigned_abs());
+            }
+            *stats.distribution.entry(difference).or_default() += 1;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    pub fn player(&self, name: &str) -> Option<&BidStats> {
+        self.players.iter().find(|stats| stats.name == name)
+    }
+}
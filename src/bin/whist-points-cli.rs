@@ -0,0 +1,23 @@
+//! Command-line scorer sharing its saves with the app.
+//!
+//! `whist-points-cli --help` lists the commands. The save is picked with
+//! `--save` or the `WHIST_POINTS_SAVE` variable.
+
+use std::{io::Write as _, process::ExitCode};
+
+use whist_points::cli::{self, CliError};
+
+fn main() -> ExitCode {
+    match cli::run(std::env::args_os()) {
+        Ok(output) => {
+            let _: std::io::Result<()> = write!(std::io::stdout(), "{output}");
+            ExitCode::SUCCESS
+        }
+        // Help and usage errors, with clap's own output and exit code.
+        Err(CliError::Args(e)) => e.exit(),
+        Err(e) => {
+            let _: std::io::Result<()> = writeln!(std::io::stderr(), "{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
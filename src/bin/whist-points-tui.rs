@@ -0,0 +1,41 @@
+//! Terminal frontend, e.g. on a headless machine next to the table.
+//!
+//! `whist-points-tui [SAVE]`, the save being shared with the app and
+//! `whist-points-cli`: a folder, a SQLite database with the `sqlite` feature,
+//! or the app's own `app.ron` file while the app is closed. It defaults to
+//! the `WHIST_POINTS_SAVE` variable, then to `whist-points`.
+
+use std::{error::Error, io::Write as _, path::PathBuf, process::ExitCode};
+
+use whist_points::{state::AppState, storage, tui::Tui};
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let path = std::env::args_os()
+        .nth(1)
+        .or_else(|| std::env::var_os("WHIST_POINTS_SAVE"))
+        .map_or_else(|| PathBuf::from("whist-points"), PathBuf::from);
+    let storage = storage::open_path(path)?;
+    let app = AppState::load(storage.as_ref());
+    if let Some(quarantine) = &app.quarantine {
+        return Err(format!(
+            "The save cannot be read, open it in the app to recover it: {}",
+            quarantine.error
+        )
+        .into());
+    }
+
+    let mut terminal = ratatui::init();
+    let result = Tui::new(app, storage).run(&mut terminal);
+    ratatui::restore();
+    Ok(result?)
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            let _: std::io::Result<()> = writeln!(std::io::stderr(), "{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! Relay keeping the devices of a table in sync.
+//!
+//! `whist-relay [ADDRESS]`, listening on `0.0.0.0:9001` by default. Devices
+//! connect to `ws://<this machine>:<port>`.
+
+fn main() -> std::io::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "0.0.0.0:9001".to_owned());
+    let relay = whist_points::relay::Relay::bind(&addr)?;
+    log::info!("Listening on {}", relay.local_addr()?);
+    relay.run();
+    Ok(())
+}
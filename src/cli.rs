@@ -0,0 +1,316 @@
+//! Command-line scorer, behind the `cli` feature.
+//!
+//! Works on a save folder, or a database with the `sqlite` feature, that the
+//! desktop app can open from Game > Storage, or on the app's own `app.ron`
+//! file while the app is closed. Every command loads the save,
+//! applies itself and persists the game like the app does, hands going to the
+//! journal.
+
+use std::{fmt::Write as _, path::PathBuf};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use thiserror::Error;
+
+use crate::{
+    PlayersState,
+    save::{self, SaveError},
+    session::{Action, SessionError},
+    share::{self, ShareError},
+    state::AppState,
+    storage::{self, Format, StorageError},
+};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "whist-points-cli",
+    version,
+    about = "Keep whist scores from a terminal"
+)]
+pub struct Cli {
+    /// Save folder, SQLite database when ending with `.sqlite` or `.db`, or
+    /// the desktop app's own storage when ending with `.ron`, e.g.
+    /// `~/.local/share/whistpoints/app.ron` on Linux. The app must be closed
+    /// while its own storage is used.
+    #[arg(long, env = "WHIST_POINTS_SAVE", default_value = "whist-points")]
+    save: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Starts a game with four players, replacing the current one. Use
+    /// `finish` first to keep it in the archive.
+    New {
+        #[arg(num_args = 4, required = true)]
+        players: Vec<String>,
+    },
+    /// Adds a hand.
+    Add {
+        #[arg(long)]
+        contract: String,
+        /// Names of the contractors.
+        #[arg(long, num_args = 1..=3, required = true)]
+        contractors: Vec<String>,
+        #[arg(long)]
+        bid: Option<u8>,
+        /// Tricks taken by the contractors.
+        #[arg(long, default_value_t = 0)]
+        tricks: u8,
+        /// Points of three contractors, in seat order, for hands scored by
+        /// hand.
+        #[arg(
+            long,
+            num_args = 3,
+            value_delimiter = ',',
+            allow_negative_numbers = true
+        )]
+        points: Option<Vec<i16>>,
+    },
+    /// Prints the score table.
+    Show,
+    /// Removes the last hand.
+    Undo,
+    /// Archives the current game, `new` starting the next one.
+    Finish,
+    /// Lists the contracts that hands can use.
+    Contracts,
+    /// Prints the game in another format.
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// One line per hand, then the totals.
+    Csv,
+    /// The save of the whole app, as written to the storage.
+    Save,
+    /// A link opening the game in the web app, see the Share dialog.
+    Link,
+}
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error(transparent)]
+    Args(#[from] clap::Error),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+    #[error(transparent)]
+    Save(#[from] SaveError),
+    #[error(transparent)]
+    Share(#[from] ShareError),
+    #[error("The save cannot be read, open it in the app to recover it: {0}")]
+    Unreadable(String),
+    #[error("Invalid players: {0}")]
+    Players(String),
+    #[error("No game started, see the `new` command")]
+    NoGame,
+    #[error("No hand to archive")]
+    NothingToArchive,
+    #[error("Unknown contract {0}, see the `contracts` command")]
+    UnknownContract(String),
+    #[error("Invalid hand: {0}")]
+    Hand(String),
+    #[error(transparent)]
+    Session(#[from] SessionError),
+    #[error("Set the web app address in the app's Share dialog first")]
+    NoWebUrl,
+}
+
+/// Parses `args`, program name first, and runs the command. Returns what to
+/// print.
+///
+/// # Errors
+///
+/// Fails on invalid arguments, an unreadable save or an invalid hand.
+pub fn run<I, T>(args: I) -> Result<String, CliError>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    let cli = Cli::try_parse_from(args)?;
+    let mut storage = storage::open_path(cli.save)?;
+    let mut app = AppState::load(storage.as_ref());
+    if let Some(quarantine) = &app.quarantine {
+        return Err(CliError::Unreadable(quarantine.error.clone()));
+    }
+
+    let output = match cli.command {
+        Command::New { players } => {
+            app.reset_game();
+            let names: [String; 4] = players
+                .try_into()
+                .expect("Four players are required by the arguments");
+            app.apply(Action::SetPlayers {
+                names,
+                origin: [0, 1, 2, 3],
+            })
+            .map_err(|e| CliError::Players(e.to_string()))?;
+            table(&app)?
+        }
+        Command::Add {
+            contract,
+            contractors,
+            bid,
+            tricks,
+            points,
+        } => {
+            add_hand(&mut app, &contract, contractors, bid, tricks, points)?;
+            table(&app)?
+        }
+        Command::Show => table(&app)?,
+        Command::Undo => {
+            app.apply(Action::RemoveLastHand)?;
+            table(&app)?
+        }
+        Command::Finish => {
+            if !app.finish_game() {
+                return Err(CliError::NothingToArchive);
+            }
+            "Game archived\n".to_owned()
+        }
+        Command::Contracts => app
+            .session
+            .contracts
+            .iter()
+            .fold(String::new(), |mut out, c| {
+                let _: std::fmt::Result = writeln!(
+                    out,
+                    "{} ({} to {} contractors{})",
+                    c.name,
+                    c.contractors_kind.start(),
+                    c.contractors_kind.end(),
+                    c.max_bid
+                        .map_or(String::new(), |max| format!(", bid up to {max}"))
+                );
+                out
+            }),
+        Command::Export { format } => export(&app, format, storage.format())?,
+    };
+    app.persist(storage.as_mut());
+    storage.flush()?;
+    Ok(output)
+}
+
+fn add_hand(
+    app: &mut AppState,
+    contract: &str,
+    contractors: Vec<String>,
+    bid: Option<u8>,
+    tricks: u8,
+    points: Option<Vec<i16>>,
+) -> Result<(), CliError> {
+    let PlayersState::Playing(players) = &app.session.players_state else {
+        return Err(CliError::NoGame);
+    };
+    let contract_idx = app
+        .session
+        .contracts
+        .iter()
+        .position(|c| c.name.eq_ignore_ascii_case(contract))
+        .ok_or_else(|| CliError::UnknownContract(contract.to_owned()))?;
+
+    let names = players.names();
+    let mut actions = vec![Action::StartHand(contract_idx)];
+    for name in contractors {
+        let seat = names
+            .iter()
+            .position(|n| n.eq_ignore_ascii_case(&name))
+            .ok_or_else(|| CliError::Hand(format!("{name} is not playing")))?;
+        actions.push(Action::ToggleContractor(seat));
+    }
+    if let Some(bid) = bid {
+        actions.push(Action::SetBid(bid));
+    }
+    actions.push(Action::SetTricks(tricks));
+    if let Some(points) = points {
+        let points = points
+            .try_into()
+            .expect("Three points are required by the arguments");
+        actions.push(Action::SetPoints(points));
+    }
+    actions.push(Action::SubmitHand);
+
+    for action in actions {
+        if let Err(e) = app.apply(action) {
+            let _: Result<_, _> = app.apply(Action::CancelHand);
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
+/// Hands and totals, one column per player.
+fn table(app: &AppState) -> Result<String, CliError> {
+    let PlayersState::Playing(players) = &app.session.players_state else {
+        return Err(CliError::NoGame);
+    };
+    let names = players.names();
+    let width = names
+        .iter()
+        .map(|n| n.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(6);
+    let contract_width = (&app.session.historic)
+        .into_iter()
+        .map(|(hand, _)| hand.gamemode_name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Total".len());
+
+    let mut out = format!("{:>3}  {:<contract_width$}", "#", "Contract");
+    for name in &names {
+        let _: std::fmt::Result = write!(out, "  {name:>width$}");
+    }
+    out.push('\n');
+    for (idx, (hand, _)) in (&app.session.historic).into_iter().enumerate() {
+        let _: std::fmt::Result = write!(
+            out,
+            "{:>3}  {:<contract_width$}",
+            idx + 1,
+            hand.gamemode_name
+        );
+        for score in hand.scores {
+            let _: std::fmt::Result = write!(out, "  {score:>width$}");
+        }
+        out.push('\n');
+    }
+    let _: std::fmt::Result = write!(out, "{:>3}  {:<contract_width$}", "", "Total");
+    for total in app.session.historic.totals() {
+        let _: std::fmt::Result = write!(out, "  {total:>width$}");
+    }
+    out.push('\n');
+    if let Some(dealer) = names.get(app.session.historic.len() % 4) {
+        let _: std::fmt::Result = writeln!(out, "Dealer: {dealer}");
+    }
+    Ok(out)
+}
+
+fn export(app: &AppState, format: ExportFormat, save_format: Format) -> Result<String, CliError> {
+    let PlayersState::Playing(players) = &app.session.players_state else {
+        return Err(CliError::NoGame);
+    };
+    let names = players.names();
+    Ok(match format {
+        ExportFormat::Csv => {
+            let mut out = format!("contract,{}\n", names.join(","));
+            for (hand, _) in &app.session.historic {
+                let scores: Vec<String> = hand.scores.iter().map(i16::to_string).collect();
+                let _: std::fmt::Result =
+                    writeln!(out, "{},{}", hand.gamemode_name, scores.join(","));
+            }
+            out
+        }
+        ExportFormat::Save => save::to_string(app, save_format)?,
+        ExportFormat::Link => {
+            if app.web_url.is_empty() {
+                return Err(CliError::NoWebUrl);
+            }
+            share::link(&app.web_url, &names, &app.session.historic)?
+        }
+    })
+}
@@ -0,0 +1,9 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("An error has occur: {0}")]
+    ImpossibleState(String),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+}
@@ -0,0 +1,166 @@
+//! Which contracts each player calls, and how they fare with them.
+
+use crate::{
+    archive::{ArchivedGame, ArchivedHand},
+    date::Date,
+    historic::HandsHistoric,
+    league::Season,
+};
+
+/// Games a [`Heatmap`] is made of.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scope {
+    #[default]
+    CurrentGame,
+    /// Index of the season. The current game belongs to it while the season
+    /// runs.
+    Season(usize),
+    /// Every archived game and the current one.
+    AllTime,
+}
+
+impl Scope {
+    /// Games within this scope, `current` being the game being played.
+    pub(crate) fn games<'a>(
+        self,
+        current: &'a ArchivedGame,
+        archive: &'a [ArchivedGame],
+        seasons: &[Season],
+    ) -> Vec<&'a ArchivedGame> {
+        let all = archive.iter().chain(std::iter::once(current));
+        match self {
+            Self::CurrentGame => vec![current],
+            Self::Season(idx) => seasons.get(idx).map_or_else(Vec::new, |season| {
+                let running = !season.is_closed() && season.contains(current.finished_on);
+                season
+                    .games(archive)
+                    .chain(running.then_some(current))
+                    .collect()
+            }),
+            Self::AllTime => all.collect(),
+        }
+    }
+}
+
+/// Contracts one player called.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContractCell {
+    pub calls: usize,
+    /// Calls scoring positive points.
+    pub won: usize,
+    /// Points of the player, over all their calls.
+    pub points: i32,
+}
+
+impl ContractCell {
+    /// Share of the calls won, `None` without any call.
+    pub fn success_rate(&self) -> Option<f64> {
+        (self.calls > 0).then(|| self.won as f64 / self.calls as f64)
+    }
+
+    /// Points per call, `None` without any call.
+    pub fn average_delta(&self) -> Option<f64> {
+        (self.calls > 0).then(|| f64::from(self.points) / self.calls as f64)
+    }
+}
+
+/// Players crossed with the contracts they called as contractors.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Heatmap {
+    /// People in the order they first played.
+    pub players: Vec<String>,
+    /// Contracts in the order they were first called.
+    pub contracts: Vec<String>,
+    /// One row of `contracts.len()` cells per player.
+    cells: Vec<Vec<ContractCell>>,
+}
+
+impl Heatmap {
+    /// Heatmap of the game being played, `names` being the current seats.
+    pub fn from_historic(historic: &HandsHistoric, names: &[String]) -> Self {
+        let mut heatmap = Self::default();
+        heatmap.add_game(&ArchivedGame::new(historic, names, Date::today()));
+        heatmap
+    }
+
+    /// Heatmap of the games within `scope`. An unknown season is empty.
+    pub(crate) fn new(
+        scope: Scope,
+        historic: &HandsHistoric,
+        names: &[String],
+        archive: &[ArchivedGame],
+        seasons: &[Season],
+    ) -> Self {
+        let current = ArchivedGame::new(historic, names, Date::today());
+        let mut heatmap = Self::default();
+        for game in scope.games(&current, archive, seasons) {
+            heatmap.add_game(game);
+        }
+        heatmap
+    }
+
+    fn add_game(&mut self, game: &ArchivedGame) {
+        for hand in &game.hands {
+            self.add_hand(hand);
+        }
+    }
+
+    fn add_hand(&mut self, hand: &ArchivedHand) {
+        let contract = match self.contracts.iter().position(|c| *c == hand.gamemode_name) {
+            Some(idx) => idx,
+            None => {
+                self.contracts.push(hand.gamemode_name.clone());
+                for row in &mut self.cells {
+                    row.push(ContractCell::default());
+                }
+                self.contracts.len() - 1
+            }
+        };
+        for &(seat, _) in &hand.contractors {
+            let Some(name) = hand.occupants.get(seat) else {
+                continue;
+            };
+            let player = match self.players.iter().position(|p| p == name) {
+                Some(idx) => idx,
+                None => {
+                    self.players.push(name.clone());
+                    self.cells
+                        .push(vec![ContractCell::default(); self.contracts.len()]);
+                    self.players.len() - 1
+                }
+            };
+            let score = hand.scores.get(seat).copied().unwrap_or_default();
+            let cell = self
+                .cells
+                .get_mut(player)
+                .and_then(|row| row.get_mut(contract))
+                .expect("Known player and contract");
+            cell.calls += 1;
+            cell.points += i32::from(score);
+            if score > 0 {
+                cell.won += 1;
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    /// Calls of `contract` by `player`, `None` for unknown ones.
+    pub fn cell(&self, player: &str, contract: &str) -> Option<&ContractCell> {
+        let player = self.players.iter().position(|p| p == player)?;
+        let contract = self.contracts.iter().position(|c| c == contract)?;
+        self.cells.get(player)?.get(contract)
+    }
+
+    /// Most calls in a single cell, to scale the colours.
+    pub fn max_calls(&self) -> usize {
+        self.cells
+            .iter()
+            .flatten()
+            .map(|cell| cell.calls)
+            .max()
+            .unwrap_or(0)
+    }
+}
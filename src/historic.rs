@@ -0,0 +1,308 @@
+//! Hands of a game, with the running totals and the substitutions.
+
+use serde::{Deserialize, Serialize};
+use whist_game::{HandRecap, PlayerId};
+
+use crate::error::AppError;
+
+/// A player taking over `seat` from hand `from_hand` onwards.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Substitution {
+    pub seat: usize,
+    pub from_hand: usize,
+    pub previous: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PersonStats {
+    pub name: String,
+    pub hands: usize,
+    pub points: i32,
+    pub contracts: usize,
+    pub contracts_won: usize,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct HandsHistoric {
+    list: Vec<HandRecap>,
+    players_scores: Vec<[i16; 4]>,
+    #[serde(default)]
+    substitutions: Vec<Substitution>,
+    #[serde(skip)]
+    events: Vec<HistoricEvent>,
+    /// Events taken so far, so that other observers can tell which of the
+    /// remaining ones they already saw.
+    #[serde(skip)]
+    taken_events: usize,
+}
+
+/// Changes made to the hands since the owner last took them, used to journal
+/// hands without saving the whole game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoricEvent {
+    Pushed(usize),
+    RemovedLast,
+}
+
+impl HandsHistoric {
+    /// Adds a hand, refusing it when a total would not fit.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the totals would overflow, leaving the hands as they were.
+    pub fn push(&mut self, hand_recap: HandRecap) -> Result<(), AppError> {
+        let new_scores = self
+            .totals_after(&hand_recap.scores)
+            .ok_or_else(|| AppError::InvalidInput("Scores out of range".to_owned()))?;
+        self.players_scores.push(new_scores);
+        self.events.push(HistoricEvent::Pushed(self.list.len()));
+        self.list.push(hand_recap);
+        Ok(())
+    }
+
+    /// Totals once a hand scoring `scores` is added, `None` on overflow.
+    pub fn totals_after(&self, scores: &[i16; 4]) -> Option<[i16; 4]> {
+        let totals = self.totals();
+        let mut new_totals = [0; 4];
+        for (new, (total, score)) in new_totals.iter_mut().zip(totals.iter().zip(scores)) {
+            *new = total.checked_add(*score)?;
+        }
+        Some(new_totals)
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&HandRecap> {
+        self.list.get(idx)
+    }
+
+    pub fn take_events(&mut self) -> Vec<HistoricEvent> {
+        self.taken_events += self.events.len();
+        std::mem::take(&mut self.events)
+    }
+
+    /// Number of events since the creation of the hands, taken or not.
+    pub fn event_count(&self) -> usize {
+        self.taken_events + self.events.len()
+    }
+
+    /// Events not taken yet that came after the first `seen` ones.
+    pub fn events_after(&self, seen: usize) -> &[HistoricEvent] {
+        self.events
+            .get(seen.saturating_sub(self.taken_events)..)
+            .unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        assert_eq!(
+            self.list.len(),
+            self.players_scores.len(),
+            "Length difference would imply a misuse of the struct"
+        );
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn remove_last(&mut self) {
+        assert_eq!(
+            self.list.len(),
+            self.players_scores.len(),
+            "Length difference would imply a misuse of the struct"
+        );
+        if self.list.pop().is_some() {
+            self.events.push(HistoricEvent::RemovedLast);
+        }
+        self.players_scores.pop();
+        // The substitute keeps the seat: a substitution from the removed hand
+        // now starts at the next one, merged with any already starting there.
+        let len = self.list.len();
+        for seat in 0..4 {
+            let Some(late) = self
+                .substitutions
+                .iter()
+                .position(|sub| sub.seat == seat && sub.from_hand > len)
+            else {
+                continue;
+            };
+            let late = self.substitutions.remove(late);
+            match self
+                .substitutions
+                .iter_mut()
+                .find(|sub| sub.seat == seat && sub.from_hand == len)
+            {
+                Some(sub) => sub.name = late.name,
+                None => self.substitutions.push(Substitution {
+                    from_hand: len,
+                    ..late
+                }),
+            }
+        }
+        self.substitutions.retain(|sub| sub.previous != sub.name);
+    }
+
+    pub fn totals(&self) -> [i16; 4] {
+        self.players_scores.last().copied().unwrap_or_default()
+    }
+
+    /// Moves every recorded hand to the new seating, `origin[i]` being the
+    /// previous seat of the player now sitting at seat `i` with id `ids[i]`.
+    pub fn reseat(&mut self, origin: &[usize; 4], ids: &[PlayerId; 4]) {
+        let permute = |scores: &[i16; 4]| -> [i16; 4] {
+            std::array::from_fn(|i| {
+                origin
+                    .get(i)
+                    .and_then(|&o| scores.get(o))
+                    .copied()
+                    .expect("Origin is a permutation of the seats")
+            })
+        };
+        for hand in &mut self.list {
+            hand.scores = permute(&hand.scores);
+            for (id, _) in &mut hand.contractors_tricks {
+                let seat = origin
+                    .iter()
+                    .position(|&o| o == id.idx())
+                    .expect("Origin is a permutation of the seats");
+                *id = *ids.get(seat).expect("Seat found in origin");
+            }
+        }
+        for scores in &mut self.players_scores {
+            *scores = permute(scores);
+        }
+        for sub in &mut self.substitutions {
+            sub.seat = origin
+                .iter()
+                .position(|&o| o == sub.seat)
+                .expect("Origin is a permutation of the seats");
+        }
+    }
+
+    /// Renames players in the substitutions, `renames` pairing a previous
+    /// name with the new one.
+    pub fn rename(&mut self, renames: &[(String, String)]) {
+        let rename = |name: &mut String| {
+            if let Some((_, new)) = renames.iter().find(|(old, _)| old == name) {
+                name.clone_from(new);
+            }
+        };
+        for sub in &mut self.substitutions {
+            rename(&mut sub.previous);
+            rename(&mut sub.name);
+        }
+    }
+
+    pub fn substitutions(&self) -> &[Substitution] {
+        &self.substitutions
+    }
+
+    /// First hand a new substitution on `seat` may start at.
+    pub fn earliest_substitution(&self, seat: usize) -> usize {
+        self.substitutions
+            .iter()
+            .filter(|sub| sub.seat == seat)
+            .map(|sub| sub.from_hand + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn add_substitution(&mut self, substitution: Substitution) -> Result<(), AppError> {
+        if substitution.seat >= 4 {
+            return Err(AppError::ImpossibleState(format!(
+                "No seat {}",
+                substitution.seat
+            )));
+        }
+        let range = self.earliest_substitution(substitution.seat)..=self.list.len();
+        if !range.contains(&substitution.from_hand) {
+            return Err(AppError::ImpossibleState(format!(
+                "Substitution must start between hands {} and {}",
+                range.start() + 1,
+                range.end() + 1
+            )));
+        }
+        self.substitutions.push(substitution);
+        Ok(())
+    }
+
+    /// Name of whoever was sitting at `seat` during hand `hand_idx`,
+    /// `current` being the name of the player sitting there now.
+    pub fn occupant<'a>(&'a self, seat: usize, hand_idx: usize, current: &'a str) -> &'a str {
+        self.substitutions
+            .iter()
+            .filter(|sub| sub.seat == seat && sub.from_hand > hand_idx)
+            .min_by_key(|sub| sub.from_hand)
+            .map_or(current, |sub| sub.previous.as_str())
+    }
+
+    pub fn seat_names_at(&self, hand_idx: usize, current: &[String]) -> Vec<String> {
+        current
+            .iter()
+            .enumerate()
+            .map(|(seat, name)| self.occupant(seat, hand_idx, name).to_owned())
+            .collect()
+    }
+
+    /// Header label of a seat, listing every player who sat there.
+    pub fn seat_header(&self, seat: usize, current: &str) -> String {
+        let mut subs: Vec<_> = self
+            .substitutions
+            .iter()
+            .filter(|sub| sub.seat == seat)
+            .collect();
+        subs.sort_by_key(|sub| sub.from_hand);
+        let mut header = subs
+            .first()
+            .map_or(current, |sub| sub.previous.as_str())
+            .to_owned();
+        for sub in subs {
+            header = format!("{header} → {} (#{})", sub.name, sub.from_hand + 1);
+        }
+        header
+    }
+
+    /// Statistics credited to whoever was actually sitting at each seat.
+    pub fn person_stats(&self, current: &[String]) -> Vec<PersonStats> {
+        let mut stats: Vec<PersonStats> = vec![];
+        for (hand_idx, hand) in self.list.iter().enumerate() {
+            for (seat, current) in current.iter().enumerate().take(4) {
+                let name = self.occupant(seat, hand_idx, current);
+                let score = *hand.scores.get(seat).expect("4 seats");
+                let idx = match stats.iter().position(|s| s.name == name) {
+                    Some(idx) => idx,
+                    None => {
+                        stats.push(PersonStats {
+                            name: name.to_owned(),
+                            ..Default::default()
+                        });
+                        stats.len() - 1
+                    }
+                };
+                let entry = stats.get_mut(idx).expect("Found or just pushed");
+                entry.hands += 1;
+                entry.points += i32::from(score);
+                if hand
+                    .contractors_tricks
+                    .iter()
+                    .any(|(id, _)| id.idx() == seat)
+                {
+                    entry.contracts += 1;
+                    if score > 0 {
+                        entry.contracts_won += 1;
+                    }
+                }
+            }
+        }
+        stats
+    }
+}
+
+impl<'a> IntoIterator for &'a HandsHistoric {
+    type Item = (&'a HandRecap, &'a [i16; 4]);
+    type IntoIter = std::iter::Zip<std::slice::Iter<'a, HandRecap>, std::slice::Iter<'a, [i16; 4]>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.iter().zip(self.players_scores.iter())
+    }
+}
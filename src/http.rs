@@ -0,0 +1,222 @@
+//! Score table served on the local network, behind the `http` feature.
+//!
+//! `/` is an HTML page reloading itself, for phones at the table, and
+//! `/scores.json` the same [`Scoreboard`] as JSON. Both list the standings and
+//! every hand played.
+
+use std::{
+    fmt::{self, Write as _},
+    net::{IpAddr, ToSocketAddrs, UdpSocket},
+    sync::{Arc, Mutex, PoisonError},
+    thread::JoinHandle,
+};
+
+use thiserror::Error;
+use tiny_http::{Header, Request, Response, Server};
+
+use crate::scoreboard::Scoreboard;
+
+/// Port tried first, so that the address stays the same between games.
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Seconds between two reloads of the HTML page.
+const REFRESH_SECONDS: u32 = 5;
+
+#[derive(Debug, Error)]
+pub enum HttpError {
+    #[error("Unable to start the scoreboard server: {0}")]
+    Bind(String),
+}
+
+/// Background server answering with the last scoreboard it was given.
+pub struct ScoreServer {
+    server: Arc<Server>,
+    board: Arc<Mutex<Option<Scoreboard>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ScoreServer {
+    /// Listens on `addr` until the value is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the address cannot be bound, e.g. when the port is taken.
+    pub fn start(addr: impl ToSocketAddrs) -> Result<Self, HttpError> {
+        let server = Arc::new(Server::http(addr).map_err(|e| HttpError::Bind(e.to_string()))?);
+        let board = Arc::new(Mutex::new(None));
+        let thread = std::thread::Builder::new()
+            .name("scoreboard server".to_owned())
+            .spawn({
+                let server = Arc::clone(&server);
+                let board = Arc::clone(&board);
+                move || {
+                    for request in server.incoming_requests() {
+                        let board = board.lock().unwrap_or_else(PoisonError::into_inner).clone();
+                        respond(request, board.as_ref());
+                    }
+                }
+            })
+            .map_err(|e| HttpError::Bind(e.to_string()))?;
+        Ok(Self {
+            server,
+            board,
+            thread: Some(thread),
+        })
+    }
+
+    /// Listens on every interface, on [`DEFAULT_PORT`] if it is free.
+    ///
+    /// # Errors
+    ///
+    /// Fails if no port can be bound.
+    pub fn start_on_lan() -> Result<Self, HttpError> {
+        Self::start(("0.0.0.0", DEFAULT_PORT)).or_else(|_e| Self::start(("0.0.0.0", 0)))
+    }
+
+    /// Port the server listens on.
+    pub fn port(&self) -> u16 {
+        self.server
+            .server_addr()
+            .to_ip()
+            .map_or(0, |addr| addr.port())
+    }
+
+    /// Address other devices on the network can open.
+    pub fn url(&self) -> String {
+        let ip = lan_ip().map_or_else(|| "localhost".to_owned(), |ip| ip.to_string());
+        format!("http://{ip}:{}/", self.port())
+    }
+
+    /// Scoreboard served from now on.
+    pub fn update(&self, board: Scoreboard) {
+        *self.board.lock().unwrap_or_else(PoisonError::into_inner) = Some(board);
+    }
+}
+
+impl Drop for ScoreServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take()
+            && thread.join().is_err()
+        {
+            log::error!("Scoreboard server panicked");
+        }
+    }
+}
+
+/// Address of this machine on the local network. Connecting a UDP socket
+/// sends nothing, it only picks the interface used to reach the network.
+fn lan_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).ok()?;
+    socket.connect(("192.0.2.1", 9)).ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+fn respond(request: Request, board: Option<&Scoreboard>) {
+    let path = request.url().split('?').next().unwrap_or_default();
+    let response = match path {
+        "/" => Response::from_string(html(board)).with_header(content_type("text/html")),
+        "/scores.json" => match serde_json::to_string(&board) {
+            Ok(json) => Response::from_string(json).with_header(content_type("application/json")),
+            Err(e) => Response::from_string(e.to_string()).with_status_code(500),
+        },
+        _ => Response::from_string("Not found").with_status_code(404),
+    };
+    if let Err(e) = request.respond(response) {
+        log::debug!("Scoreboard request dropped: {e}");
+    }
+}
+
+fn content_type(mime: &str) -> Header {
+    Header::from_bytes("Content-Type", format!("{mime}; charset=utf-8")).expect("Valid header")
+}
+
+/// Self-refreshing page showing the score table.
+pub fn html(board: Option<&Scoreboard>) -> String {
+    let mut page = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <meta http-equiv=\"refresh\" content=\"{REFRESH_SECONDS}\">\
+         <title>Whist Points</title>\
+         <style>body{{font-family:sans-serif;font-size:1.4em;margin:1em}}\
+         table{{border-collapse:collapse;width:100%}}\
+         td{{padding:.3em .5em;border-bottom:1px solid #ccc}}\
+         .n{{text-align:right}}</style></head><body>"
+    );
+    match board {
+        Some(board) => {
+            if let Err(e) = write_board(&mut page, board) {
+                log::error!("{e}");
+            }
+        }
+        None => page.push_str("<p>Waiting for the players.</p>"),
+    }
+    page.push_str("</body></html>\n");
+    page
+}
+
+/// Standings, then every hand played with the points of each seat.
+fn write_board(page: &mut String, board: &Scoreboard) -> fmt::Result {
+    writeln!(
+        page,
+        "<p>Hand {} &middot; Dealer: {}</p>",
+        board.hands + 1,
+        escape(&board.dealer)
+    )?;
+    if let Some(contract) = &board.last_contract {
+        writeln!(page, "<p>Last hand: {}</p>", escape(contract))?;
+    }
+    writeln!(page, "<table>")?;
+    for row in board.ranked() {
+        let delta = row
+            .last_delta
+            .map_or(String::new(), |delta| format!("{delta:+}"));
+        writeln!(
+            page,
+            "<tr><td>{}</td><td>{}</td><td>{}</td>\
+             <td class=\"n\"><b>{}</b></td><td class=\"n\">{delta}</td></tr>",
+            row.rank,
+            row.movement.arrow(),
+            escape(&row.name),
+            row.total,
+        )?;
+    }
+    writeln!(page, "</table>")?;
+
+    if board.history.is_empty() {
+        return Ok(());
+    }
+    write!(page, "<h2>Hands</h2><table><tr><th>#</th><th>Contract</th>")?;
+    for row in &board.rows {
+        write!(page, "<th class=\"n\">{}</th>", escape(&row.name))?;
+    }
+    writeln!(page, "</tr>")?;
+    for (idx, hand) in board.history.iter().enumerate() {
+        write!(
+            page,
+            "<tr><td>{}</td><td>{}</td>",
+            idx + 1,
+            escape(&hand.contract)
+        )?;
+        for score in hand.scores {
+            write!(page, "<td class=\"n\">{score:+}</td>")?;
+        }
+        writeln!(page, "</tr>")?;
+    }
+    writeln!(page, "</table>")
+}
+
+fn escape(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&#39;"),
+                _ => escaped.push(c),
+            }
+            escaped
+        })
+}
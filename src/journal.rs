@@ -0,0 +1,101 @@
+//! Append-only journal of hands.
+//!
+//! Saving the whole game after every hand would rewrite an ever growing
+//! state, so hands are appended to a journal, one entry per line, next to
+//! the last snapshot. Entries carry a sequence number; the snapshot records
+//! the last one it includes, so a crash between writing a snapshot and
+//! clearing the journal cannot replay a hand twice.
+//!
+//! Only the new entry is written when the backend can append in place; the
+//! others rewrite the journal, which [`COMPACT_EVERY`] keeps short. The
+//! storage is flushed after every entry: eframe's storage would otherwise
+//! keep the hand in memory until its next autosave.
+
+use log::error;
+use serde::{Deserialize, Serialize};
+use whist_game::HandRecap;
+
+use crate::{
+    save::{self, SaveError},
+    session,
+    state::AppState,
+    storage::{Format, StorageBackend, keys},
+};
+
+/// Number of journaled actions after which a snapshot is written.
+pub const COMPACT_EVERY: usize = 16;
+
+#[derive(Serialize)]
+pub enum ActionRef<'a> {
+    PushHand(&'a HandRecap),
+    RemoveLastHand,
+}
+
+#[derive(Deserialize)]
+enum Action {
+    PushHand(HandRecap),
+    RemoveLastHand,
+}
+
+#[derive(Serialize)]
+struct EntryRef<'a> {
+    seq: u64,
+    action: ActionRef<'a>,
+}
+
+#[derive(Deserialize)]
+struct Entry {
+    seq: u64,
+    action: Action,
+}
+
+/// Appends an action to the journal and flushes the storage.
+///
+/// # Errors
+///
+/// Fails if the action cannot be encoded or written.
+pub fn append(
+    storage: &mut dyn StorageBackend,
+    seq: u64,
+    action: ActionRef<'_>,
+) -> Result<(), SaveError> {
+    let mut line = save::encode(&EntryRef { seq, action }, storage.format())?;
+    line.push('\n');
+    storage.append(keys::JOURNAL, &line)?;
+    storage.flush()?;
+    Ok(())
+}
+
+/// Applies the journaled actions that are more recent than the snapshot
+/// `app` was loaded from. Returns the number of replayed actions.
+///
+/// A truncated entry requests a snapshot, so that the next entries are not
+/// appended after it.
+pub fn replay(journal: &str, format: Format, app: &mut AppState) -> usize {
+    let mut replayed = 0;
+    for line in journal.lines().filter(|line| !line.trim().is_empty()) {
+        let entry = match save::decode::<Entry>(line, format) {
+            Ok(entry) => entry,
+            Err(e) => {
+                // Only the action in flight during a crash can be truncated.
+                error!("Journal replay stopped: {e}");
+                app.snapshot_requested = true;
+                break;
+            }
+        };
+        if entry.seq <= app.journal_seq {
+            continue;
+        }
+        let action = match entry.action {
+            Action::PushHand(recap) => session::Action::RecordHand(recap),
+            Action::RemoveLastHand => session::Action::RemoveLastHand,
+        };
+        if let Err(e) = app.apply(action) {
+            error!("Journal entry {}: {e}", entry.seq);
+        }
+        app.journal_seq = entry.seq;
+        replayed += 1;
+    }
+    app.session.historic.take_events();
+    replayed
+}
@@ -0,0 +1,323 @@
+use crate::{
+    archive::{ArchivedGame, positions},
+    date::Date,
+};
+
+pub const INITIAL_RATING: f64 = 1500.0;
+const K_FACTOR: f64 = 32.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeagueEntry {
+    pub name: String,
+    pub rating: f64,
+    pub total_points: i32,
+    pub games: usize,
+    pub wins: usize,
+    positions_sum: usize,
+    /// Rating after each game the player took part in, with the game index.
+    pub history: Vec<(usize, f64)>,
+}
+
+impl LeagueEntry {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            rating: INITIAL_RATING,
+            total_points: 0,
+            games: 0,
+            wins: 0,
+            positions_sum: 0,
+            history: vec![],
+        }
+    }
+
+    pub fn average_position(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.positions_sum as f64 / self.games as f64
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.wins as f64 / self.games as f64
+    }
+}
+
+/// Archive figures of a player, computed by a storage holding the archive
+/// in tables, see [`crate::storage::StorageBackend::archive_summary`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerSummary {
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    pub points: i64,
+    pub average_position: f64,
+}
+
+/// How often a contract was played, and how often its contractors made it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractSummary {
+    pub name: String,
+    pub hands: u32,
+    pub won: u32,
+}
+
+/// Figures computed over the whole archive by the storage.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ArchiveSummary {
+    pub players: Vec<PlayerSummary>,
+    pub contracts: Vec<ContractSummary>,
+}
+
+/// Final result of a game, all the ratings need.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameResult {
+    /// Player credited with each seat.
+    pub players: [String; 4],
+    pub totals: [i16; 4],
+}
+
+/// Roster ranking computed from finished games.
+#[derive(Debug, Clone, Default)]
+pub struct Leaderboard {
+    pub entries: Vec<LeagueEntry>,
+}
+
+impl Leaderboard {
+    pub fn new<'a>(games: impl IntoIterator<Item = &'a ArchivedGame>) -> Self {
+        let mut results = vec![];
+        let mut points = vec![];
+        for game in games {
+            results.push(GameResult {
+                players: game.players.clone(),
+                totals: game.totals,
+            });
+            for hand in &game.hands {
+                for (name, score) in hand.occupants.iter().zip(hand.scores) {
+                    points.push((name.clone(), i32::from(score)));
+                }
+            }
+        }
+        Self::from_results(results, points)
+    }
+
+    /// Ranks the players from the results of the games in the order they were
+    /// played, `points` being scored by a player in any seat, substitutes
+    /// included.
+    pub fn from_results(
+        results: impl IntoIterator<Item = GameResult>,
+        points: impl IntoIterator<Item = (String, i32)>,
+    ) -> Self {
+        let mut board = Self::default();
+        for (game_idx, game) in results.into_iter().enumerate() {
+            board.add_game(game_idx, &game);
+        }
+        for (name, points) in points {
+            let idx = board.entry_idx(&name);
+            board
+                .entries
+                .get_mut(idx)
+                .expect("Just inserted")
+                .total_points += points;
+        }
+        board
+            .entries
+            .sort_by(|a, b| b.rating.total_cmp(&a.rating).then(a.name.cmp(&b.name)));
+        board
+    }
+
+    fn entry_idx(&mut self, name: &str) -> usize {
+        if let Some(idx) = self.entries.iter().position(|e| e.name == name) {
+            idx
+        } else {
+            self.entries.push(LeagueEntry::new(name));
+            self.entries.len() - 1
+        }
+    }
+
+    /// Elo adapted to a four player free-for-all: every seat plays a virtual
+    /// duel against each other seat, decided by the final totals.
+    fn add_game(&mut self, game_idx: usize, game: &GameResult) {
+        let seats: [usize; 4] =
+            std::array::from_fn(|seat| self.entry_idx(game.players.get(seat).expect("4 seats")));
+        let ratings = seats.map(|idx| self.entries.get(idx).expect("Just inserted").rating);
+        let positions = positions(&game.totals);
+
+        for (seat, &idx) in seats.iter().enumerate() {
+            let total = game.totals.get(seat).expect("4 seats");
+            let rating = ratings.get(seat).expect("4 seats");
+            let mut delta = 0.0;
+            for (other, other_total) in game.totals.iter().enumerate() {
+                if other == seat {
+                    continue;
+                }
+                let other_rating = ratings.get(other).expect("4 seats");
+                let expected = 1.0 / (1.0 + 10f64.powf((other_rating - rating) / 400.0));
+                let actual = match total.cmp(other_total) {
+                    std::cmp::Ordering::Greater => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Less => 0.0,
+                };
+                delta += actual - expected;
+            }
+
+            let position = *positions.get(seat).expect("4 seats");
+            let entry = self.entries.get_mut(idx).expect("Just inserted");
+            entry.rating += K_FACTOR / 3.0 * delta;
+            entry.games += 1;
+            entry.positions_sum += position;
+            if position == 1 {
+                entry.wins += 1;
+            }
+            entry.history.push((game_idx, entry.rating));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum StandingsRule {
+    /// Points awarded for finishing 1st, 2nd, 3rd and 4th.
+    PositionPoints([i32; 4]),
+    RawTotals,
+    /// Sum of the raw totals of the best `n` evenings of each player.
+    BestEvenings(usize),
+}
+
+impl Default for StandingsRule {
+    fn default() -> Self {
+        Self::PositionPoints([4, 2, 1, 0])
+    }
+}
+
+impl std::fmt::Display for StandingsRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PositionPoints(points) => write!(f, "Position points {points:?}"),
+            Self::RawTotals => write!(f, "Raw totals"),
+            Self::BestEvenings(n) => write!(f, "Best {n} evenings"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Season {
+    pub name: String,
+    pub start: Date,
+    /// Last day of the season, `None` while it is running.
+    pub end: Option<Date>,
+    /// Games in the archive when the season was closed, so that the games
+    /// finished later on its last day stay out of it.
+    #[serde(default)]
+    pub archived_games: Option<usize>,
+    pub rule: StandingsRule,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Standing {
+    pub name: String,
+    pub score: i32,
+    pub games: usize,
+    pub evenings: usize,
+}
+
+impl Season {
+    pub fn new(name: String, start: Date, rule: StandingsRule) -> Self {
+        Self {
+            name,
+            start,
+            end: None,
+            archived_games: None,
+            rule,
+        }
+    }
+
+    /// Ends the season on `today`, with the `archived_games` first games of
+    /// the archive.
+    pub fn close(&mut self, today: Date, archived_games: usize) {
+        self.end = Some(today);
+        self.archived_games = Some(archived_games);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.end.is_some()
+    }
+
+    pub fn contains(&self, date: Date) -> bool {
+        self.start <= date && self.end.is_none_or(|end| date <= end)
+    }
+
+    /// Games of `archive` played during the season.
+    pub fn games<'a>(&self, archive: &'a [ArchivedGame]) -> impl Iterator<Item = &'a ArchivedGame> {
+        archive
+            .iter()
+            .take(self.archived_games.unwrap_or(archive.len()))
+            .filter(|game| self.contains(game.finished_on))
+    }
+
+    pub fn standings(&self, games: &[ArchivedGame]) -> Vec<Standing> {
+        let mut tallies: Vec<Tally> = vec![];
+        for game in self.games(games) {
+            let positions = game.positions();
+            for (seat, name) in game.players.iter().enumerate() {
+                let total = i32::from(*game.totals.get(seat).expect("4 seats"));
+                let position = *positions.get(seat).expect("4 seats");
+                let idx = if let Some(idx) = tallies.iter().position(|t| t.name == *name) {
+                    idx
+                } else {
+                    tallies.push(Tally {
+                        name: name.clone(),
+                        ..Default::default()
+                    });
+                    tallies.len() - 1
+                };
+                let tally = tallies.get_mut(idx).expect("Just inserted");
+                tally.games += 1;
+                if let StandingsRule::PositionPoints(points) = self.rule {
+                    tally.position_points += points.get(position - 1).copied().unwrap_or_default();
+                }
+                match tally
+                    .evenings
+                    .iter_mut()
+                    .find(|(day, _)| *day == game.finished_on)
+                {
+                    Some((_, sum)) => *sum += total,
+                    None => tally.evenings.push((game.finished_on, total)),
+                }
+            }
+        }
+
+        let mut standings: Vec<Standing> = tallies
+            .into_iter()
+            .map(|mut tally| {
+                let score = match self.rule {
+                    StandingsRule::PositionPoints(_) => tally.position_points,
+                    StandingsRule::RawTotals => tally.evenings.iter().map(|(_, sum)| sum).sum(),
+                    StandingsRule::BestEvenings(n) => {
+                        tally.evenings.sort_by(|a, b| b.1.cmp(&a.1));
+                        tally.evenings.iter().take(n).map(|(_, sum)| sum).sum()
+                    }
+                };
+                Standing {
+                    name: tally.name,
+                    score,
+                    games: tally.games,
+                    evenings: tally.evenings.len(),
+                }
+            })
+            .collect();
+        standings.sort_by(|a, b| b.score.cmp(&a.score).then(a.name.cmp(&b.name)));
+        standings
+    }
+}
+
+#[derive(Default)]
+struct Tally {
+    name: String,
+    /// Raw total of each evening played.
+    evenings: Vec<(Date, i32)>,
+    games: usize,
+    position_points: i32,
+}
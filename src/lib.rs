@@ -1,13 +1,71 @@
 #![warn(clippy::all, rust_2018_idioms)]
+#[cfg(feature = "session")]
 use whist_game::{CollectedTricks, Score, Tricks};
 
+#[cfg(feature = "gui")]
 mod app;
+#[cfg(feature = "gui")]
 pub use app::WhistApp;
+#[cfg(feature = "session")]
+pub use session::PlayersState;
+#[cfg(feature = "session")]
+pub mod archive;
+#[cfg(feature = "session")]
+pub mod bids;
+#[cfg(all(feature = "cli", not(target_arch = "wasm32")))]
+pub mod cli;
+#[cfg(feature = "session")]
+pub mod date;
+#[cfg(feature = "session")]
+mod error;
+#[cfg(feature = "session")]
+pub mod heatmap;
+#[cfg(feature = "session")]
+pub mod historic;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub mod http;
+#[cfg(feature = "session")]
+mod journal;
+#[cfg(feature = "session")]
+pub mod league;
+#[cfg(feature = "session")]
+pub mod partnership;
+#[cfg(feature = "gui")]
+pub mod qr;
+#[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+pub mod relay;
+#[cfg(feature = "session")]
+pub mod replay;
+#[cfg(feature = "session")]
+pub mod save;
+#[cfg(feature = "session")]
+pub mod scoreboard;
+#[cfg(feature = "session")]
+pub mod session;
+#[cfg(feature = "session")]
+pub mod settlement;
+#[cfg(feature = "session")]
+pub mod share;
+#[cfg(all(feature = "sqlite", not(target_arch = "wasm32")))]
+pub mod sqlite;
+#[cfg(feature = "session")]
+pub mod state;
+#[cfg(feature = "session")]
+pub mod storage;
+#[cfg(feature = "sync")]
+pub mod sync;
+#[cfg(feature = "session")]
+pub mod tournament;
+#[cfg(all(feature = "tui", not(target_arch = "wasm32")))]
+pub mod tui;
+#[cfg(feature = "gui")]
 mod ui;
 
+#[cfg(feature = "session")]
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 struct Queens;
 
+#[cfg(feature = "session")]
 #[typetag::serde]
 impl Score for Queens {
     fn min_tricks(&self) -> Tricks {
@@ -0,0 +1,158 @@
+//! Who plays well together and against whom, from the contractors of each
+//! hand.
+
+use crate::historic::HandsHistoric;
+
+/// Hands two people played together as contractors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PairStats {
+    pub hands: usize,
+    /// Hands where their combined points were positive.
+    pub won: usize,
+    /// Points of both, over all their hands.
+    pub points: i32,
+}
+
+impl PairStats {
+    /// Share of the hands won, `None` before any hand.
+    pub fn success_rate(&self) -> Option<f64> {
+        ratio(self.won, self.hands)
+    }
+
+    /// Combined points per hand, `None` before any hand.
+    pub fn average_points(&self) -> Option<f64> {
+        ratio_i32(self.points, self.hands)
+    }
+}
+
+/// Hands a person defended against another one's contract.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeadToHead {
+    pub hands: usize,
+    /// Hands where the contractor lost points.
+    pub defended: usize,
+    /// Points of the defender, over all these hands.
+    pub points: i32,
+}
+
+impl HeadToHead {
+    /// Share of the contracts defeated, `None` before any hand.
+    pub fn defence_rate(&self) -> Option<f64> {
+        ratio(self.defended, self.hands)
+    }
+
+    /// Points of the defender per hand, `None` before any hand.
+    pub fn average_points(&self) -> Option<f64> {
+        ratio_i32(self.points, self.hands)
+    }
+}
+
+fn ratio(count: usize, hands: usize) -> Option<f64> {
+    (hands > 0).then(|| count as f64 / hands as f64)
+}
+
+fn ratio_i32(points: i32, hands: usize) -> Option<f64> {
+    (hands > 0).then(|| f64::from(points) / hands as f64)
+}
+
+/// Pairs and head-to-heads of everyone who sat at the table, credited to
+/// whoever was actually sitting at each seat.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Partnerships {
+    /// People in the order they first played.
+    pub names: Vec<String>,
+    /// `names.len()` squared, symmetric.
+    pairs: Vec<PairStats>,
+    /// `names.len()` squared, defender first.
+    head_to_head: Vec<HeadToHead>,
+}
+
+impl Partnerships {
+    pub fn new(historic: &HandsHistoric, current: &[String]) -> Self {
+        let mut names: Vec<String> = vec![];
+        let mut seats_of_hands = vec![];
+        for (hand_idx, (hand, _)) in historic.into_iter().enumerate() {
+            let people: Vec<usize> = (0..4)
+                .map(|seat| {
+                    let current = current.get(seat).map_or("", String::as_str);
+                    let name = historic.occupant(seat, hand_idx, current);
+                    names.iter().position(|n| n == name).unwrap_or_else(|| {
+                        names.push(name.to_owned());
+                        names.len() - 1
+                    })
+                })
+                .collect();
+            seats_of_hands.push((hand, people));
+        }
+
+        let size = names.len();
+        let mut partnerships = Self {
+            names,
+            pairs: vec![PairStats::default(); size * size],
+            head_to_head: vec![HeadToHead::default(); size * size],
+        };
+        for (hand, people) in seats_of_hands {
+            let contractors: Vec<usize> = hand
+                .contractors_tricks
+                .iter()
+                .map(|(id, _)| id.idx())
+                .collect();
+            let score = |seat: usize| i32::from(hand.scores.get(seat).copied().unwrap_or_default());
+            let person = |seat: usize| *people.get(seat).expect("4 seats");
+
+            for (idx, &first) in contractors.iter().enumerate() {
+                for &second in contractors.iter().skip(idx + 1) {
+                    let points = score(first) + score(second);
+                    for (a, b) in [(first, second), (second, first)] {
+                        let pair = partnerships.pair_mut(person(a), person(b));
+                        pair.hands += 1;
+                        pair.points += points;
+                        if points > 0 {
+                            pair.won += 1;
+                        }
+                    }
+                }
+                for defender in (0..4).filter(|seat| !contractors.contains(seat)) {
+                    let entry = partnerships.head_to_head_mut(person(defender), person(first));
+                    entry.hands += 1;
+                    entry.points += score(defender);
+                    if score(first) < 0 {
+                        entry.defended += 1;
+                    }
+                }
+            }
+        }
+        partnerships
+    }
+
+    fn index(&self, first: usize, second: usize) -> usize {
+        first * self.names.len() + second
+    }
+
+    fn pair_mut(&mut self, first: usize, second: usize) -> &mut PairStats {
+        let idx = self.index(first, second);
+        self.pairs.get_mut(idx).expect("Known people")
+    }
+
+    fn head_to_head_mut(&mut self, defender: usize, contractor: usize) -> &mut HeadToHead {
+        let idx = self.index(defender, contractor);
+        self.head_to_head.get_mut(idx).expect("Known people")
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// Hands `first` and `second` played together, `None` for unknown people.
+    pub fn pair(&self, first: &str, second: &str) -> Option<&PairStats> {
+        let idx = self.index(self.position(first)?, self.position(second)?);
+        self.pairs.get(idx)
+    }
+
+    /// How `defender` fared against the contracts of `contractor`, `None` for
+    /// unknown people.
+    pub fn head_to_head(&self, defender: &str, contractor: &str) -> Option<&HeadToHead> {
+        let idx = self.index(self.position(defender)?, self.position(contractor)?);
+        self.head_to_head.get(idx)
+    }
+}
@@ -0,0 +1,87 @@
+//! QR codes of shared game links.
+
+use qrcodegen::{DataTooLong, QrCode, QrCodeEcc};
+use thiserror::Error;
+
+/// Blank modules around the code, as required by readers.
+const QUIET_ZONE: usize = 4;
+
+#[derive(Debug, Error)]
+pub enum QrError {
+    #[error("The game is too long for a QR code, share the link instead")]
+    TooLong(#[from] DataTooLong),
+    #[error("Unable to encode the image: {0}")]
+    Png(#[from] png::EncodingError),
+}
+
+/// Dark and light modules of a QR code, quiet zone included.
+#[derive(Debug, Clone)]
+pub struct QrImage {
+    size: usize,
+    dark: Vec<bool>,
+}
+
+impl QrImage {
+    /// Encodes `text` with the lowest error correction, leaving as much room
+    /// as possible for long games.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `text` does not fit in the largest QR code.
+    pub fn new(text: &str) -> Result<Self, QrError> {
+        let code = QrCode::encode_text(text, QrCodeEcc::Low)?;
+        let inner = usize::try_from(code.size()).expect("QR codes are at most 177 modules wide");
+        let size = inner + 2 * QUIET_ZONE;
+        let dark = (0..size * size)
+            .map(|idx| {
+                let x = (idx % size).checked_sub(QUIET_ZONE);
+                let y = (idx / size).checked_sub(QUIET_ZONE);
+                match (x, y) {
+                    (Some(x), Some(y)) if x < inner && y < inner => {
+                        code.get_module(x as i32, y as i32)
+                    }
+                    _ => false,
+                }
+            })
+            .collect();
+        Ok(Self { size, dark })
+    }
+
+    /// Width and height in modules.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.dark
+            .get(y * self.size + x)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Grayscale PNG with `scale` pixels per module.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the image cannot be encoded.
+    pub fn to_png(&self, scale: usize) -> Result<Vec<u8>, QrError> {
+        let scale = scale.max(1);
+        let side = self.size * scale;
+        let pixels: Vec<u8> = (0..side * side)
+            .map(|idx| {
+                let (x, y) = (idx % side / scale, idx / side / scale);
+                if self.is_dark(x, y) { 0 } else { 255 }
+            })
+            .collect();
+
+        let mut png = vec![];
+        let side = u32::try_from(side).expect("A QR code image fits in u32 pixels");
+        let mut encoder = png::Encoder::new(&mut png, side, side);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&pixels)?;
+        writer.finish()?;
+        Ok(png)
+    }
+}
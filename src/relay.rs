@@ -0,0 +1,245 @@
+//! Relay ordering the operations of every room, behind the `sync` feature.
+//!
+//! The relay knows nothing of whist: it numbers the operations of a room,
+//! rejects those that no longer apply and forwards the others to every device
+//! of the room. Rooms only live in memory; after a restart, the first device
+//! to come back publishes its game again.
+
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        Arc, Mutex, PoisonError,
+        mpsc::{self, Receiver, Sender},
+    },
+    time::Duration,
+};
+
+use log::{debug, error, info};
+use tungstenite::{Message, WebSocket};
+
+use crate::sync::{ClientMessage, Origin, PROTOCOL_VERSION, RelayMessage};
+
+/// How long a read waits before messages for the device are looked at.
+const POLL: Duration = Duration::from_millis(50);
+
+#[derive(Default)]
+struct Room {
+    log: Vec<RelayMessage>,
+    /// Number of hands once every logged operation is applied.
+    hands: usize,
+    devices: Vec<Sender<String>>,
+}
+
+impl Room {
+    fn seq(&self) -> u64 {
+        self.log.len() as u64
+    }
+
+    fn contains(&self, origin: Origin) -> bool {
+        self.log
+            .iter()
+            .any(|entry| matches!(entry, RelayMessage::Applied { origin: o, .. } if *o == origin))
+    }
+
+    fn broadcast(&mut self, message: &RelayMessage) {
+        match serde_json::to_string(message) {
+            // Devices whose connection ended are forgotten.
+            Ok(text) => self
+                .devices
+                .retain(|device| device.send(text.clone()).is_ok()),
+            Err(e) => error!("{e}"),
+        }
+    }
+}
+
+type Rooms = Arc<Mutex<HashMap<String, Room>>>;
+
+pub struct Relay {
+    listener: TcpListener,
+    rooms: Rooms,
+}
+
+impl Relay {
+    /// # Errors
+    ///
+    /// Fails if the address cannot be bound.
+    pub fn bind(addr: impl ToSocketAddrs) -> std::io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            rooms: Rooms::default(),
+        })
+    }
+
+    /// # Errors
+    ///
+    /// Fails if the socket has no address, which should not happen once bound.
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves devices until the process ends, one thread per connection.
+    pub fn run(&self) {
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    error!("{e}");
+                    continue;
+                }
+            };
+            let rooms = Arc::clone(&self.rooms);
+            if let Err(e) = std::thread::Builder::new()
+                .name("relay connection".to_owned())
+                .spawn(move || connection(stream, &rooms))
+            {
+                error!("{e}");
+            }
+        }
+    }
+
+    /// Serves devices from a background thread, e.g. in tests.
+    pub fn spawn(self) {
+        std::thread::spawn(move || self.run());
+    }
+}
+
+fn connection(stream: TcpStream, rooms: &Rooms) {
+    let peer = stream
+        .peer_addr()
+        .map_or_else(|_e| "unknown".to_owned(), |addr| addr.to_string());
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(e) => {
+            debug!("{peer}: {e}");
+            return;
+        }
+    };
+    if let Err(e) = socket.get_mut().set_read_timeout(Some(POLL)) {
+        error!("{e}");
+        return;
+    }
+    info!("{peer} connected");
+    let reason = serve(&mut socket, rooms);
+    info!("{peer} left: {reason}");
+}
+
+/// Device of a connection once it joined a room.
+struct Device {
+    room: String,
+    client: u64,
+    sender: Sender<String>,
+    messages: Receiver<String>,
+}
+
+fn serve(socket: &mut WebSocket<TcpStream>, rooms: &Rooms) -> String {
+    let mut device: Option<Device> = None;
+    loop {
+        if let Some(device) = &device {
+            while let Ok(text) = device.messages.try_recv() {
+                if let Err(e) = socket.send(Message::text(text)) {
+                    return e.to_string();
+                }
+            }
+        }
+        let text = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => return "Closed".to_owned(),
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+            {
+                continue;
+            }
+            Err(e) => return e.to_string(),
+        };
+        let message = match serde_json::from_str::<ClientMessage>(&text) {
+            Ok(message) => message,
+            Err(e) => return format!("Unreadable message: {e}"),
+        };
+        let mut rooms = rooms.lock().unwrap_or_else(PoisonError::into_inner);
+        match message {
+            ClientMessage::Join {
+                version,
+                room,
+                client,
+                since,
+            } => {
+                if version != PROTOCOL_VERSION {
+                    let error = RelayMessage::Error(format!(
+                        "Protocol version {version} instead of {PROTOCOL_VERSION}"
+                    ));
+                    if let Ok(text) = serde_json::to_string(&error)
+                        && let Err(e) = socket.send(Message::text(text))
+                    {
+                        debug!("{e}");
+                    }
+                    return "Unsupported version".to_owned();
+                }
+                // Everything goes through the channel, so that the backlog is
+                // sent before what other devices submit next.
+                let (sender, messages) = mpsc::channel();
+                let entry = rooms.entry(room.clone()).or_default();
+                let welcome = RelayMessage::Welcome { seq: entry.seq() };
+                let backlog = entry
+                    .log
+                    .iter()
+                    .skip(usize::try_from(since).unwrap_or(usize::MAX));
+                for message in std::iter::once(&welcome).chain(backlog) {
+                    match serde_json::to_string(message) {
+                        Ok(text) => {
+                            if sender.send(text).is_err() {
+                                return "Disconnected".to_owned();
+                            }
+                        }
+                        Err(e) => error!("{e}"),
+                    }
+                }
+                entry.devices.push(sender.clone());
+                device = Some(Device {
+                    room,
+                    client,
+                    sender,
+                    messages,
+                });
+            }
+            ClientMessage::Submit { id, op } => {
+                let Some(device) = &device else {
+                    return "Submitted before joining".to_owned();
+                };
+                let room = rooms.entry(device.room.clone()).or_default();
+                let origin = Origin {
+                    client: device.client,
+                    id,
+                };
+                // Sent again after a reconnection, already in the backlog.
+                if room.contains(origin) {
+                    continue;
+                }
+                match op.apply_to(room.hands) {
+                    Some(hands) => {
+                        room.hands = hands;
+                        let applied = RelayMessage::Applied {
+                            seq: room.seq() + 1,
+                            origin,
+                            op,
+                        };
+                        room.broadcast(&applied);
+                        room.log.push(applied);
+                    }
+                    None => match serde_json::to_string(&RelayMessage::Rejected { id }) {
+                        // Through the channel, after the operation that made
+                        // it fail.
+                        Ok(text) => {
+                            if device.sender.send(text).is_err() {
+                                return "Disconnected".to_owned();
+                            }
+                        }
+                        Err(e) => error!("{e}"),
+                    },
+                }
+            }
+        }
+    }
+}
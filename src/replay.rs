@@ -0,0 +1,179 @@
+//! Hand by hand replay of a game, for the discussion after it.
+
+use std::fmt::Write as _;
+
+use crate::{
+    archive::ArchivedGame,
+    historic::HandsHistoric,
+    scoreboard::{HandRow, Scoreboard},
+};
+
+#[derive(Debug, Clone)]
+struct ReplayHand {
+    contract: String,
+    scores: [i16; 4],
+    /// Who was sitting at each seat during this hand.
+    occupants: Vec<String>,
+}
+
+/// A game and a position in it, from before the first hand (0) to after the
+/// last one.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    names: Vec<String>,
+    hands: Vec<ReplayHand>,
+    /// Running totals after each hand.
+    totals: Vec<[i16; 4]>,
+    position: usize,
+}
+
+impl Replay {
+    /// Replay of the game being played, `names` being the current seats.
+    pub fn new(names: &[String], historic: &HandsHistoric) -> Self {
+        let hands = historic
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (hand, _))| ReplayHand {
+                contract: hand.gamemode_name.clone(),
+                scores: hand.scores,
+                occupants: historic.seat_names_at(idx, names),
+            })
+            .collect();
+        let totals = historic.into_iter().map(|(_, totals)| *totals).collect();
+        Self {
+            names: names.to_vec(),
+            hands,
+            totals,
+            position: 0,
+        }
+    }
+
+    pub fn from_archive(game: &ArchivedGame) -> Self {
+        let hands: Vec<ReplayHand> = game
+            .hands
+            .iter()
+            .map(|hand| ReplayHand {
+                contract: hand.gamemode_name.clone(),
+                scores: hand.scores,
+                occupants: hand.occupants.to_vec(),
+            })
+            .collect();
+        let totals = hands
+            .iter()
+            .scan([0_i16; 4], |totals, hand| {
+                for (total, score) in totals.iter_mut().zip(hand.scores) {
+                    *total = total.saturating_add(score);
+                }
+                Some(*totals)
+            })
+            .collect();
+        let names = game
+            .hands
+            .first()
+            .map_or_else(|| game.players.to_vec(), |hand| hand.occupants.to_vec());
+        Self {
+            names,
+            hands,
+            totals,
+            position: 0,
+        }
+    }
+
+    /// Number of hands of the game.
+    pub fn len(&self) -> usize {
+        self.hands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hands.is_empty()
+    }
+
+    /// Hands played at this point of the replay.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_at_start(&self) -> bool {
+        self.position == 0
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.position == self.len()
+    }
+
+    /// Moves to `position`, within the game.
+    pub fn seek(&mut self, position: usize) {
+        self.position = position.min(self.len());
+    }
+
+    /// Plays the next hand, returns `false` at the end of the game.
+    pub fn forward(&mut self) -> bool {
+        let moved = !self.is_at_end();
+        self.seek(self.position + 1);
+        moved
+    }
+
+    /// Takes the last played hand back, returns `false` at the start.
+    pub fn back(&mut self) -> bool {
+        let moved = !self.is_at_start();
+        self.seek(self.position.saturating_sub(1));
+        moved
+    }
+
+    /// Standings at this point: totals, ranks and how the last hand moved
+    /// them.
+    pub fn board(&self) -> Scoreboard {
+        let last = self.position.checked_sub(1);
+        let hand = last.and_then(|idx| self.hands.get(idx));
+        let names = hand.map_or(&self.names, |hand| &hand.occupants);
+        let totals = last
+            .and_then(|idx| self.totals.get(idx))
+            .copied()
+            .unwrap_or_default();
+        Scoreboard {
+            history: self
+                .hands
+                .iter()
+                .take(self.position)
+                .map(|hand| HandRow {
+                    contract: hand.contract.clone(),
+                    scores: hand.scores,
+                })
+                .collect(),
+            ..Scoreboard::after(
+                names,
+                totals,
+                hand.map(|hand| (hand.contract.as_str(), hand.scores)),
+                self.position,
+            )
+        }
+    }
+
+    /// Text summary of this point of the game, to paste in a chat.
+    pub fn snapshot(&self) -> String {
+        let board = self.board();
+        let mut out = match &board.last_contract {
+            Some(contract) => format!(
+                "After hand {} of {}: {contract}\n",
+                self.position,
+                self.len()
+            ),
+            None => format!("Before the first of {} hands\n", self.len()),
+        };
+        let width = board
+            .rows
+            .iter()
+            .map(|row| row.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        for row in board.ranked() {
+            let _: std::fmt::Result =
+                write!(out, "{}. {:<width$} {:>5}", row.rank, row.name, row.total);
+            if let Some(delta) = row.last_delta {
+                let _: std::fmt::Result = write!(out, " ({delta:+}) {}", row.movement.arrow());
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
@@ -0,0 +1,475 @@
+//! Versioned save format.
+//!
+//! The app state is stored in a small envelope carrying the format version.
+//! Each incompatible change to the saved types bumps [`SAVE_VERSION`] and
+//! adds a migration from the previous version, so that old saves are
+//! upgraded step by step instead of being silently replaced by a new game.
+//!
+//! Version 0 is the bare app state written by 0.2.4 and earlier. Since
+//! version 1 the current game is a [`Session`] of its own, and the archive,
+//! the settings and the roster are separate documents next to it, see
+//! [`crate::storage::keys`].
+
+use ron::value::RawValue;
+use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
+use thiserror::Error;
+use whist_game::HandRecap;
+
+use crate::{
+    PlayersState,
+    archive::ArchivedGame,
+    historic::HandsHistoric,
+    league::Season,
+    session::Session,
+    settlement::SettlementConfig,
+    state::AppState,
+    storage::{Format, StorageBackend, StorageError, StorageRead, keys},
+};
+
+/// Version written by this build.
+pub const SAVE_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("Unreadable save: {0}")]
+    Decode(#[from] ron::error::SpannedError),
+    #[error("Unable to encode save: {0}")]
+    Encode(#[from] ron::Error),
+    #[error("Invalid JSON save: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Save version {0} is newer than this application")]
+    UnsupportedVersion(u32),
+    #[error("The saved {0} could not be read: {1}")]
+    Unreadable(String, String),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+}
+
+/// Encodes any persisted document.
+///
+/// # Errors
+///
+/// Fails if the value cannot be represented in `format`.
+pub fn encode<T: Serialize>(value: &T, format: Format) -> Result<String, SaveError> {
+    Ok(match format {
+        Format::Ron => ron::ser::to_string(value)?,
+        Format::Json => serde_json::to_string(value)?,
+    })
+}
+
+/// Decodes any persisted document.
+///
+/// # Errors
+///
+/// Fails if `raw` is not a valid `T` in `format`.
+pub fn decode<T: DeserializeOwned>(raw: &str, format: Format) -> Result<T, SaveError> {
+    Ok(match format {
+        Format::Ron => ron::from_str(raw)?,
+        Format::Json => serde_json::from_str(raw)?,
+    })
+}
+
+#[derive(Serialize)]
+struct EnvelopeRef<'a> {
+    version: u32,
+    app: &'a AppState,
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    app: AppState,
+}
+
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+/// Finished games and seasons.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Archive {
+    pub games: Vec<ArchivedGame>,
+    pub seasons: Vec<Season>,
+}
+
+/// User preferences.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub settlement: SettlementConfig,
+    /// Address of the web app that shared links open.
+    pub web_url: String,
+}
+
+/// Every player name seen so far, offered when setting up a new game.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Roster {
+    pub players: Vec<String>,
+}
+
+/// Encodes the app state in the current save format.
+///
+/// # Errors
+///
+/// Fails if the state cannot be encoded in `format`.
+pub fn to_string(app: &AppState, format: Format) -> Result<String, SaveError> {
+    encode(
+        &EnvelopeRef {
+            version: SAVE_VERSION,
+            app,
+        },
+        format,
+    )
+}
+
+/// Version of a raw save, saves written before versioning being version 0.
+pub fn version(raw: &str, format: Format) -> u32 {
+    decode::<VersionProbe>(raw, format).map_or(0, |probe| probe.version)
+}
+
+/// Decodes a save of any known version, migrating it to the current one.
+///
+/// # Errors
+///
+/// Fails if the save is not valid for its version or was written by a newer
+/// version of the application.
+pub fn load(raw: &str, format: Format) -> Result<AppState, SaveError> {
+    match version(raw, format) {
+        0 => Ok(decode::<v0::WhistApp>(raw, format)?.into()),
+        SAVE_VERSION => Ok(decode::<Envelope>(raw, format)?.app),
+        version => Err(SaveError::UnsupportedVersion(version)),
+    }
+}
+
+/// Reads the archive, settings and roster from their separate documents,
+/// when they exist.
+///
+/// # Errors
+///
+/// Fails with the key and raw content of the first unreadable document.
+pub fn load_documents(
+    storage: &dyn StorageRead,
+    app: &mut AppState,
+) -> Result<(), (Quarantine, SaveError)> {
+    let format = storage.format();
+    let quarantine = |key: &str, raw: String, e: SaveError| {
+        let quarantine = Quarantine {
+            raw,
+            error: e.to_string(),
+            format,
+            key: key.to_owned(),
+        };
+        (quarantine, e)
+    };
+    if let Some(raw) = storage.get(keys::ARCHIVE) {
+        match decode::<Archive>(&raw, format) {
+            Ok(archive) => {
+                app.archive = archive.games;
+                app.seasons = archive.seasons;
+            }
+            Err(e) => return Err(quarantine(keys::ARCHIVE, raw, e)),
+        }
+    }
+    if let Some(raw) = storage.get(keys::SETTINGS) {
+        match decode::<Settings>(&raw, format) {
+            Ok(settings) => {
+                app.settlement = settings.settlement;
+                app.web_url = settings.web_url;
+            }
+            Err(e) => return Err(quarantine(keys::SETTINGS, raw, e)),
+        }
+    }
+    if let Some(raw) = storage.get(keys::ROSTER) {
+        match decode::<Roster>(&raw, format) {
+            Ok(roster) => app.roster = roster.players,
+            Err(e) => return Err(quarantine(keys::ROSTER, raw, e)),
+        }
+    }
+    Ok(())
+}
+
+/// Writes the app state with its separate documents and empties the journal
+/// the state now includes.
+///
+/// # Errors
+///
+/// Fails if a document cannot be encoded or written. Documents written
+/// before the failure are kept.
+pub fn write_snapshot(app: &AppState, storage: &mut dyn StorageBackend) -> Result<(), SaveError> {
+    let format = storage.format();
+    let archive = Archive {
+        games: app.archive.clone(),
+        seasons: app.seasons.clone(),
+    };
+    let settings = Settings {
+        settlement: app.settlement.clone(),
+        web_url: app.web_url.clone(),
+    };
+    let roster = Roster {
+        players: app.roster.clone(),
+    };
+    storage.set(keys::ARCHIVE, encode(&archive, format)?)?;
+    storage.set(keys::SETTINGS, encode(&settings, format)?)?;
+    storage.set(keys::ROSTER, encode(&roster, format)?)?;
+    storage.set(keys::APP, to_string(app, format)?)?;
+    storage.remove(keys::JOURNAL)?;
+    storage.flush()?;
+    Ok(())
+}
+
+/// A save that could not be decoded, kept aside until the user decides what
+/// to do with it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quarantine {
+    pub raw: String,
+    pub error: String,
+    #[serde(default)]
+    pub format: Format,
+    /// Document the data was read from.
+    #[serde(default = "app_key")]
+    pub key: String,
+}
+
+impl Quarantine {
+    /// Only the current game can be partially salvaged, other documents
+    /// are plain lists that are either readable or not.
+    pub fn can_salvage(&self) -> bool {
+        self.key == keys::APP
+    }
+}
+
+fn app_key() -> String {
+    keys::APP.to_owned()
+}
+
+fn some_raw<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Box<RawValue>>, D::Error> {
+    Box::<RawValue>::deserialize(deserializer).map(Some)
+}
+
+/// Top level fields of a save, left undecoded.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RawApp {
+    /// The game since version 1, holding the players and the hands.
+    #[serde(deserialize_with = "some_raw")]
+    session: Option<Box<RawValue>>,
+    #[serde(deserialize_with = "some_raw")]
+    players_state: Option<Box<RawValue>>,
+    #[serde(deserialize_with = "some_raw")]
+    historic: Option<Box<RawValue>>,
+    #[serde(deserialize_with = "some_raw")]
+    tournament: Option<Box<RawValue>>,
+}
+
+#[derive(Deserialize)]
+struct RawEnvelope {
+    app: Box<RawValue>,
+}
+
+#[derive(Deserialize)]
+struct RawHistoric {
+    list: Vec<Box<RawValue>>,
+}
+
+/// The part of a hand that does not depend on the contract implementations.
+#[derive(Deserialize)]
+struct HandScores {
+    scores: [i16; 4],
+    gamemode_name: String,
+}
+
+impl From<HandScores> for HandRecap {
+    fn from(hand: HandScores) -> Self {
+        Self {
+            scores: hand.scores,
+            gamemode_name: hand.gamemode_name,
+            contractors_tricks: vec![],
+            bid: None,
+        }
+    }
+}
+
+/// Whatever could be decoded from an unreadable save, `hands` being used
+/// only when the whole historic could not.
+#[derive(Default)]
+struct Parts {
+    players_state: Option<PlayersState>,
+    historic: Option<HandsHistoric>,
+    hands: Vec<HandRecap>,
+    tournament: Option<Option<crate::tournament::Tournament>>,
+}
+
+fn field<T: DeserializeOwned>(raw: Option<&RawValue>) -> Option<T> {
+    raw?.into_rust().ok()
+}
+
+fn ron_parts(raw: &str) -> Result<Parts, SaveError> {
+    let mut app: RawApp = match ron::from_str::<RawEnvelope>(raw) {
+        Ok(envelope) => envelope.app.into_rust()?,
+        Err(_) => ron::from_str(raw)?,
+    };
+    if let Some(game) = field::<RawApp>(app.session.as_deref()) {
+        app.players_state = game.players_state;
+        app.historic = game.historic;
+    }
+    let historic = field(app.historic.as_deref());
+    let hands = if historic.is_some() {
+        vec![]
+    } else {
+        field::<RawHistoric>(app.historic.as_deref())
+            .map_or(vec![], |h| h.list)
+            .into_iter()
+            .filter_map(|hand| {
+                hand.into_rust::<HandRecap>()
+                    .ok()
+                    .or_else(|| hand.into_rust::<HandScores>().ok().map(Into::into))
+            })
+            .collect()
+    };
+    Ok(Parts {
+        players_state: field(app.players_state.as_deref()),
+        historic,
+        hands,
+        tournament: field(app.tournament.as_deref()),
+    })
+}
+
+fn json_field<T: DeserializeOwned>(app: &serde_json::Value, name: &str) -> Option<T> {
+    T::deserialize(app.get(name)?).ok()
+}
+
+fn json_parts(raw: &str) -> Result<Parts, SaveError> {
+    let mut app: serde_json::Value = serde_json::from_str(raw)?;
+    if let Some(inner) = app.get_mut("app") {
+        app = inner.take();
+    }
+    if let Some(game) = app.get_mut("session").map(serde_json::Value::take)
+        && let Some(fields) = app.as_object_mut()
+    {
+        for name in ["players_state", "historic"] {
+            if let Some(value) = game.get(name) {
+                fields.insert(name.to_owned(), value.clone());
+            }
+        }
+    }
+    let historic = json_field(&app, "historic");
+    let hands = match (&historic, app.pointer("/historic/list")) {
+        (None, Some(serde_json::Value::Array(list))) => list
+            .iter()
+            .filter_map(|hand| {
+                HandRecap::deserialize(hand)
+                    .ok()
+                    .or_else(|| HandScores::deserialize(hand).ok().map(Into::into))
+            })
+            .collect(),
+        _ => vec![],
+    };
+    Ok(Parts {
+        players_state: json_field(&app, "players_state"),
+        historic,
+        hands,
+        tournament: json_field(&app, "tournament"),
+    })
+}
+
+/// Recovers whatever can still be decoded from an unreadable save: players
+/// and hand scores are kept, contract objects are replaced by the defaults.
+///
+/// # Errors
+///
+/// Fails if the save is not even syntactically valid in `format`.
+pub fn salvage(raw: &str, format: Format) -> Result<AppState, SaveError> {
+    let parts = match format {
+        Format::Ron => ron_parts(raw)?,
+        Format::Json => json_parts(raw)?,
+    };
+
+    let historic = parts.historic.unwrap_or_else(|| {
+        let mut historic = HandsHistoric::default();
+        for hand in parts.hands {
+            // Hands beyond totals out of range are as lost as unreadable ones.
+            if historic.push(hand).is_err() {
+                break;
+            }
+        }
+        historic
+    });
+
+    let mut salvaged = AppState {
+        session: Session {
+            players_state: parts.players_state.unwrap_or_default(),
+            historic,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    if let Some(tournament) = parts.tournament {
+        salvaged.tournament = tournament;
+    }
+    Ok(salvaged)
+}
+
+/// Layout of the saves written by 0.2.4 and earlier: the bare app state.
+mod v0 {
+    use serde::Deserialize;
+    use whist_game::{HandRecap, contracts::Contract};
+
+    use crate::{
+        historic::HandsHistoric,
+        session::{PlayersState, Session},
+        state::AppState,
+    };
+
+    #[derive(Deserialize)]
+    #[serde(default)]
+    pub struct WhistApp {
+        pub players_state: PlayersState,
+        pub player_field: String,
+        pub contracts: Vec<Contract>,
+        pub current_contract_idx: usize,
+        pub historic: Historic,
+    }
+
+    impl Default for WhistApp {
+        fn default() -> Self {
+            let session = Session::default();
+            Self {
+                players_state: session.players_state,
+                player_field: String::new(),
+                contracts: session.contracts,
+                current_contract_idx: 0,
+                historic: Historic::default(),
+            }
+        }
+    }
+
+    #[derive(Default, Deserialize)]
+    pub struct Historic {
+        list: Vec<HandRecap>,
+    }
+
+    impl From<WhistApp> for AppState {
+        fn from(old: WhistApp) -> Self {
+            // Cumulative scores are rebuilt from the hands rather than trusted.
+            let mut historic = HandsHistoric::default();
+            for hand in old.historic.list {
+                if historic.push(hand).is_err() {
+                    break;
+                }
+            }
+            Self {
+                session: Session {
+                    players_state: old.players_state,
+                    historic,
+                    contracts: old.contracts,
+                    draft: None,
+                },
+                player_field: old.player_field,
+                current_contract_idx: old.current_contract_idx,
+                ..Default::default()
+            }
+        }
+    }
+}
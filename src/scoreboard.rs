@@ -0,0 +1,124 @@
+//! Read-only summary of the current game, for spectators.
+
+use serde::Serialize;
+
+use crate::{archive::positions, historic::HandsHistoric};
+
+/// How a seat's rank changed with the last hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Movement {
+    Up,
+    Down,
+    Same,
+}
+
+impl Movement {
+    pub fn arrow(self) -> &'static str {
+        match self {
+            Self::Up => "▲",
+            Self::Down => "▼",
+            Self::Same => "▶",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScoreRow {
+    pub name: String,
+    pub total: i16,
+    /// Points won or lost in the last hand.
+    pub last_delta: Option<i16>,
+    /// Starting at 1, tied seats sharing the best rank.
+    pub rank: usize,
+    pub movement: Movement,
+}
+
+/// A played hand, as listed under the standings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HandRow {
+    pub contract: String,
+    /// Points of each seat, in seat order.
+    pub scores: [i16; 4],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Scoreboard {
+    /// One row per seat, in seat order.
+    pub rows: Vec<ScoreRow>,
+    pub hands: usize,
+    pub last_contract: Option<String>,
+    pub dealer: String,
+    /// Hands played so far, first one first.
+    pub history: Vec<HandRow>,
+}
+
+impl Scoreboard {
+    pub fn new(names: &[String], historic: &HandsHistoric) -> Self {
+        let last = historic
+            .len()
+            .checked_sub(1)
+            .and_then(|idx| historic.get(idx))
+            .map(|hand| (hand.gamemode_name.as_str(), hand.scores));
+        Self {
+            history: historic
+                .into_iter()
+                .map(|(hand, _)| HandRow {
+                    contract: hand.gamemode_name.clone(),
+                    scores: hand.scores,
+                })
+                .collect(),
+            ..Self::after(names, historic.totals(), last, historic.len())
+        }
+    }
+
+    /// Board once `hands` hands are played, reaching `totals`, `last` being
+    /// the contract and the scores of the last one. The history of the hands
+    /// is left to the caller.
+    pub fn after(
+        names: &[String],
+        totals: [i16; 4],
+        last: Option<(&str, [i16; 4])>,
+        hands: usize,
+    ) -> Self {
+        let mut previous = totals;
+        if let Some((_, scores)) = last {
+            for (total, delta) in previous.iter_mut().zip(scores) {
+                *total -= delta;
+            }
+        }
+        let previous_ranks = positions(&previous);
+
+        let rows = positions(&totals)
+            .into_iter()
+            .zip(previous_ranks)
+            .zip(totals)
+            .enumerate()
+            .map(|(seat, ((rank, previous_rank), total))| ScoreRow {
+                name: names.get(seat).cloned().unwrap_or_default(),
+                total,
+                last_delta: last.and_then(|(_, scores)| scores.get(seat).copied()),
+                rank,
+                movement: match rank.cmp(&previous_rank) {
+                    std::cmp::Ordering::Less => Movement::Up,
+                    std::cmp::Ordering::Greater => Movement::Down,
+                    std::cmp::Ordering::Equal => Movement::Same,
+                },
+            })
+            .collect();
+
+        Self {
+            rows,
+            hands,
+            last_contract: last.map(|(contract, _)| contract.to_owned()),
+            dealer: names.get(hands % 4).cloned().unwrap_or_default(),
+            history: vec![],
+        }
+    }
+
+    /// Rows from the leader down.
+    pub fn ranked(&self) -> Vec<&ScoreRow> {
+        let mut rows: Vec<&ScoreRow> = self.rows.iter().collect();
+        rows.sort_by_key(|row| row.rank);
+        rows
+    }
+}
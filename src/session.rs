@@ -0,0 +1,613 @@
+//! Game model shared by the frontends, behind the `session` feature.
+//!
+//! A [`Session`] holds the players and the hands of one game, and only
+//! changes through [`Action`]s, each answered with an [`Outcome`] or a
+//! [`SessionError`]. How to show them is left to the frontend; none of this
+//! needs egui.
+
+use std::{error::Error, ops::RangeInclusive};
+
+use thiserror::Error;
+use whist_game::{
+    HandRecap, PlayerId, Players, PlayersBuilder, Tricks,
+    contracts::{Contract, default_contracts},
+    hand::{HandBuilder, InputRequest},
+};
+
+use crate::{
+    Queens,
+    historic::{HandsHistoric, Substitution},
+};
+
+/// Custom points a contractor can score in one hand.
+pub const POINTS_RANGE: RangeInclusive<i16> = -240..=240;
+
+/// The contracts of a new game: the standard ones, then Queens.
+pub fn contracts() -> Vec<Contract> {
+    let mut contracts = default_contracts();
+    contracts.push(Contract {
+        name: "Queens".to_owned(),
+        max_bid: None,
+        contractors_kind: 1..=3,
+        gamemode: Box::new(Queens),
+    });
+    contracts
+}
+
+#[derive(Debug, Error)]
+pub enum SessionError {
+    #[error("Players already set")]
+    PlayersSet,
+    #[error("Invalid player: {0}")]
+    InvalidPlayer(String),
+    #[error("Seats {0:?} are not a permutation of the four seats")]
+    Seating([usize; 4]),
+    #[error("Invalid substitution: {0}")]
+    Substitution(String),
+    #[error("No game started")]
+    NoGame,
+    #[error("No contract {0}")]
+    NoContract(usize),
+    #[error("No hand being entered")]
+    NoDraft,
+    #[error("No seat {0}")]
+    NoSeat(usize),
+    #[error("Select {min} to {max} contractors")]
+    ContractorsNumber { min: u8, max: u8 },
+    #[error("A bid of {0} is not possible for this contract")]
+    Bid(u8),
+    #[error("{0} tricks is not possible")]
+    Tricks(u8),
+    #[error("Custom points are only entered for three contractors")]
+    Points,
+    #[error("{0} points is not possible")]
+    PointsValue(i16),
+    #[error("The totals would not fit")]
+    Overflow,
+    #[error("Invalid hand: {0}")]
+    InvalidHand(String),
+    #[error("No hand to remove")]
+    NoHand,
+}
+
+fn invalid_hand(e: &dyn Error) -> SessionError {
+    SessionError::InvalidHand(e.to_string())
+}
+
+/// Players seated in the order of `names`.
+///
+/// # Errors
+///
+/// Fails unless `names` are four distinct valid names.
+pub fn build_players(names: &[String]) -> Result<Players, SessionError> {
+    let invalid = |e: &dyn Error| SessionError::InvalidPlayer(e.to_string());
+    let mut builder = PlayersBuilder::default();
+    for name in names {
+        builder.add_player(name).map_err(|e| invalid(&e))?;
+    }
+    builder.build().map_err(|e| invalid(&e))
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub enum PlayersState {
+    Building(PlayersBuilder),
+    Playing(Players),
+}
+
+impl Default for PlayersState {
+    fn default() -> Self {
+        Self::Building(PlayersBuilder::default())
+    }
+}
+
+impl PlayersState {
+    fn build(self) -> Result<Self, Box<dyn Error>> {
+        match self {
+            Self::Building(builder) => {
+                let players = builder.build()?;
+                Ok(Self::Playing(players))
+            }
+            Self::Playing(_) => Err("Players already set".into()),
+        }
+    }
+
+    pub(crate) fn is_building(&self) -> bool {
+        matches!(self, Self::Building(_))
+    }
+
+    pub(crate) fn players(&self) -> Option<&Players> {
+        match self {
+            Self::Playing(players) => Some(players),
+            Self::Building(_) => None,
+        }
+    }
+
+    pub(crate) fn players_mut(&mut self) -> Option<&mut Players> {
+        match self {
+            Self::Playing(players) => Some(players),
+            Self::Building(_) => None,
+        }
+    }
+}
+
+/// Hand being entered: its contract, who plays it and how it went.
+///
+/// Three contractors score custom points, one per contractor in seat order,
+/// the fourth player taking the opposite of their sum.
+pub struct HandDraft {
+    contract: Contract,
+    contractors_range: RangeInclusive<u8>,
+    bid_range: Option<RangeInclusive<u8>>,
+    /// Seats of the contractors, in seat order.
+    contractors: Vec<usize>,
+    bid: u8,
+    tricks: u8,
+    points: Option<[i16; 3]>,
+}
+
+impl HandDraft {
+    pub fn new(contract: Contract) -> Self {
+        let mut draft = Self {
+            contract,
+            contractors_range: 1..=1,
+            bid_range: None,
+            contractors: vec![],
+            bid: 0,
+            tricks: 0,
+            points: None,
+        };
+        for request in HandBuilder::new(draft.contract.clone()).all_requests() {
+            if let InputRequest::PlayersNumber { min, max } = request {
+                draft.contractors_range = min..=max;
+            } else if let InputRequest::Bid { min, max } = request {
+                draft.bid = min.get();
+                draft.bid_range = Some(min.get()..=max.get());
+            }
+        }
+        draft
+    }
+
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    pub fn contractors_range(&self) -> RangeInclusive<u8> {
+        self.contractors_range.clone()
+    }
+
+    /// Bids allowed by the contract, `None` when it has no bid.
+    pub fn bid_range(&self) -> Option<RangeInclusive<u8>> {
+        self.bid_range.clone()
+    }
+
+    pub fn contractors(&self) -> &[usize] {
+        &self.contractors
+    }
+
+    pub fn is_contractor(&self, seat: usize) -> bool {
+        self.contractors.contains(&seat)
+    }
+
+    pub fn bid(&self) -> u8 {
+        self.bid
+    }
+
+    pub fn tricks(&self) -> u8 {
+        self.tricks
+    }
+
+    /// Custom points, set once three contractors are selected.
+    pub fn points(&self) -> Option<[i16; 3]> {
+        self.points
+    }
+
+    /// Whether the number of contractors fits the contract.
+    pub fn is_ready(&self) -> bool {
+        u8::try_from(self.contractors.len())
+            .is_ok_and(|count| self.contractors_range.contains(&count))
+    }
+
+    /// Selects the player at `seat` as a contractor, or unselects them.
+    ///
+    /// # Errors
+    ///
+    /// Fails if there is no such seat or the contract allows no more
+    /// contractors.
+    pub fn toggle_contractor(&mut self, seat: usize) -> Result<(), SessionError> {
+        if seat >= 4 {
+            return Err(SessionError::NoSeat(seat));
+        }
+        if let Some(idx) = self.contractors.iter().position(|&s| s == seat) {
+            self.contractors.remove(idx);
+        } else if self.contractors.len() < usize::from(*self.contractors_range.end()) {
+            self.contractors.push(seat);
+            self.contractors.sort_unstable();
+        } else {
+            return Err(SessionError::ContractorsNumber {
+                min: *self.contractors_range.start(),
+                max: *self.contractors_range.end(),
+            });
+        }
+        self.points = (self.contractors.len() == 3).then(|| self.points.unwrap_or_default());
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// Fails if the contract has no bid or does not allow this one.
+    pub fn set_bid(&mut self, bid: u8) -> Result<(), SessionError> {
+        if !self
+            .bid_range
+            .as_ref()
+            .is_some_and(|range| range.contains(&bid))
+        {
+            return Err(SessionError::Bid(bid));
+        }
+        self.bid = bid;
+        Ok(())
+    }
+
+    /// Sets the tricks taken by the contractors.
+    ///
+    /// # Errors
+    ///
+    /// Fails if there are not that many tricks in a hand.
+    pub fn set_tricks(&mut self, tricks: u8) -> Result<(), SessionError> {
+        Tricks::new(tricks).map_err(|_e| SessionError::Tricks(tricks))?;
+        self.tricks = tricks;
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// Fails unless three contractors are selected and the points are within
+    /// [`POINTS_RANGE`].
+    pub fn set_points(&mut self, points: [i16; 3]) -> Result<(), SessionError> {
+        let current = self.points.as_mut().ok_or(SessionError::Points)?;
+        if let Some(&point) = points.iter().find(|p| !POINTS_RANGE.contains(p)) {
+            return Err(SessionError::PointsValue(point));
+        }
+        *current = points;
+        Ok(())
+    }
+
+    /// Scores the hand for `players`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the number of contractors does not fit the contract or the
+    /// contract refuses the hand.
+    pub fn build(&self, players: &Players) -> Result<HandRecap, SessionError> {
+        if !self.is_ready() {
+            return Err(SessionError::ContractorsNumber {
+                min: *self.contractors_range.start(),
+                max: *self.contractors_range.end(),
+            });
+        }
+        let names = players.names();
+        let ids = self
+            .contractors
+            .iter()
+            .map(|&seat| {
+                names
+                    .get(seat)
+                    .and_then(|name| players.get_id(name))
+                    .ok_or(SessionError::NoSeat(seat))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some(points) = self.points {
+            let mut scores = [0; 4];
+            let mut remaining_score = 0;
+            let mut remaining_seat: usize = 6;
+            for (&seat, point) in self.contractors.iter().zip(points) {
+                *scores.get_mut(seat).expect("Within range") = point;
+                remaining_score -= point;
+                remaining_seat -= seat;
+            }
+            *scores.get_mut(remaining_seat).expect("Within range") = remaining_score;
+            return Ok(HandRecap {
+                scores,
+                gamemode_name: self.contract.name.clone(),
+                contractors_tricks: ids
+                    .into_iter()
+                    .map(|id| (id, Tricks::new(0).expect("Within range")))
+                    .collect(),
+                bid: None,
+            });
+        }
+
+        let mut builder = HandBuilder::new(self.contract.clone());
+        builder
+            .set_contractors(&ids)
+            .map_err(|e| invalid_hand(&e))?;
+        let bid = Tricks::new(self.bid).map_err(|_e| SessionError::Bid(self.bid))?;
+        builder.set_bid(bid).map_err(|e| invalid_hand(&e))?;
+        let tricks = Tricks::new(self.tricks).map_err(|_e| SessionError::Tricks(self.tricks))?;
+        builder
+            .set_tricks(&[tricks])
+            .map_err(|e| invalid_hand(&e))?;
+        let hand = builder.build().map_err(|e| invalid_hand(&e))?;
+        let scores = hand.get_scores().map_err(|e| invalid_hand(&e))?;
+        Ok(hand.as_recap(scores))
+    }
+}
+
+/// Adds a scored hand to the game, returning its index.
+///
+/// # Errors
+///
+/// Fails if the scores do not add up or the totals would overflow, leaving
+/// the game as it was.
+pub fn record_hand(
+    players: &mut Players,
+    historic: &mut HandsHistoric,
+    recap: HandRecap,
+) -> Result<usize, SessionError> {
+    historic
+        .totals_after(&recap.scores)
+        .ok_or(SessionError::Overflow)?;
+    players
+        .update_score(&recap.scores)
+        .map_err(|e| invalid_hand(&e))?;
+    historic.push(recap).map_err(|_e| SessionError::Overflow)?;
+    Ok(historic.len() - 1)
+}
+
+pub enum Action {
+    AddPlayer(String),
+    /// Starts entering a hand of the contract at this index.
+    StartHand(usize),
+    ToggleContractor(usize),
+    SetBid(u8),
+    SetTricks(u8),
+    SetPoints([i16; 3]),
+    CancelHand,
+    SubmitHand,
+    /// Records a hand scored elsewhere, e.g. on another device.
+    RecordHand(HandRecap),
+    RemoveLastHand,
+    /// Seats `names`, `origin[i]` being the previous seat of the player now
+    /// named `names[i]`. Starts the game while the players are not set yet,
+    /// renames and reseats them afterwards.
+    SetPlayers {
+        names: [String; 4],
+        origin: [usize; 4],
+    },
+    /// Seats `name` in place of the player at `seat` from hand `from_hand`
+    /// onwards, the seat keeping its running score.
+    Substitute {
+        seat: usize,
+        name: String,
+        from_hand: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Players seated so far, the game starting with the fourth.
+    PlayerAdded(usize),
+    GameStarted,
+    HandStarted,
+    DraftChanged,
+    HandCancelled,
+    /// Index of the recorded hand.
+    HandRecorded(usize),
+    HandRemoved,
+    /// Players renamed, reseated or substituted.
+    PlayersChanged,
+}
+
+/// One game: its players, its hands and the hand being entered.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub players_state: PlayersState,
+    pub historic: HandsHistoric,
+    pub contracts: Vec<Contract>,
+    #[serde(skip)]
+    pub draft: Option<HandDraft>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            players_state: PlayersState::default(),
+            historic: HandsHistoric::default(),
+            contracts: contracts(),
+            draft: None,
+        }
+    }
+}
+
+impl Session {
+    /// Applies `action`, leaving the session unchanged when it fails.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the action does not fit the state of the game, e.g. a hand
+    /// before the players are set, or is invalid.
+    pub fn apply(&mut self, action: Action) -> Result<Outcome, SessionError> {
+        match action {
+            Action::AddPlayer(name) => {
+                let PlayersState::Building(builder) = &mut self.players_state else {
+                    return Err(SessionError::PlayersSet);
+                };
+                let seated = builder
+                    .add_player(&name)
+                    .map_err(|e| SessionError::InvalidPlayer(e.to_string()))?;
+                if seated < 4 {
+                    return Ok(Outcome::PlayerAdded(seated));
+                }
+                self.players_state = std::mem::take(&mut self.players_state)
+                    .build()
+                    .expect("Exactly 4 players set");
+                Ok(Outcome::GameStarted)
+            }
+            Action::StartHand(idx) => {
+                if self.players_state.is_building() {
+                    return Err(SessionError::NoGame);
+                }
+                let contract = self
+                    .contracts
+                    .get(idx)
+                    .ok_or(SessionError::NoContract(idx))?;
+                self.draft = Some(HandDraft::new(contract.clone()));
+                Ok(Outcome::HandStarted)
+            }
+            Action::ToggleContractor(seat) => {
+                self.draft_mut()?.toggle_contractor(seat)?;
+                Ok(Outcome::DraftChanged)
+            }
+            Action::SetBid(bid) => {
+                self.draft_mut()?.set_bid(bid)?;
+                Ok(Outcome::DraftChanged)
+            }
+            Action::SetTricks(tricks) => {
+                self.draft_mut()?.set_tricks(tricks)?;
+                Ok(Outcome::DraftChanged)
+            }
+            Action::SetPoints(points) => {
+                self.draft_mut()?.set_points(points)?;
+                Ok(Outcome::DraftChanged)
+            }
+            Action::CancelHand => {
+                self.draft.take().ok_or(SessionError::NoDraft)?;
+                Ok(Outcome::HandCancelled)
+            }
+            Action::SubmitHand => {
+                let draft = self.draft.as_ref().ok_or(SessionError::NoDraft)?;
+                let players = self
+                    .players_state
+                    .players_mut()
+                    .ok_or(SessionError::NoGame)?;
+                let recap = draft.build(players)?;
+                let idx = record_hand(players, &mut self.historic, recap)?;
+                self.draft = None;
+                Ok(Outcome::HandRecorded(idx))
+            }
+            Action::RecordHand(recap) => {
+                let players = self
+                    .players_state
+                    .players_mut()
+                    .ok_or(SessionError::NoGame)?;
+                let idx = record_hand(players, &mut self.historic, recap)?;
+                Ok(Outcome::HandRecorded(idx))
+            }
+            Action::RemoveLastHand => {
+                let last = self
+                    .historic
+                    .len()
+                    .checked_sub(1)
+                    .and_then(|idx| self.historic.get(idx))
+                    .ok_or(SessionError::NoHand)?;
+                let undo = last.scores.map(|score| -score);
+                if let Some(players) = self.players_state.players_mut() {
+                    players.update_score(&undo).map_err(|e| invalid_hand(&e))?;
+                }
+                self.historic.remove_last();
+                Ok(Outcome::HandRemoved)
+            }
+            Action::SetPlayers { names, origin } => {
+                let renames = self.renames(&names, &origin);
+                let outcome = self.set_players(&names, &origin)?;
+                self.historic.rename(&renames);
+                Ok(outcome)
+            }
+            Action::Substitute {
+                seat,
+                name,
+                from_hand,
+            } => self.substitute(seat, name, from_hand),
+        }
+    }
+
+    /// Players of the game getting a new name with `names`, as pairs of the
+    /// previous name and the new one.
+    fn renames(&self, names: &[String; 4], origin: &[usize; 4]) -> Vec<(String, String)> {
+        let previous = self
+            .players_state
+            .players()
+            .map(Players::names)
+            .unwrap_or_default();
+        origin
+            .iter()
+            .zip(names)
+            .filter_map(|(seat, name)| {
+                let old = previous.get(*seat)?;
+                (old != name).then(|| (old.clone(), name.clone()))
+            })
+            .collect()
+    }
+
+    fn set_players(
+        &mut self,
+        names: &[String; 4],
+        origin: &[usize; 4],
+    ) -> Result<Outcome, SessionError> {
+        let mut seats = *origin;
+        seats.sort_unstable();
+        if seats != [0, 1, 2, 3] {
+            return Err(SessionError::Seating(*origin));
+        }
+        let mut players = build_players(names)?;
+        let totals = self.historic.totals();
+        let totals = origin.map(|seat| *totals.get(seat).expect("Checked seat"));
+        players
+            .update_score(&totals)
+            .map_err(|e| invalid_hand(&e))?;
+
+        let outcome = if self.players_state.is_building() {
+            Outcome::GameStarted
+        } else {
+            let ids: [PlayerId; 4] = std::array::from_fn(|seat| {
+                names
+                    .get(seat)
+                    .and_then(|name| players.get_id(name))
+                    .expect("Players were just built from these names")
+            });
+            self.historic.reseat(origin, &ids);
+            Outcome::PlayersChanged
+        };
+        self.players_state = PlayersState::Playing(players);
+        Ok(outcome)
+    }
+
+    fn substitute(
+        &mut self,
+        seat: usize,
+        name: String,
+        from_hand: usize,
+    ) -> Result<Outcome, SessionError> {
+        let players = self.players_state.players().ok_or(SessionError::NoGame)?;
+        let mut names: [String; 4] = players.names().try_into().expect("Always 4 players");
+        let previous = std::mem::replace(
+            names.get_mut(seat).ok_or(SessionError::NoSeat(seat))?,
+            name.clone(),
+        );
+        // Checked before renaming so that a refused substitution leaves the
+        // game untouched.
+        let range = self.historic.earliest_substitution(seat)..=self.historic.len();
+        if !range.contains(&from_hand) {
+            return Err(SessionError::Substitution(format!(
+                "it must start between hands {} and {}",
+                range.start() + 1,
+                range.end() + 1
+            )));
+        }
+        self.set_players(&names, &[0, 1, 2, 3])?;
+        self.historic
+            .add_substitution(Substitution {
+                seat,
+                from_hand,
+                previous,
+                name,
+            })
+            .map_err(|e| SessionError::Substitution(e.to_string()))?;
+        Ok(Outcome::PlayersChanged)
+    }
+
+    fn draft_mut(&mut self) -> Result<&mut HandDraft, SessionError> {
+        self.draft.as_mut().ok_or(SessionError::NoDraft)
+    }
+}
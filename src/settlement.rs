@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+
+/// How final totals are converted into money. Amounts are in cents.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SettlementConfig {
+    pub cents_per_point: i64,
+    /// Losses below this amount are not paid.
+    pub min_payout: i64,
+    /// No player pays more than this amount.
+    pub max_payout: Option<i64>,
+    /// Smallest coin, every amount is rounded to a multiple of it.
+    pub coin: i64,
+    pub currency: String,
+}
+
+impl Default for SettlementConfig {
+    fn default() -> Self {
+        Self {
+            cents_per_point: 10,
+            min_payout: 0,
+            max_payout: None,
+            coin: 10,
+            currency: "€".to_owned(),
+        }
+    }
+}
+
+impl SettlementConfig {
+    pub fn format(&self, cents: i64) -> String {
+        let sign = if cents < 0 { "-" } else { "" };
+        let cents = cents.abs();
+        format!("{sign}{}.{:02} {}", cents / 100, cents % 100, self.currency)
+    }
+
+    fn round_to_coin(&self, cents: i64) -> i64 {
+        let coin = self.coin.max(1);
+        (cents + coin / 2) / coin * coin
+    }
+
+    fn round_down_to_coin(&self, cents: i64) -> i64 {
+        let coin = self.coin.max(1);
+        cents / coin * coin
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    pub from: usize,
+    pub to: usize,
+    pub cents: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settlement {
+    /// Net amount of each seat, positive when receiving money.
+    pub balances: [i64; 4],
+    pub transfers: Vec<Transfer>,
+}
+
+impl Settlement {
+    pub fn new(totals: &[i16; 4], config: &SettlementConfig) -> Self {
+        let raw = totals.map(|total| i64::from(total) * config.cents_per_point);
+
+        // Losers pay their rounded and capped loss...
+        let mut balances = raw.map(|amount| {
+            if amount >= 0 || -amount < config.min_payout {
+                return 0;
+            }
+            let mut paid = config.round_to_coin(-amount);
+            if let Some(max) = config.max_payout
+                && paid > max
+            {
+                // Rounded down, the cap is never exceeded.
+                paid = config.round_down_to_coin(max);
+            }
+            -paid
+        });
+
+        // ...and winners share what was paid, in proportion of their points.
+        let paid: i64 = -balances.iter().sum::<i64>();
+        let won: i64 = raw.iter().filter(|amount| **amount > 0).sum();
+        if won > 0 {
+            let coin = config.coin.max(1);
+            for (balance, amount) in balances.iter_mut().zip(raw) {
+                if amount > 0 {
+                    *balance = paid * amount / won / coin * coin;
+                }
+            }
+            let remainder = -balances.iter().sum::<i64>();
+            if let Some((best, _)) = raw.iter().enumerate().max_by_key(|(_, amount)| **amount) {
+                *balances.get_mut(best).expect("4 seats") += remainder;
+            }
+        } else {
+            balances = [0; 4];
+        }
+
+        Self {
+            balances,
+            transfers: minimal_transfers(balances),
+        }
+    }
+
+    pub fn to_text(&self, names: &[String], config: &SettlementConfig) -> String {
+        let name = |seat: usize| names.get(seat).map(String::as_str).unwrap_or_default();
+        let mut text = String::from("Settlement\n");
+        for (seat, balance) in self.balances.iter().enumerate() {
+            text.push_str(&format!("{}: {}\n", name(seat), config.format(*balance)));
+        }
+        text.push('\n');
+        if self.transfers.is_empty() {
+            text.push_str("Nobody pays\n");
+        }
+        for transfer in &self.transfers {
+            text.push_str(&format!(
+                "{} pays {} to {}\n",
+                name(transfer.from),
+                config.format(transfer.cents),
+                name(transfer.to)
+            ));
+        }
+        text
+    }
+}
+
+/// Settles debts with as few transfers as possible: matching debts first
+/// settle each other, the rest goes from the biggest debtor to the biggest
+/// creditor.
+fn minimal_transfers(mut balances: [i64; 4]) -> Vec<Transfer> {
+    let mut transfers = vec![];
+    loop {
+        let debtors = (0..4).filter(|&i| balances.get(i).is_some_and(|b| *b < 0));
+        let creditors = || (0..4).filter(|&i| balances.get(i).is_some_and(|b| *b > 0));
+        let exact = debtors.clone().find_map(|from| {
+            creditors()
+                .find(|&to| balances.get(to).copied() == balances.get(from).map(|b| -b))
+                .map(|to| (from, to))
+        });
+        let pair = exact.or_else(|| {
+            let from = debtors.min_by_key(|&i| balances.get(i).copied())?;
+            let to = creditors().max_by_key(|&i| balances.get(i).copied())?;
+            Some((from, to))
+        });
+        let Some((from, to)) = pair else {
+            break;
+        };
+        let debt = -*balances.get(from).expect("4 seats");
+        let credit = *balances.get(to).expect("4 seats");
+        let cents = debt.min(credit);
+        *balances.get_mut(from).expect("4 seats") += cents;
+        *balances.get_mut(to).expect("4 seats") -= cents;
+        transfers.push(Transfer { from, to, cents });
+    }
+    transfers
+}
@@ -0,0 +1,161 @@
+//! Games shared through a link.
+//!
+//! The players, the names of the contracts played and the hands are encoded
+//! in the URL fragment, so a link carries the whole sheet without any server:
+//! `#whist=<version>.<base64 of the deflated RON>`.
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use whist_game::{Contract, HandRecap};
+
+use crate::historic::HandsHistoric;
+
+/// Version of the encoding written by this build.
+pub const SHARE_VERSION: u32 = 1;
+
+const PREFIX: &str = "whist=";
+
+/// Decoded size above which a link is rejected, far above any real game.
+const MAX_SIZE: usize = 1 << 20;
+
+#[derive(Debug, Error)]
+pub enum ShareError {
+    #[error("This link does not hold a game")]
+    NotShared,
+    #[error("Shared game version {0} is newer than this application")]
+    UnsupportedVersion(u32),
+    #[error("Corrupted link: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("Corrupted link: {0}")]
+    Inflate(String),
+    #[error("Unreadable shared game: {0}")]
+    Decode(#[from] ron::error::SpannedError),
+    #[error("Invalid shared game: {0}")]
+    Invalid(String),
+    #[error("Unable to encode the game: {0}")]
+    Encode(#[from] ron::Error),
+}
+
+#[derive(Serialize)]
+struct SharedGameRef<'a> {
+    names: &'a [String],
+    contracts: Vec<&'a str>,
+    hands: Vec<&'a HandRecap>,
+}
+
+#[derive(Deserialize)]
+struct SharedGameOwned {
+    names: Vec<String>,
+    contracts: Vec<String>,
+    hands: Vec<HandRecap>,
+}
+
+/// A game read from a link.
+pub struct SharedGame {
+    pub names: Vec<String>,
+    /// Contracts referenced by the hands.
+    pub contracts: Vec<String>,
+    pub historic: HandsHistoric,
+}
+
+/// Encodes a game as a URL fragment, without the leading `#`.
+///
+/// # Errors
+///
+/// Fails if the hands cannot be encoded.
+pub fn encode(names: &[String], historic: &HandsHistoric) -> Result<String, ShareError> {
+    let hands: Vec<&HandRecap> = historic.into_iter().map(|(hand, _)| hand).collect();
+    let mut contracts: Vec<&str> = vec![];
+    for hand in &hands {
+        if !contracts.contains(&hand.gamemode_name.as_str()) {
+            contracts.push(&hand.gamemode_name);
+        }
+    }
+    let ron = ron::ser::to_string(&SharedGameRef {
+        names,
+        contracts,
+        hands,
+    })?;
+    let deflated = miniz_oxide::deflate::compress_to_vec(ron.as_bytes(), 9);
+    Ok(format!(
+        "{PREFIX}{SHARE_VERSION}.{}",
+        URL_SAFE_NO_PAD.encode(deflated)
+    ))
+}
+
+/// The link to `base`, the address of the web app, opening the game.
+///
+/// # Errors
+///
+/// Fails if the hands cannot be encoded.
+pub fn link(base: &str, names: &[String], historic: &HandsHistoric) -> Result<String, ShareError> {
+    let base = base.split('#').next().unwrap_or_default();
+    Ok(format!("{base}#{}", encode(names, historic)?))
+}
+
+/// Decodes a game from a whole link or from its fragment.
+///
+/// # Errors
+///
+/// Fails if the link holds no game, a corrupted one or one written by a newer
+/// version of the application.
+pub fn decode(link: &str) -> Result<SharedGame, ShareError> {
+    let fragment = link.trim().rsplit('#').next().unwrap_or_default();
+    let payload = fragment.strip_prefix(PREFIX).ok_or(ShareError::NotShared)?;
+    let (version, data) = payload.split_once('.').ok_or(ShareError::NotShared)?;
+    let version: u32 = version.parse().map_err(|_e| ShareError::NotShared)?;
+    if version != SHARE_VERSION {
+        return Err(ShareError::UnsupportedVersion(version));
+    }
+    let deflated = URL_SAFE_NO_PAD.decode(data)?;
+    let ron = miniz_oxide::inflate::decompress_to_vec_with_limit(&deflated, MAX_SIZE)
+        .map_err(|e| ShareError::Inflate(format!("{:?}", e.status)))?;
+    let ron = String::from_utf8(ron).map_err(|e| ShareError::Inflate(e.to_string()))?;
+    let shared: SharedGameOwned = ron::from_str(&ron)?;
+    let mut historic = HandsHistoric::default();
+    for hand in shared.hands {
+        historic
+            .push(hand)
+            .map_err(|e| ShareError::Invalid(e.to_string()))?;
+    }
+    historic.take_events();
+    Ok(SharedGame {
+        names: shared.names,
+        contracts: shared.contracts,
+        historic,
+    })
+}
+
+impl SharedGame {
+    /// Contracts of the game that this application does not know. Their
+    /// hands keep their scores but cannot be edited.
+    pub fn missing_contracts(&self, contracts: &[Contract]) -> Vec<&str> {
+        self.contracts
+            .iter()
+            .filter(|name| !contracts.iter().any(|contract| &contract.name == *name))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Address of the running web app.
+#[cfg(target_arch = "wasm32")]
+pub fn page_url() -> Option<String> {
+    web_sys::window()?.location().href().ok()
+}
+
+/// Game carried by the address the web app was opened with. The fragment is
+/// then cleared so that a reload does not offer it again.
+#[cfg(target_arch = "wasm32")]
+pub fn take_from_page() -> Option<Result<SharedGame, ShareError>> {
+    let location = web_sys::window()?.location();
+    let hash = location.hash().ok()?;
+    if !hash.starts_with(&format!("#{PREFIX}")) {
+        return None;
+    }
+    if let Err(e) = location.set_hash("") {
+        log::error!("{e:?}");
+    }
+    Some(decode(&hash))
+}
@@ -0,0 +1,415 @@
+//! SQLite storage, behind the `sqlite` feature.
+//!
+//! Documents are kept as JSON like in a save folder, and the archive is also
+//! spread over relational tables so that statistics over years of games are
+//! plain SQL queries instead of decoding every game. Those tables are only a
+//! copy of the archive document, rebuilt whenever their layout changes.
+
+use std::path::Path;
+
+use rusqlite::{Connection, OptionalExtension as _, Transaction, params};
+
+use crate::{
+    archive::ArchivedGame,
+    league::{GameResult, Leaderboard},
+    save::{self, Archive, SaveError},
+    state::AppState,
+    storage::{
+        ArchiveSummary, ContractSummary, Format, PlayerSummary, StorageBackend, StorageError,
+        StorageRead, keys,
+    },
+};
+
+/// Layout of the tables, stored as the `user_version` of the database.
+const SCHEMA_VERSION: i64 = 1;
+
+/// Tables derived from the archive document, dependent tables first.
+const ARCHIVE_TABLES: [&str; 6] = [
+    "hand_scores",
+    "hands",
+    "results",
+    "games",
+    "contracts",
+    "players",
+];
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS documents (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS players (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE IF NOT EXISTS contracts (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE IF NOT EXISTS games (
+        -- Position of the game in the archive, from 1.
+        id INTEGER PRIMARY KEY,
+        finished_on INTEGER NOT NULL,
+        -- The archived game as JSON, telling whether the rows are up to date.
+        fingerprint TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS results (
+        game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+        seat INTEGER NOT NULL,
+        player_id INTEGER NOT NULL REFERENCES players(id),
+        total INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (game_id, seat)
+    );
+    CREATE TABLE IF NOT EXISTS hands (
+        id INTEGER PRIMARY KEY,
+        game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+        idx INTEGER NOT NULL,
+        contract_id INTEGER NOT NULL REFERENCES contracts(id),
+        bid INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS hand_scores (
+        hand_id INTEGER NOT NULL REFERENCES hands(id) ON DELETE CASCADE,
+        seat INTEGER NOT NULL,
+        player_id INTEGER NOT NULL REFERENCES players(id),
+        score INTEGER NOT NULL,
+        tricks INTEGER,
+        PRIMARY KEY (hand_id, seat)
+    );
+";
+
+/// A database file holding every document and the archived games.
+pub struct SqliteStorage {
+    conn: Connection,
+}
+
+impl SqliteStorage {
+    /// Opens the database at `path`, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be opened as a database.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// A database that only lives as long as the value, for tests.
+    ///
+    /// # Errors
+    ///
+    /// Fails if SQLite cannot allocate the database.
+    pub fn in_memory() -> Result<Self, StorageError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(mut conn: Connection) -> Result<Self, StorageError> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let tx = conn.transaction()?;
+        if version < SCHEMA_VERSION {
+            for table in ARCHIVE_TABLES {
+                tx.execute_batch(&format!("DROP TABLE IF EXISTS {table};"))?;
+            }
+        }
+        tx.execute_batch(SCHEMA)?;
+        if version < SCHEMA_VERSION {
+            let archive: Option<String> = tx
+                .query_row(
+                    "SELECT value FROM documents WHERE key = ?1",
+                    params![keys::ARCHIVE],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if let Some(archive) = archive {
+                sync_archive(&tx, &archive)?;
+            }
+            tx.execute_batch(&format!("PRAGMA user_version = {SCHEMA_VERSION};"))?;
+        }
+        tx.commit()?;
+        Ok(Self { conn })
+    }
+
+    /// Copies the state saved in `source`, journal included, into the
+    /// database. Returns the number of imported archived games.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `source` holds unreadable data or the database cannot be
+    /// written. Nothing is imported in the first case.
+    pub fn import(&mut self, source: &dyn StorageRead) -> Result<usize, SaveError> {
+        let app = AppState::load(source);
+        if let Some(quarantine) = app.quarantine {
+            return Err(SaveError::Unreadable(quarantine.key, quarantine.error));
+        }
+        save::write_snapshot(&app, self)?;
+        Ok(app.archive.len())
+    }
+
+    /// Archive figures of every player, best points first.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be read.
+    pub fn player_summaries(&self) -> Result<Vec<PlayerSummary>, StorageError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT players.name, COUNT(*), SUM(results.position = 1), SUM(results.total),
+                    AVG(results.position)
+             FROM results JOIN players ON players.id = results.player_id
+             GROUP BY players.id
+             ORDER BY SUM(results.total) DESC, players.name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(PlayerSummary {
+                name: row.get(0)?,
+                games: row.get(1)?,
+                wins: row.get(2)?,
+                points: row.get(3)?,
+                average_position: row.get(4)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Contracts played in the archive, most played first. A contract counts
+    /// as won when its contractors scored.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be read.
+    pub fn contract_summaries(&self) -> Result<Vec<ContractSummary>, StorageError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT contracts.name, COUNT(*), SUM(won)
+             FROM (
+                 SELECT hands.contract_id,
+                        COALESCE(SUM(hand_scores.score)
+                            FILTER (WHERE hand_scores.tricks IS NOT NULL), 0) > 0 AS won
+                 FROM hands JOIN hand_scores ON hand_scores.hand_id = hands.id
+                 GROUP BY hands.id
+             ) JOIN contracts ON contracts.id = contract_id
+             GROUP BY contracts.id
+             ORDER BY COUNT(*) DESC, contracts.name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ContractSummary {
+                name: row.get(0)?,
+                hands: row.get(1)?,
+                won: row.get(2)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Ratings of every player, computed from the results and hand scores
+    /// stored in the tables.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be read.
+    pub fn leaderboard(&self) -> Result<Leaderboard, StorageError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT results.game_id, players.name, results.total
+             FROM results JOIN players ON players.id = results.player_id
+             ORDER BY results.game_id, results.seat",
+        )?;
+        let seats = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<(i64, String, i16)>, _>>()?;
+        let results = seats.chunk_by(|a, b| a.0 == b.0).map(|game| {
+            let seat = |seat: usize| game.get(seat).expect("4 seats per game");
+            GameResult {
+                players: std::array::from_fn(|idx| seat(idx).1.clone()),
+                totals: std::array::from_fn(|idx| seat(idx).2),
+            }
+        });
+
+        let mut stmt = self.conn.prepare(
+            "SELECT players.name, SUM(hand_scores.score)
+             FROM hand_scores JOIN players ON players.id = hand_scores.player_id
+             GROUP BY players.id",
+        )?;
+        let points = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(String, i32)>, _>>()?;
+        Ok(Leaderboard::from_results(results, points))
+    }
+}
+
+/// Brings the relational copy of the archive document `raw` up to date, game
+/// by game: the rows of a game are only rewritten when it changed.
+/// An unreadable document is refused rather than leaving stale rows.
+fn sync_archive(tx: &Transaction<'_>, raw: &str) -> Result<(), StorageError> {
+    let games = save::decode::<Archive>(raw, Format::Json)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?
+        .games;
+    tx.execute("DELETE FROM games WHERE id > ?1", params![games.len()])?;
+    for (idx, game) in games.iter().enumerate() {
+        let id = idx + 1;
+        let fingerprint = serde_json::to_string(game)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let stored: Option<String> = tx
+            .query_row(
+                "SELECT fingerprint FROM games WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if stored.as_ref() == Some(&fingerprint) {
+            continue;
+        }
+        tx.execute("DELETE FROM games WHERE id = ?1", params![id])?;
+        insert_game(tx, id, game, &fingerprint)?;
+    }
+    Ok(())
+}
+
+fn insert_game(
+    tx: &Transaction<'_>,
+    id: usize,
+    game: &ArchivedGame,
+    fingerprint: &str,
+) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT INTO games (id, finished_on, fingerprint) VALUES (?1, ?2, ?3)",
+        params![id, game.finished_on.days(), fingerprint],
+    )?;
+    for (seat, ((name, total), position)) in game
+        .players
+        .iter()
+        .zip(game.totals)
+        .zip(game.positions())
+        .enumerate()
+    {
+        tx.execute(
+            "INSERT INTO results (game_id, seat, player_id, total, position)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![id, seat, player_id(tx, name)?, total, position],
+        )?;
+    }
+    for (idx, hand) in game.hands.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO hands (game_id, idx, contract_id, bid) VALUES (?1, ?2, ?3, ?4)",
+            params![id, idx, contract_id(tx, &hand.gamemode_name)?, hand.bid],
+        )?;
+        let hand_id = tx.last_insert_rowid();
+        for (seat, (name, score)) in hand.occupants.iter().zip(hand.scores).enumerate() {
+            let tricks = hand
+                .contractors
+                .iter()
+                .find(|(contractor, _)| *contractor == seat)
+                .map(|(_, tricks)| *tricks);
+            tx.execute(
+                "INSERT INTO hand_scores (hand_id, seat, player_id, score, tricks)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![hand_id, seat, player_id(tx, name)?, score, tricks],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn player_id(tx: &Transaction<'_>, name: &str) -> rusqlite::Result<i64> {
+    tx.execute(
+        "INSERT OR IGNORE INTO players (name) VALUES (?1)",
+        params![name],
+    )?;
+    tx.query_row(
+        "SELECT id FROM players WHERE name = ?1",
+        params![name],
+        |row| row.get(0),
+    )
+}
+
+fn contract_id(tx: &Transaction<'_>, name: &str) -> rusqlite::Result<i64> {
+    tx.execute(
+        "INSERT OR IGNORE INTO contracts (name) VALUES (?1)",
+        params![name],
+    )?;
+    tx.query_row(
+        "SELECT id FROM contracts WHERE name = ?1",
+        params![name],
+        |row| row.get(0),
+    )
+}
+
+impl StorageRead for SqliteStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.conn
+            .query_row(
+                "SELECT value FROM documents WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()
+            .flatten()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let Ok(mut stmt) = self.conn.prepare("SELECT key FROM documents") else {
+            return vec![];
+        };
+        stmt.query_map([], |row| row.get::<_, String>(0))
+            .map(|rows| rows.filter_map(Result::ok).collect())
+            .unwrap_or_default()
+    }
+
+    fn format(&self) -> Format {
+        Format::Json
+    }
+}
+
+impl StorageBackend for SqliteStorage {
+    /// The archive tables are updated along with the archive document, both
+    /// or neither being written.
+    fn set(&mut self, key: &str, value: String) -> Result<(), StorageError> {
+        let tx = self.conn.transaction()?;
+        if key == keys::ARCHIVE {
+            sync_archive(&tx, &value)?;
+        }
+        tx.execute(
+            "INSERT INTO documents (key, value) VALUES (?1, ?2)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn archive_summary(&self) -> Option<ArchiveSummary> {
+        self.player_summaries()
+            .and_then(|players| {
+                Ok(ArchiveSummary {
+                    players,
+                    contracts: self.contract_summaries()?,
+                })
+            })
+            .inspect_err(|e| log::error!("{e}"))
+            .ok()
+    }
+
+    fn leaderboard(&self) -> Option<Leaderboard> {
+        Self::leaderboard(self)
+            .inspect_err(|e| log::error!("{e}"))
+            .ok()
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+        self.conn
+            .execute("DELETE FROM documents WHERE key = ?1", params![key])?;
+        Ok(())
+    }
+
+    fn append(&mut self, key: &str, line: &str) -> Result<(), StorageError> {
+        self.conn.execute(
+            "INSERT INTO documents (key, value) VALUES (?1, ?2)
+             ON CONFLICT (key) DO UPDATE SET value = value || excluded.value",
+            params![key, line],
+        )?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), StorageError> {
+        // Every statement is committed as soon as it runs.
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+//! Everything the app persists, without any frontend.
+//!
+//! [`AppState`] is the state shared by the app window, the CLI and the
+//! terminal frontend: the game being played, the archive and the settings,
+//! loaded from a storage backend and saved back to it as a snapshot plus a
+//! journal of hands.
+
+use log::error;
+use serde::{Deserialize, Serialize};
+
+#[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+use crate::sync::SavedSync;
+use crate::{
+    archive::ArchivedGame,
+    date::Date,
+    historic::HistoricEvent,
+    journal::{self, ActionRef},
+    league::Season,
+    save::{self, Quarantine},
+    session::{Action, Outcome, Session, SessionError},
+    settlement::SettlementConfig,
+    storage::{StorageBackend, StorageRead, keys},
+    tournament::Tournament,
+};
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AppState {
+    /// The game being played, changed through [`Self::apply`].
+    pub session: Session,
+    pub player_field: String,
+    pub current_contract_idx: usize,
+    pub hand_detail: Option<usize>,
+    /// Stored in the archive document.
+    #[serde(skip)]
+    pub archive: Vec<ArchivedGame>,
+    #[serde(skip)]
+    pub seasons: Vec<Season>,
+    pub tournament: Option<Tournament>,
+    /// Stored in the settings document.
+    #[serde(skip)]
+    pub settlement: SettlementConfig,
+    pub quarantine: Option<Quarantine>,
+    /// Last journal entry included in this state.
+    pub journal_seq: u64,
+    #[serde(skip)]
+    pub journal_len: usize,
+    #[serde(skip)]
+    pub snapshot_requested: bool,
+    #[serde(skip)]
+    pub roster: Vec<String>,
+    /// Stored in the settings document.
+    #[serde(skip)]
+    pub web_url: String,
+    /// Room joined again on the next start.
+    #[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+    pub sync_saved: Option<SavedSync>,
+}
+
+impl AppState {
+    /// Loads the state saved in `source`, setting aside what cannot be read.
+    pub fn load(source: &dyn StorageRead) -> Self {
+        let format = source.format();
+        let mut state = match source.get(keys::APP) {
+            None => Self::default(),
+            Some(raw) => match save::load(&raw, format) {
+                Ok(mut state) => {
+                    let journal = source.get(keys::JOURNAL).unwrap_or_default();
+                    state.journal_len = journal::replay(&journal, format, &mut state);
+                    state
+                }
+                Err(e) => {
+                    error!("{e}");
+                    Self {
+                        quarantine: Some(Quarantine {
+                            raw,
+                            error: e.to_string(),
+                            format,
+                            key: keys::APP.to_owned(),
+                        }),
+                        ..Default::default()
+                    }
+                }
+            },
+        };
+        if let Err((quarantine, e)) = save::load_documents(source, &mut state) {
+            error!("{e}");
+            state.quarantine.get_or_insert(quarantine);
+        }
+        state
+    }
+
+    /// Starts a new game, keeping everything that outlives a single game.
+    pub fn reset_game(&mut self) {
+        let Self {
+            archive,
+            seasons,
+            tournament,
+            settlement,
+            quarantine,
+            journal_seq,
+            journal_len,
+            roster,
+            web_url,
+            ..
+        } = std::mem::take(self);
+        *self = Self {
+            archive,
+            seasons,
+            tournament,
+            settlement,
+            quarantine,
+            journal_seq,
+            journal_len,
+            roster,
+            web_url,
+            snapshot_requested: true,
+            ..Default::default()
+        };
+    }
+
+    /// Stores the current game in the archive and starts a new one. Returns
+    /// `false` when there was nothing to archive.
+    pub fn finish_game(&mut self) -> bool {
+        let Some(players) = self.session.players_state.players() else {
+            return false;
+        };
+        if self.session.historic.is_empty() {
+            return false;
+        }
+        let game = ArchivedGame::new(&self.session.historic, &players.names(), Date::today());
+        self.archive.push(game);
+        self.reset_game();
+        true
+    }
+
+    /// Applies `action` to the current game, remembering the players it
+    /// seats.
+    ///
+    /// # Errors
+    ///
+    /// Fails as [`Session::apply`] does, leaving the game unchanged.
+    pub fn apply(&mut self, action: Action) -> Result<Outcome, SessionError> {
+        let outcome = self.session.apply(action)?;
+        if matches!(outcome, Outcome::GameStarted | Outcome::PlayersChanged) {
+            if let Some(players) = self.session.players_state.players() {
+                let names = players.names();
+                self.remember_players(&names);
+            }
+            self.snapshot_requested = true;
+        }
+        Ok(outcome)
+    }
+
+    /// Adds the names not seen before to the roster.
+    pub fn remember_players(&mut self, names: &[String]) {
+        for name in names {
+            if !self.roster.contains(name) {
+                self.roster.push(name.clone());
+                self.snapshot_requested = true;
+            }
+        }
+    }
+
+    /// Persists what changed since the last call: hands are appended to the
+    /// journal, anything else triggers a snapshot.
+    pub fn persist(&mut self, storage: &mut dyn StorageBackend) {
+        if let Some(tournament) = &mut self.tournament {
+            for table in tournament.rounds.iter_mut().flat_map(|r| &mut r.tables) {
+                if !table.session.historic.take_events().is_empty() {
+                    self.snapshot_requested = true;
+                }
+            }
+        }
+
+        for event in self.session.historic.take_events() {
+            if self.snapshot_requested {
+                break;
+            }
+            let action = match event {
+                HistoricEvent::Pushed(idx) => match self.session.historic.get(idx) {
+                    Some(hand) => ActionRef::PushHand(hand),
+                    // Removed within the same frame, the snapshot will tell.
+                    None => {
+                        self.snapshot_requested = true;
+                        break;
+                    }
+                },
+                HistoricEvent::RemovedLast => ActionRef::RemoveLastHand,
+            };
+            match journal::append(storage, self.journal_seq + 1, action) {
+                Ok(()) => {
+                    self.journal_seq += 1;
+                    self.journal_len += 1;
+                }
+                Err(e) => {
+                    error!("{e}");
+                    self.snapshot_requested = true;
+                }
+            }
+        }
+
+        if self.snapshot_requested || self.journal_len >= journal::COMPACT_EVERY {
+            self.save_snapshot(storage);
+        }
+    }
+
+    /// Writes the whole state and empties the journal it now includes.
+    pub fn save_snapshot(&mut self, storage: &mut dyn StorageBackend) {
+        self.session.historic.take_events();
+        match save::write_snapshot(self, storage) {
+            Ok(()) => {
+                self.journal_len = 0;
+                self.snapshot_requested = false;
+            }
+            Err(e) => error!("{e}"),
+        }
+    }
+}
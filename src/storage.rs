@@ -0,0 +1,545 @@
+//! Storage backends.
+//!
+//! Everything the app persists goes through a small key/value interface so
+//! that the same documents can live in eframe's storage, in a directory
+//! chosen by the user, in the browser storage or in memory for tests.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::league::Leaderboard;
+pub use crate::league::{ArchiveSummary, ContractSummary, PlayerSummary};
+
+/// Keys of the persisted documents.
+pub mod keys {
+    /// Current game, see [`crate::save`].
+    pub const APP: &str = "app";
+    /// Hands played since the last snapshot, see [`crate::journal`].
+    pub const JOURNAL: &str = "journal";
+    /// Finished games and seasons.
+    pub const ARCHIVE: &str = "archive";
+    /// User preferences.
+    pub const SETTINGS: &str = "settings";
+    /// Names of every known player.
+    pub const ROSTER: &str = "roster";
+    /// Folder chosen by the user, always kept in eframe's storage.
+    pub const SAVE_DIR: &str = "save_dir";
+    /// Database file chosen by the user, always kept in eframe's storage.
+    pub const SAVE_DATABASE: &str = "save_database";
+
+    pub const ALL: [&str; 5] = [APP, JOURNAL, ARCHIVE, SETTINGS, ROSTER];
+}
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("Storage error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Browser storage error: {0}")]
+    Browser(String),
+    #[cfg(all(feature = "sqlite", not(target_arch = "wasm32")))]
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+}
+
+/// Text encoding of the documents, chosen by the backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Format {
+    #[default]
+    Ron,
+    Json,
+}
+
+pub trait StorageRead {
+    fn get(&self, key: &str) -> Option<String>;
+
+    fn keys(&self) -> Vec<String>;
+
+    fn format(&self) -> Format {
+        Format::Ron
+    }
+}
+
+pub trait StorageBackend: StorageRead {
+    /// Stores `value` under `key`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the underlying storage cannot be written.
+    fn set(&mut self, key: &str, value: String) -> Result<(), StorageError>;
+
+    /// Removes `key`, doing nothing if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the underlying storage cannot be written.
+    fn remove(&mut self, key: &str) -> Result<(), StorageError>;
+
+    /// Adds `line` at the end of the document under `key`, creating it if
+    /// needed. Backends that can write in place override it, the others
+    /// rewrite the document.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the underlying storage cannot be written.
+    fn append(&mut self, key: &str, line: &str) -> Result<(), StorageError> {
+        let mut value = self.get(key).unwrap_or_default();
+        value.push_str(line);
+        self.set(key, value)
+    }
+
+    /// Makes sure everything written so far survives a crash.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the underlying storage cannot be written.
+    fn flush(&mut self) -> Result<(), StorageError>;
+
+    /// Figures over the whole archive, for the backends that can compute
+    /// them without decoding every game.
+    fn archive_summary(&self) -> Option<ArchiveSummary> {
+        None
+    }
+
+    /// Ratings computed from the backend's own tables, for the backends
+    /// holding the archive as such.
+    fn leaderboard(&self) -> Option<Leaderboard> {
+        None
+    }
+}
+
+#[cfg(feature = "gui")]
+pub use app_storage::{EframeReader, EframeStorage};
+
+#[cfg(feature = "gui")]
+mod app_storage {
+    use super::{StorageBackend, StorageError, StorageRead, keys};
+
+    /// Read access to eframe's storage, as given at creation.
+    pub struct EframeReader<'a>(pub &'a dyn eframe::Storage);
+
+    impl StorageRead for EframeReader<'_> {
+        fn get(&self, key: &str) -> Option<String> {
+            self.0.get_string(key).filter(|value| !value.is_empty())
+        }
+
+        fn keys(&self) -> Vec<String> {
+            eframe_keys(self.0)
+        }
+    }
+
+    /// eframe's storage: a RON file on native, the local storage on the web.
+    pub struct EframeStorage<'a>(pub &'a mut dyn eframe::Storage);
+
+    impl StorageRead for EframeStorage<'_> {
+        fn get(&self, key: &str) -> Option<String> {
+            self.0.get_string(key).filter(|value| !value.is_empty())
+        }
+
+        fn keys(&self) -> Vec<String> {
+            eframe_keys(self.0)
+        }
+    }
+
+    impl StorageBackend for EframeStorage<'_> {
+        fn set(&mut self, key: &str, value: String) -> Result<(), StorageError> {
+            self.0.set_string(key, value);
+            Ok(())
+        }
+
+        fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+            // eframe cannot remove a key, an empty value stands for a missing one.
+            self.0.set_string(key, String::new());
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), StorageError> {
+            self.0.flush();
+            Ok(())
+        }
+    }
+
+    fn eframe_keys(storage: &dyn eframe::Storage) -> Vec<String> {
+        keys::ALL
+            .into_iter()
+            .filter(|key| storage.get_string(key).is_some_and(|v| !v.is_empty()))
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+/// Documents kept in memory, for tests.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    pub documents: BTreeMap<String, String>,
+}
+
+impl StorageRead for MemoryStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.documents.get(key).cloned()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.documents.keys().cloned().collect()
+    }
+}
+
+impl StorageBackend for MemoryStorage {
+    fn set(&mut self, key: &str, value: String) -> Result<(), StorageError> {
+        self.documents.insert(key.to_owned(), value);
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+        self.documents.remove(key);
+        Ok(())
+    }
+
+    fn append(&mut self, key: &str, line: &str) -> Result<(), StorageError> {
+        self.documents
+            .entry(key.to_owned())
+            .or_default()
+            .push_str(line);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), StorageError> {
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use directory::DirectoryStorage;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod directory {
+    use std::{
+        io::Write as _,
+        path::{Path, PathBuf},
+    };
+
+    use super::{Format, StorageBackend, StorageError, StorageRead};
+
+    /// A folder holding one JSON file per document.
+    #[derive(Debug, Clone)]
+    pub struct DirectoryStorage {
+        dir: PathBuf,
+    }
+
+    impl DirectoryStorage {
+        /// Uses `dir`, creating it if needed.
+        ///
+        /// # Errors
+        ///
+        /// Fails if the folder cannot be created.
+        pub fn new(dir: impl Into<PathBuf>) -> Result<Self, StorageError> {
+            let dir = dir.into();
+            std::fs::create_dir_all(&dir)?;
+            Ok(Self { dir })
+        }
+
+        pub fn dir(&self) -> &Path {
+            &self.dir
+        }
+
+        fn path(&self, key: &str) -> PathBuf {
+            self.dir.join(format!("{key}.json"))
+        }
+    }
+
+    impl StorageRead for DirectoryStorage {
+        fn get(&self, key: &str) -> Option<String> {
+            std::fs::read_to_string(self.path(key)).ok()
+        }
+
+        fn keys(&self) -> Vec<String> {
+            let Ok(entries) = std::fs::read_dir(&self.dir) else {
+                return vec![];
+            };
+            entries
+                .filter_map(Result::ok)
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    (path.extension()? == "json")
+                        .then(|| path.file_stem()?.to_str().map(str::to_owned))
+                        .flatten()
+                })
+                .collect()
+        }
+
+        fn format(&self) -> Format {
+            Format::Json
+        }
+    }
+
+    impl StorageBackend for DirectoryStorage {
+        fn set(&mut self, key: &str, value: String) -> Result<(), StorageError> {
+            // Write then rename, so that a crash never leaves half a file.
+            let path = self.path(key);
+            let tmp = path.with_extension("json.tmp");
+            std::fs::write(&tmp, value)?;
+            std::fs::rename(tmp, path)?;
+            Ok(())
+        }
+
+        fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+            match std::fs::remove_file(self.path(key)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            }
+        }
+
+        /// Appends to the file and syncs it, a crash leaving at worst a
+        /// truncated last line.
+        fn append(&mut self, key: &str, line: &str) -> Result<(), StorageError> {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.path(key))?;
+            file.write_all(line.as_bytes())?;
+            file.sync_data()?;
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), StorageError> {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use app_file::AppFileStorage;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod app_file {
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    use super::{StorageBackend, StorageError, StorageRead, keys};
+
+    /// The `app.ron` file in which eframe keeps the desktop app's storage,
+    /// read and written without eframe. The app must be closed meanwhile, it
+    /// would otherwise overwrite the file with its own copy on exit.
+    #[derive(Debug, Clone)]
+    pub struct AppFileStorage {
+        path: PathBuf,
+        /// Every entry of the file, those of eframe itself included.
+        entries: BTreeMap<String, String>,
+    }
+
+    impl AppFileStorage {
+        /// Reads `path`, starting empty if it does not exist yet.
+        ///
+        /// # Errors
+        ///
+        /// Fails if the file cannot be read or is not eframe's storage.
+        pub fn open(path: impl Into<PathBuf>) -> Result<Self, StorageError> {
+            let path = path.into();
+            let entries = match std::fs::read_to_string(&path) {
+                Ok(raw) => ron::from_str(&raw).map_err(std::io::Error::other)?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+                Err(e) => return Err(e.into()),
+            };
+            Ok(Self { path, entries })
+        }
+    }
+
+    impl StorageRead for AppFileStorage {
+        fn get(&self, key: &str) -> Option<String> {
+            self.entries
+                .get(key)
+                .filter(|value| !value.is_empty())
+                .cloned()
+        }
+
+        fn keys(&self) -> Vec<String> {
+            keys::ALL
+                .into_iter()
+                .filter(|key| self.get(key).is_some())
+                .map(str::to_owned)
+                .collect()
+        }
+    }
+
+    impl StorageBackend for AppFileStorage {
+        fn set(&mut self, key: &str, value: String) -> Result<(), StorageError> {
+            self.entries.insert(key.to_owned(), value);
+            Ok(())
+        }
+
+        fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+            // As eframe, which cannot remove a key.
+            self.entries.insert(key.to_owned(), String::new());
+            Ok(())
+        }
+
+        /// Rewrites the whole file, as eframe does.
+        fn flush(&mut self) -> Result<(), StorageError> {
+            let raw = ron::ser::to_string_pretty(&self.entries, ron::ser::PrettyConfig::default())
+                .map_err(std::io::Error::other)?;
+            if let Some(dir) = self.path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let tmp = self.path.with_extension("ron.tmp");
+            std::fs::write(&tmp, raw)?;
+            std::fs::rename(tmp, &self.path)?;
+            Ok(())
+        }
+    }
+}
+
+/// Opens the save given on a command line: eframe's `app.ron` file, a
+/// database for `.sqlite` and `.db` files with the `sqlite` feature, a folder
+/// otherwise.
+///
+/// # Errors
+///
+/// Fails if the save cannot be opened.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_path(path: std::path::PathBuf) -> Result<Box<dyn StorageBackend>, StorageError> {
+    if path.extension().is_some_and(|ext| ext == "ron") {
+        return Ok(Box::new(AppFileStorage::open(path)?));
+    }
+    Ok(StorageChoice::for_path(path)
+        .open()?
+        .expect("Only the app storage opens to nothing"))
+}
+
+/// Where the user asked the games to be saved.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum StorageChoice {
+    #[default]
+    AppStorage,
+    Folder(std::path::PathBuf),
+    #[cfg(feature = "sqlite")]
+    Database(std::path::PathBuf),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl StorageChoice {
+    /// The choice remembered in eframe's storage.
+    #[cfg(feature = "gui")]
+    pub fn remembered(storage: &dyn eframe::Storage) -> Self {
+        let path = |key| {
+            storage
+                .get_string(key)
+                .filter(|path| !path.is_empty())
+                .map(std::path::PathBuf::from)
+        };
+        #[cfg(feature = "sqlite")]
+        if let Some(path) = path(keys::SAVE_DATABASE) {
+            return Self::Database(path);
+        }
+        path(keys::SAVE_DIR).map_or(Self::AppStorage, Self::Folder)
+    }
+
+    /// Remembers the choice in eframe's storage for the next start.
+    #[cfg(feature = "gui")]
+    pub fn remember(&self, storage: &mut dyn eframe::Storage) {
+        let path = |path: &std::path::Path| path.display().to_string();
+        let (dir, database) = match self {
+            Self::AppStorage => (String::new(), String::new()),
+            Self::Folder(dir) => (path(dir), String::new()),
+            #[cfg(feature = "sqlite")]
+            Self::Database(file) => (String::new(), path(file)),
+        };
+        storage.set_string(keys::SAVE_DIR, dir);
+        storage.set_string(keys::SAVE_DATABASE, database);
+        storage.flush();
+    }
+
+    /// A database for `.sqlite` and `.db` files when built with the `sqlite`
+    /// feature, a folder otherwise.
+    pub fn for_path(path: std::path::PathBuf) -> Self {
+        #[cfg(feature = "sqlite")]
+        if path
+            .extension()
+            .is_some_and(|ext| ext == "sqlite" || ext == "db")
+        {
+            return Self::Database(path);
+        }
+        Self::Folder(path)
+    }
+
+    pub fn path(&self) -> Option<&std::path::Path> {
+        match self {
+            Self::AppStorage => None,
+            Self::Folder(path) => Some(path),
+            #[cfg(feature = "sqlite")]
+            Self::Database(path) => Some(path),
+        }
+    }
+
+    /// Opens the chosen backend, `None` standing for eframe's storage.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the folder or the database cannot be opened.
+    pub fn open(&self) -> Result<Option<Box<dyn StorageBackend>>, StorageError> {
+        Ok(match self {
+            Self::AppStorage => None,
+            Self::Folder(dir) => Some(Box::new(DirectoryStorage::new(dir)?)),
+            #[cfg(feature = "sqlite")]
+            Self::Database(file) => Some(Box::new(crate::sqlite::SqliteStorage::open(file)?)),
+        })
+    }
+}
+
+#[cfg(all(feature = "gui", target_arch = "wasm32"))]
+pub use browser::BrowserStorage;
+
+#[cfg(all(feature = "gui", target_arch = "wasm32"))]
+mod browser {
+    use super::{StorageBackend, StorageError, StorageRead};
+
+    const PREFIX: &str = "whist_points/";
+
+    /// The browser local storage, each document under its own key.
+    pub struct BrowserStorage {
+        storage: web_sys::Storage,
+    }
+
+    impl BrowserStorage {
+        pub fn new() -> Option<Self> {
+            let storage = web_sys::window()?.local_storage().ok()??;
+            Some(Self { storage })
+        }
+    }
+
+    fn js_error(e: &eframe::wasm_bindgen::JsValue) -> StorageError {
+        StorageError::Browser(format!("{e:?}"))
+    }
+
+    impl StorageRead for BrowserStorage {
+        fn get(&self, key: &str) -> Option<String> {
+            self.storage.get_item(&format!("{PREFIX}{key}")).ok()?
+        }
+
+        fn keys(&self) -> Vec<String> {
+            let len = self.storage.length().unwrap_or_default();
+            (0..len)
+                .filter_map(|idx| self.storage.key(idx).ok()?)
+                .filter_map(|key| key.strip_prefix(PREFIX).map(str::to_owned))
+                .collect()
+        }
+    }
+
+    impl StorageBackend for BrowserStorage {
+        fn set(&mut self, key: &str, value: String) -> Result<(), StorageError> {
+            self.storage
+                .set_item(&format!("{PREFIX}{key}"), &value)
+                .map_err(|e| js_error(&e))
+        }
+
+        fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+            self.storage
+                .remove_item(&format!("{PREFIX}{key}"))
+                .map_err(|e| js_error(&e))
+        }
+
+        fn flush(&mut self) -> Result<(), StorageError> {
+            Ok(())
+        }
+    }
+}
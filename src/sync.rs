@@ -0,0 +1,750 @@
+//! One game kept by several devices through a relay, behind the `sync`
+//! feature.
+//!
+//! Every change is an [`Op`] sent to the relay, which orders the operations of
+//! a room and forwards them to all its devices. A device applies its own
+//! changes at once. When the relay ordered someone else's first, the pending
+//! changes are undone, the other one is applied, and the pending ones are
+//! replayed if they still apply. The relay runs the same check, so every
+//! device ends up with the same sheet.
+//!
+//! Two devices entering the same hand is the usual conflict: the second one no
+//! longer applies, and its device keeps it aside to be added anyway or dropped.
+
+use std::hash::{BuildHasher as _, RandomState};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+    mpsc::{self, Receiver, Sender},
+};
+
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use whist_game::HandRecap;
+
+use crate::{historic::HistoricEvent, session::Action, state::AppState};
+
+/// Version of the messages, checked by the relay when a device joins.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Seat permutation of a plain rename.
+const SAME_SEATS: [usize; 4] = [0, 1, 2, 3];
+
+/// A change to the game. Hands travel as JSON so that the relay does not
+/// depend on the game rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Op {
+    /// Players by seat, `origin[i]` being the previous seat of `names[i]`.
+    SetPlayers {
+        names: [String; 4],
+        origin: [usize; 4],
+    },
+    /// Appends a hand, which must become hand number `index`.
+    PushHand { index: usize, hand: Value },
+    /// Removes the last hand, which must be hand number `index`.
+    RemoveHand { index: usize },
+}
+
+impl Op {
+    /// Number of hands once applied to a game of `hands` hands, `None` if
+    /// the operation no longer applies.
+    pub fn apply_to(&self, hands: usize) -> Option<usize> {
+        match self {
+            Self::SetPlayers { .. } => Some(hands),
+            Self::PushHand { index, .. } => (*index == hands).then_some(hands + 1),
+            Self::RemoveHand { index } => (*index + 1 == hands).then_some(*index),
+        }
+    }
+}
+
+/// Device and number of the submission an operation comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Origin {
+    pub client: u64,
+    pub id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// Enters a room, asking for the operations after `since`.
+    Join {
+        version: u32,
+        room: String,
+        client: u64,
+        since: u64,
+    },
+    Submit {
+        id: u64,
+        op: Op,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RelayMessage {
+    /// Answer to a join, followed by the missed operations.
+    Welcome {
+        seq: u64,
+    },
+    /// Operation number `seq` of the room.
+    Applied {
+        seq: u64,
+        origin: Origin,
+        op: Op,
+    },
+    /// A submission that no longer applied.
+    Rejected {
+        id: u64,
+    },
+    Error(String),
+}
+
+/// What a pending operation needs to be undone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum Undo {
+    Push,
+    Remove(Value),
+    Players(Option<([String; 4], [usize; 4])>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Pending {
+    id: u64,
+    op: Op,
+    undo: Undo,
+}
+
+/// The state of a device in a room, driven by [`Replica::local`] for the
+/// changes made on the device and [`Replica::receive`] for the relay.
+///
+/// It is saved with the game, the copy of the game excepted, so that the
+/// changes not acknowledged yet are sent after a restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replica {
+    room: String,
+    client: u64,
+    /// Last operation of the room applied here.
+    seq: u64,
+    /// Hands in the room, operations still pending excluded.
+    confirmed: usize,
+    pending: Vec<Pending>,
+    next_id: u64,
+    /// Hands entered here that another device entered first.
+    conflicts: Vec<Value>,
+    /// Set while joining again from the first operation of the room, the
+    /// operations already on their way being ignored until the welcome.
+    #[serde(default)]
+    rejoining: bool,
+    #[serde(skip)]
+    outbox: Vec<ClientMessage>,
+    /// Hand events of the game already handled.
+    #[serde(skip)]
+    seen_events: usize,
+    /// Copy of the hands and players of the game, to undo removals and
+    /// renames that are no longer in the game.
+    #[serde(skip)]
+    hands: Vec<Value>,
+    #[serde(skip)]
+    names: Option<[String; 4]>,
+}
+
+impl Replica {
+    pub fn new(room: &str, app: &AppState) -> Self {
+        let mut replica = Self {
+            room: room.to_owned(),
+            client: RandomState::new().hash_one(std::process::id()),
+            seq: 0,
+            confirmed: 0,
+            pending: vec![],
+            next_id: 0,
+            conflicts: vec![],
+            rejoining: false,
+            outbox: vec![],
+            seen_events: 0,
+            hands: vec![],
+            names: None,
+        };
+        replica.refresh(app);
+        replica
+    }
+
+    /// Takes back a replica saved with `app`.
+    pub fn resume(mut self, app: &AppState) -> Self {
+        self.outbox.clear();
+        self.refresh(app);
+        self
+    }
+
+    pub fn room(&self) -> &str {
+        &self.room
+    }
+
+    /// Last operation of the room applied here.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Number of changes the relay did not acknowledge yet.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Hands kept aside after a conflict.
+    pub fn conflicts(&self) -> Vec<HandRecap> {
+        self.conflicts
+            .iter()
+            .filter_map(|hand| serde_json::from_value(hand.clone()).ok())
+            .collect()
+    }
+
+    /// Adds a conflicting hand as a new hand after all the others.
+    pub fn keep_conflict(&mut self, idx: usize, app: &mut AppState) {
+        if idx >= self.conflicts.len() {
+            return;
+        }
+        record(app, &self.conflicts.remove(idx));
+    }
+
+    pub fn drop_conflict(&mut self, idx: usize) {
+        if idx < self.conflicts.len() {
+            self.conflicts.remove(idx);
+        }
+    }
+
+    /// Messages to send to the relay.
+    pub fn take_outbox(&mut self) -> Vec<ClientMessage> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// To be sent on every connection to the relay.
+    pub fn join(&mut self) {
+        self.outbox.push(ClientMessage::Join {
+            version: PROTOCOL_VERSION,
+            room: self.room.clone(),
+            client: self.client,
+            since: self.seq,
+        });
+    }
+
+    /// Turns the changes made to `app` since the last call into operations.
+    pub fn local(&mut self, app: &AppState) {
+        let names = app
+            .session
+            .players_state
+            .players()
+            .and_then(|players| players.names().try_into().ok());
+        let events = app.session.historic.events_after(self.seen_events);
+        if names == self.names && events.is_empty() {
+            return;
+        }
+
+        if let Some(names) = names.filter(|names| Some(names) != self.names.as_ref()) {
+            let origin = self
+                .names
+                .as_ref()
+                .map_or(SAME_SEATS, |previous| origin(previous, &names));
+            let undo = self
+                .names
+                .clone()
+                .map(|previous| (previous, inverse(&origin)));
+            self.submit(Op::SetPlayers { names, origin }, Undo::Players(undo));
+        }
+
+        let mut len = self.hands.len();
+        for event in events {
+            match *event {
+                HistoricEvent::Pushed(idx) => match app.session.historic.get(idx).map(to_value) {
+                    Some(Ok(hand)) => {
+                        self.submit(Op::PushHand { index: idx, hand }, Undo::Push);
+                        len = idx + 1;
+                    }
+                    Some(Err(e)) => error!("{e}"),
+                    // Removed within the same frame, the removal is skipped too.
+                    None => len = idx + 1,
+                },
+                HistoricEvent::RemovedLast => {
+                    let Some(index) = len.checked_sub(1) else {
+                        continue;
+                    };
+                    len = index;
+                    // Otherwise pushed within the same frame, and never sent.
+                    if let Some(hand) = self.hands.get(index) {
+                        let undo = Undo::Remove(hand.clone());
+                        self.submit(Op::RemoveHand { index }, undo);
+                    }
+                }
+            }
+        }
+        self.refresh(app);
+    }
+
+    fn submit(&mut self, op: Op, undo: Undo) {
+        self.next_id += 1;
+        self.outbox.push(ClientMessage::Submit {
+            id: self.next_id,
+            op: op.clone(),
+        });
+        self.pending.push(Pending {
+            id: self.next_id,
+            op,
+            undo,
+        });
+    }
+
+    /// Applies a message of the relay to `app`. [`Self::local`] must be called
+    /// first, the changes made to `app` before are otherwise never sent.
+    pub fn receive(&mut self, message: RelayMessage, app: &mut AppState) {
+        match message {
+            RelayMessage::Welcome { seq } => self.welcome(seq, app),
+            RelayMessage::Applied { .. } | RelayMessage::Rejected { .. } if self.rejoining => {}
+            RelayMessage::Applied { seq, origin, op } => {
+                let Some(confirmed) = op.apply_to(self.confirmed) else {
+                    error!("Operation {seq} does not follow the game, joining again");
+                    self.rejoin(app);
+                    self.refresh(app);
+                    return;
+                };
+                self.seq = seq;
+                let own = origin.client == self.client;
+                if own && self.pending.first().is_some_and(|p| p.id == origin.id) {
+                    self.pending.remove(0);
+                    self.confirmed = confirmed;
+                    return;
+                }
+                self.rewind(app);
+                if own {
+                    self.pending.retain(|pending| pending.id != origin.id);
+                }
+                apply(&op, app);
+                self.confirmed = confirmed;
+                self.replay(app);
+            }
+            RelayMessage::Rejected { id } => {
+                // Usually the operation that made it fail came first and
+                // already turned it into a conflict.
+                if let Some(idx) = self.pending.iter().position(|pending| pending.id == id) {
+                    error!("Submission {id} rejected by the relay");
+                    self.rewind(app);
+                    if let Op::PushHand { hand, .. } = self.pending.remove(idx).op {
+                        self.conflicts.push(hand);
+                    }
+                    self.replay(app);
+                }
+            }
+            RelayMessage::Error(e) => error!("Relay: {e}"),
+        }
+        self.refresh(app);
+    }
+
+    fn welcome(&mut self, seq: u64, app: &mut AppState) {
+        self.rejoining = false;
+        if seq == 0 {
+            // New room, or a relay that forgot it: this device's game becomes
+            // the room's.
+            self.seq = 0;
+            self.confirmed = 0;
+            self.pending.clear();
+            if let Some(names) = self.names.clone() {
+                self.submit(
+                    Op::SetPlayers {
+                        names,
+                        origin: SAME_SEATS,
+                    },
+                    Undo::Players(None),
+                );
+            }
+            for (index, hand) in self.hands.clone().into_iter().enumerate() {
+                self.submit(Op::PushHand { index, hand }, Undo::Push);
+            }
+        } else if self.seq == 0 || seq < self.seq {
+            // Joining a game in progress: it replaces the hands entered here,
+            // kept aside as conflicts.
+            while !app.session.historic.is_empty() {
+                if let Err(e) = app.apply(Action::RemoveLastHand) {
+                    error!("{e}");
+                    break;
+                }
+            }
+            self.conflicts.append(&mut self.hands);
+            self.pending.clear();
+            self.confirmed = 0;
+            if self.seq != 0 {
+                // The relay forgot operations this device saw, start over.
+                self.seq = 0;
+                self.join();
+            }
+        } else {
+            // Back online, the relay ignores what it already has.
+            for pending in &self.pending {
+                self.outbox.push(ClientMessage::Submit {
+                    id: pending.id,
+                    op: pending.op.clone(),
+                });
+            }
+        }
+    }
+
+    /// Starts over from the first operation of the room, after one that does
+    /// not follow the game here. The confirmed hands come back from the
+    /// relay, the pending ones are kept aside as conflicts.
+    fn rejoin(&mut self, app: &mut AppState) {
+        self.rewind(app);
+        for pending in std::mem::take(&mut self.pending) {
+            if let Op::PushHand { hand, .. } = pending.op {
+                self.conflicts.push(hand);
+            }
+        }
+        while !app.session.historic.is_empty() {
+            if let Err(e) = app.apply(Action::RemoveLastHand) {
+                error!("{e}");
+                break;
+            }
+        }
+        self.seq = 0;
+        self.confirmed = 0;
+        self.rejoining = true;
+        self.join();
+    }
+
+    /// Undoes the pending operations, latest first.
+    fn rewind(&self, app: &mut AppState) {
+        for pending in self.pending.iter().rev() {
+            match &pending.undo {
+                Undo::Push => run(app, Action::RemoveLastHand),
+                Undo::Remove(hand) => record(app, hand),
+                Undo::Players(Some((names, origin))) => run(
+                    app,
+                    Action::SetPlayers {
+                        names: names.clone(),
+                        origin: *origin,
+                    },
+                ),
+                Undo::Players(None) => {}
+            }
+        }
+    }
+
+    /// Applies again the pending operations that still apply after the
+    /// confirmed ones. A hand that no longer does becomes a conflict.
+    fn replay(&mut self, app: &mut AppState) {
+        let mut len = self.confirmed;
+        let pending = std::mem::take(&mut self.pending);
+        for pending in pending {
+            if let Some(new_len) = pending.op.apply_to(len) {
+                len = new_len;
+                apply(&pending.op, app);
+                self.pending.push(pending);
+            } else if let Op::PushHand { hand, .. } = pending.op {
+                self.conflicts.push(hand);
+            }
+        }
+    }
+
+    /// Takes a copy of the game, after which its current events count as
+    /// handled.
+    fn refresh(&mut self, app: &AppState) {
+        self.names = app
+            .session
+            .players_state
+            .players()
+            .and_then(|players| players.names().try_into().ok());
+        self.hands.clear();
+        for (hand, _) in &app.session.historic {
+            match to_value(hand) {
+                Ok(hand) => self.hands.push(hand),
+                Err(e) => error!("{e}"),
+            }
+        }
+        self.seen_events = app.session.historic.event_count();
+    }
+}
+
+fn apply(op: &Op, app: &mut AppState) {
+    match op {
+        Op::SetPlayers { names, origin } => run(
+            app,
+            Action::SetPlayers {
+                names: names.clone(),
+                origin: *origin,
+            },
+        ),
+        Op::PushHand { hand, .. } => record(app, hand),
+        Op::RemoveHand { .. } => run(app, Action::RemoveLastHand),
+    }
+}
+
+/// Applies `action` to the game, logging why it does not apply.
+fn run(app: &mut AppState, action: Action) {
+    if let Err(e) = app.apply(action) {
+        error!("{e}");
+    }
+}
+
+/// Records a hand received as JSON.
+fn record(app: &mut AppState, hand: &Value) {
+    match serde_json::from_value(hand.clone()) {
+        Ok(hand) => run(app, Action::RecordHand(hand)),
+        Err(e) => error!("{e}"),
+    }
+}
+
+fn to_value(hand: &HandRecap) -> serde_json::Result<Value> {
+    serde_json::to_value(hand)
+}
+
+/// Previous seat of each new name. Names that were not seated are renames,
+/// given the seats left in order.
+fn origin(previous: &[String; 4], names: &[String; 4]) -> [usize; 4] {
+    let mut origin: [Option<usize>; 4] = std::array::from_fn(|seat| {
+        previous
+            .iter()
+            .position(|name| Some(name) == names.get(seat))
+    });
+    let free: Vec<usize> = (0..4)
+        .filter(|seat| !origin.contains(&Some(*seat)))
+        .collect();
+    let mut free = free.into_iter();
+    for slot in &mut origin {
+        if slot.is_none() {
+            *slot = free.next();
+        }
+    }
+    origin.map(|seat| seat.unwrap_or_default())
+}
+
+/// Inverse permutation of `origin`, putting the players back where they were.
+fn inverse(origin: &[usize; 4]) -> [usize; 4] {
+    let mut inverse = SAME_SEATS;
+    for (seat, previous) in origin.iter().enumerate() {
+        if let Some(slot) = inverse.get_mut(*previous) {
+            *slot = seat;
+        }
+    }
+    inverse
+}
+
+/// Connection state of a [`SyncSession`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Connecting,
+    Online,
+    /// Disconnected for the given reason, retrying.
+    Offline(String),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+enum Incoming {
+    Connected,
+    Message(RelayMessage),
+    Disconnected(String),
+}
+
+/// What is saved of a [`SyncSession`] to join its room again on the next
+/// start.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSync {
+    pub url: String,
+    pub replica: Replica,
+}
+
+/// A game kept in sync through the relay at `url`. Changes made while offline
+/// are sent once the relay is reachable again.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct SyncSession {
+    pub replica: Replica,
+    url: String,
+    status: Status,
+    outgoing: Sender<String>,
+    incoming: Receiver<Incoming>,
+    stop: Arc<AtomicBool>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SyncSession {
+    /// Joins `room` on the relay at `url`, e.g. `ws://192.168.1.10:9001`.
+    pub fn connect(url: &str, room: &str, app: &AppState) -> Self {
+        Self::start(url, Replica::new(room, app))
+    }
+
+    /// Joins again the room of a session saved with `app`.
+    pub fn resume(saved: SavedSync, app: &AppState) -> Self {
+        Self::start(&saved.url, saved.replica.resume(app))
+    }
+
+    fn start(url: &str, replica: Replica) -> Self {
+        let (outgoing, outgoing_rx) = mpsc::channel();
+        let (incoming_tx, incoming) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let spawned = std::thread::Builder::new()
+            .name("sync".to_owned())
+            .spawn({
+                let url = url.to_owned();
+                let stop = Arc::clone(&stop);
+                move || transport::run(&url, &outgoing_rx, &incoming_tx, &stop)
+            })
+            .map_err(|e| e.to_string());
+        Self {
+            replica,
+            url: url.to_owned(),
+            status: spawned.map_or_else(Status::Offline, |_thread| Status::Connecting),
+            outgoing,
+            incoming,
+            stop,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn saved(&self) -> SavedSync {
+        SavedSync {
+            url: self.url.clone(),
+            replica: self.replica.clone(),
+        }
+    }
+
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    /// Sends the changes made to `app` and applies the ones of the others.
+    pub fn step(&mut self, app: &mut AppState) {
+        self.replica.local(app);
+        while let Ok(incoming) = self.incoming.try_recv() {
+            match incoming {
+                Incoming::Connected => {
+                    self.status = Status::Online;
+                    self.replica.join();
+                }
+                Incoming::Message(message) => self.replica.receive(message, app),
+                Incoming::Disconnected(reason) => self.status = Status::Offline(reason),
+            }
+        }
+        for message in self.replica.take_outbox() {
+            // Offline, the join and the pending changes are sent again on
+            // reconnection.
+            if self.status != Status::Online {
+                continue;
+            }
+            match serde_json::to_string(&message) {
+                Ok(text) => {
+                    if self.outgoing.send(text).is_err() {
+                        self.status = Status::Offline("Connection thread stopped".to_owned());
+                    }
+                }
+                Err(e) => error!("{e}"),
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for SyncSession {
+    /// The connection thread ends on its own, without waiting for a relay
+    /// that may not answer.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod transport {
+    use std::{
+        io::ErrorKind,
+        net::TcpStream,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc::{Receiver, Sender},
+        },
+        time::Duration,
+    };
+
+    use tungstenite::{Message, WebSocket, stream::MaybeTlsStream};
+
+    use super::{Incoming, RelayMessage};
+
+    /// How long a read waits before outgoing messages are looked at.
+    const POLL: Duration = Duration::from_millis(50);
+
+    const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+    /// Keeps a connection to the relay until `stop` is set, reconnecting
+    /// with a growing delay.
+    pub fn run(
+        url: &str,
+        outgoing: &Receiver<String>,
+        incoming: &Sender<Incoming>,
+        stop: &AtomicBool,
+    ) {
+        let mut backoff = POLL;
+        while !stop.load(Ordering::Relaxed) {
+            let reason = match tungstenite::connect(url) {
+                Ok((mut socket, _)) => {
+                    backoff = POLL;
+                    if incoming.send(Incoming::Connected).is_err() {
+                        return;
+                    }
+                    serve(&mut socket, outgoing, incoming, stop)
+                }
+                Err(e) => e.to_string(),
+            };
+            if incoming.send(Incoming::Disconnected(reason)).is_err() {
+                return;
+            }
+            // Sent again once connected.
+            while outgoing.try_recv().is_ok() {}
+            let mut waited = Duration::ZERO;
+            while waited < backoff && !stop.load(Ordering::Relaxed) {
+                std::thread::sleep(POLL);
+                waited += POLL;
+            }
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    /// Relays messages both ways, returning why the connection ended.
+    fn serve(
+        socket: &mut WebSocket<MaybeTlsStream<TcpStream>>,
+        outgoing: &Receiver<String>,
+        incoming: &Sender<Incoming>,
+        stop: &AtomicBool,
+    ) -> String {
+        if let MaybeTlsStream::Plain(stream) = socket.get_mut()
+            && let Err(e) = stream.set_read_timeout(Some(POLL))
+        {
+            return e.to_string();
+        }
+        loop {
+            if stop.load(Ordering::Relaxed) {
+                if let Err(e) = socket.close(None) {
+                    log::debug!("{e}");
+                }
+                return "Disconnected".to_owned();
+            }
+            while let Ok(text) = outgoing.try_recv() {
+                if let Err(e) = socket.send(Message::text(text)) {
+                    return e.to_string();
+                }
+            }
+            match socket.read() {
+                Ok(Message::Text(text)) => match serde_json::from_str::<RelayMessage>(&text) {
+                    Ok(message) => {
+                        if incoming.send(Incoming::Message(message)).is_err() {
+                            return "Disconnected".to_owned();
+                        }
+                    }
+                    Err(e) => log::error!("Unreadable relay message: {e}"),
+                },
+                Ok(Message::Close(_)) => return "Closed by the relay".to_owned(),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e))
+                    if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(e) => return e.to_string(),
+            }
+        }
+    }
+}
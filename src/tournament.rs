@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize, Serializer};
+use thiserror::Error;
+use whist_game::Players;
+
+use crate::{
+    archive::positions,
+    historic::HandsHistoric,
+    session::{self, PlayersState, Session, SessionError},
+};
+
+#[derive(Debug, Error)]
+pub enum TournamentError {
+    #[error("Empty player name")]
+    EmptyName,
+    #[error("Registrations are closed once the first round started")]
+    RegistrationsClosed,
+    #[error("{0} is already registered")]
+    AlreadyRegistered(String),
+    #[error("A round needs a multiple of 4 players and every table of the previous round finished")]
+    CannotStartRound,
+    #[error("Unknown player {0}")]
+    UnknownPlayer(usize),
+    #[error(transparent)]
+    Session(#[from] SessionError),
+}
+
+/// One table of a round, indices refer to [`Tournament::players`].
+#[derive(Deserialize)]
+#[serde(from = "SavedTable")]
+pub struct TableGame {
+    pub seats: [usize; 4],
+    pub session: Session,
+    pub finished: bool,
+}
+
+/// A table as saved, its players being always seated.
+#[derive(Deserialize)]
+struct SavedTable {
+    seats: [usize; 4],
+    players: Players,
+    historic: HandsHistoric,
+    finished: bool,
+}
+
+#[derive(Serialize)]
+struct SavedTableRef<'a> {
+    seats: &'a [usize; 4],
+    players: &'a Players,
+    historic: &'a HandsHistoric,
+    finished: bool,
+}
+
+impl From<SavedTable> for TableGame {
+    fn from(table: SavedTable) -> Self {
+        Self {
+            seats: table.seats,
+            session: Session {
+                players_state: PlayersState::Playing(table.players),
+                historic: table.historic,
+                ..Default::default()
+            },
+            finished: table.finished,
+        }
+    }
+}
+
+impl Serialize for TableGame {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SavedTableRef {
+            seats: &self.seats,
+            players: self
+                .session
+                .players_state
+                .players()
+                .expect("Tables are seated when created"),
+            historic: &self.session.historic,
+            finished: self.finished,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl TableGame {
+    fn new(seats: [usize; 4], names: &[String]) -> Result<Self, TournamentError> {
+        let seat_names: Vec<String> = seats
+            .iter()
+            .map(|&idx| {
+                names
+                    .get(idx)
+                    .cloned()
+                    .ok_or(TournamentError::UnknownPlayer(idx))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            seats,
+            session: Session {
+                players_state: PlayersState::Playing(session::build_players(&seat_names)?),
+                ..Default::default()
+            },
+            finished: false,
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Round {
+    pub tables: Vec<TableGame>,
+}
+
+impl Round {
+    pub fn is_finished(&self) -> bool {
+        self.tables.iter().all(|table| table.finished)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TournamentStanding {
+    pub name: String,
+    pub points: i32,
+    pub table_wins: usize,
+    pub positions_sum: usize,
+    pub best_table: Option<i16>,
+}
+
+/// Several tables played at once, with players rotating between rounds.
+#[derive(Default, Deserialize, Serialize)]
+pub struct Tournament {
+    pub players: Vec<String>,
+    pub rounds: Vec<Round>,
+}
+
+impl Tournament {
+    /// Adds a player before the first round.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the name is empty or already taken, or once a round started.
+    pub fn register(&mut self, name: &str) -> Result<(), TournamentError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(TournamentError::EmptyName);
+        }
+        if !self.rounds.is_empty() {
+            return Err(TournamentError::RegistrationsClosed);
+        }
+        if self.players.iter().any(|player| player == name) {
+            return Err(TournamentError::AlreadyRegistered(name.to_owned()));
+        }
+        self.players.push(name.to_owned());
+        Ok(())
+    }
+
+    pub fn unregister(&mut self, idx: usize) {
+        if self.rounds.is_empty() && idx < self.players.len() {
+            self.players.remove(idx);
+        }
+    }
+
+    pub fn can_start_round(&self) -> bool {
+        self.players.len() >= 4
+            && self.players.len() % 4 == 0
+            && self.rounds.last().is_none_or(Round::is_finished)
+    }
+
+    /// How many times each pair of players already shared a table.
+    fn meetings(&self) -> Vec<Vec<usize>> {
+        let n = self.players.len();
+        let mut meetings = vec![vec![0; n]; n];
+        for table in self.rounds.iter().flat_map(|round| &round.tables) {
+            for &a in &table.seats {
+                for &b in &table.seats {
+                    if a != b
+                        && let Some(count) = meetings.get_mut(a).and_then(|row| row.get_mut(b))
+                    {
+                        *count += 1;
+                    }
+                }
+            }
+        }
+        meetings
+    }
+
+    /// Seats players for the next round, greedily grouping those who met the
+    /// least so far.
+    fn next_assignment(&self) -> Vec<[usize; 4]> {
+        let n = self.players.len();
+        let meetings = self.meetings();
+        let met = |a: usize, b: usize| meetings.get(a).and_then(|row| row.get(b)).copied();
+        // Rotating the starting order spreads the first pick between rounds.
+        let mut remaining: Vec<usize> = (0..n).map(|i| (i + self.rounds.len()) % n).collect();
+        let mut tables = vec![];
+        while !remaining.is_empty() {
+            let mut table = vec![remaining.remove(0)];
+            while table.len() < 4 {
+                let (pos, _) = remaining
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|&(_, &candidate)| {
+                        table
+                            .iter()
+                            .map(|&seated| met(seated, candidate).unwrap_or_default())
+                            .sum::<usize>()
+                    })
+                    .expect("Players count is a multiple of 4");
+                table.push(remaining.remove(pos));
+            }
+            // Rotate seats so that nobody always sits first and deals.
+            table.rotate_left(self.rounds.len() % 4);
+            tables.push(std::array::from_fn(|seat| {
+                *table.get(seat).expect("4 seats")
+            }));
+        }
+        tables
+    }
+
+    /// Seats every player at a table of a new round.
+    ///
+    /// # Errors
+    ///
+    /// Fails unless [`Self::can_start_round`].
+    pub fn start_round(&mut self) -> Result<(), TournamentError> {
+        if !self.can_start_round() {
+            return Err(TournamentError::CannotStartRound);
+        }
+        let tables = self
+            .next_assignment()
+            .into_iter()
+            .map(|seats| TableGame::new(seats, &self.players))
+            .collect::<Result<_, _>>()?;
+        self.rounds.push(Round { tables });
+        Ok(())
+    }
+
+    /// Overall ranking over finished tables. Ties are broken by table wins,
+    /// then by the sum of finishing positions, then by the best table total.
+    pub fn standings(&self) -> Vec<TournamentStanding> {
+        let mut standings: Vec<TournamentStanding> = self
+            .players
+            .iter()
+            .map(|name| TournamentStanding {
+                name: name.clone(),
+                points: 0,
+                table_wins: 0,
+                positions_sum: 0,
+                best_table: None,
+            })
+            .collect();
+        let finished = self
+            .rounds
+            .iter()
+            .flat_map(|round| &round.tables)
+            .filter(|table| table.finished);
+        for table in finished {
+            let totals = table.session.historic.totals();
+            let positions = positions(&totals);
+            for (seat, &player) in table.seats.iter().enumerate() {
+                let Some(standing) = standings.get_mut(player) else {
+                    continue;
+                };
+                let total = *totals.get(seat).expect("4 seats");
+                let position = *positions.get(seat).expect("4 seats");
+                standing.points += i32::from(total);
+                standing.positions_sum += position;
+                standing.best_table =
+                    Some(standing.best_table.map_or(total, |best| best.max(total)));
+                if position == 1 {
+                    standing.table_wins += 1;
+                }
+            }
+        }
+        standings.sort_by(|a, b| {
+            b.points
+                .cmp(&a.points)
+                .then(b.table_wins.cmp(&a.table_wins))
+                .then(a.positions_sum.cmp(&b.positions_sum))
+                .then(b.best_table.cmp(&a.best_table))
+                .then(a.name.cmp(&b.name))
+        });
+        standings
+    }
+}
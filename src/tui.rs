@@ -0,0 +1,559 @@
+//! Terminal frontend, behind the `tui` feature.
+//!
+//! Follows the flows of the app window with the keyboard: players first, then
+//! the score table from which hands are added and looked at. The game is the
+//! app's own state, saved like the CLI does to a folder or a database that the
+//! desktop app can open too.
+
+use std::io;
+
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize as _},
+    text::Line,
+    widgets::{Block, Clear, List, ListState, Paragraph, Row, Table, TableState},
+};
+
+use crate::{
+    PlayersState,
+    session::{Action, HandDraft, Outcome},
+    state::AppState,
+    storage::StorageBackend,
+};
+
+/// Field of the new hand wizard having the focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Contractors,
+    Bid,
+    Tricks,
+    Points(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Table,
+    Contracts,
+    /// New hand being entered, the draft being the app's.
+    Wizard(Field),
+    /// Waiting for the user to confirm that the game is over.
+    ConfirmFinish,
+}
+
+pub struct Tui {
+    app: AppState,
+    storage: Box<dyn StorageBackend>,
+    mode: Mode,
+    rows: TableState,
+    contracts: ListState,
+    /// Last error, shown until the next key.
+    status: String,
+    quit: bool,
+}
+
+impl Tui {
+    pub fn new(app: AppState, storage: Box<dyn StorageBackend>) -> Self {
+        let rows = TableState::new().with_selected(app.session.historic.len().checked_sub(1));
+        Self {
+            app,
+            storage,
+            mode: Mode::Table,
+            rows,
+            contracts: ListState::default(),
+            status: String::new(),
+            quit: false,
+        }
+    }
+
+    pub fn app(&self) -> &AppState {
+        &self.app
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Draws and handles keys until the user quits, then saves the game.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the terminal cannot be used or the game cannot be saved.
+    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                self.handle_key(key);
+            }
+        }
+        self.app.save_snapshot(self.storage.as_mut());
+        self.storage.flush().map_err(io::Error::other)
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        self.status.clear();
+        if self.app.hand_detail.is_some() {
+            if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+                self.app.hand_detail = None;
+            }
+        } else if matches!(self.app.session.players_state, PlayersState::Building(_)) {
+            self.players_key(key.code);
+        } else {
+            match std::mem::replace(&mut self.mode, Mode::Table) {
+                Mode::Table => self.table_key(key.code),
+                Mode::Contracts => self.contracts_key(key.code),
+                Mode::Wizard(field) => self.wizard_key(field, key.code),
+                Mode::ConfirmFinish => self.confirm_finish_key(key.code),
+            }
+        }
+        self.app.persist(self.storage.as_mut());
+    }
+
+    /// Applies `action` to the game, the error going to the status line.
+    fn apply(&mut self, action: Action) -> Option<Outcome> {
+        self.app
+            .apply(action)
+            .map_err(|e| self.status = e.to_string())
+            .ok()
+    }
+
+    fn draft(&self) -> Option<&HandDraft> {
+        self.app.session.draft.as_ref()
+    }
+
+    fn players_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char(c) => self.app.player_field.push(c),
+            KeyCode::Backspace => {
+                self.app.player_field.pop();
+            }
+            KeyCode::Tab => {
+                // Cycles through the known players not seated yet.
+                let known = self.known_players();
+                let next = known
+                    .iter()
+                    .position(|name| *name == self.app.player_field)
+                    .map_or(0, |idx| idx + 1);
+                if let Some(name) = known.get(next).or_else(|| known.first()) {
+                    self.app.player_field.clone_from(name);
+                }
+            }
+            KeyCode::Enter => {
+                let name = std::mem::take(&mut self.app.player_field);
+                self.apply(Action::AddPlayer(name.trim().to_owned()));
+            }
+            KeyCode::Esc => self.quit = true,
+            _ => {}
+        }
+    }
+
+    fn known_players(&self) -> Vec<String> {
+        let PlayersState::Building(builder) = &self.app.session.players_state else {
+            return vec![];
+        };
+        self.app
+            .roster
+            .iter()
+            .filter(|name| !builder.players.iter().any(|p| &p.name == *name))
+            .cloned()
+            .collect()
+    }
+
+    fn table_key(&mut self, code: KeyCode) {
+        let len = self.app.session.historic.len();
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                let row = self.rows.selected().map_or(0, |row| row.saturating_sub(1));
+                self.rows.select((len > 0).then_some(row));
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let row = self.rows.selected().map_or(0, |row| row + 1);
+                self.rows
+                    .select(len.checked_sub(1).map(|last| row.min(last)));
+            }
+            KeyCode::Enter => self.app.hand_detail = self.rows.selected().filter(|row| *row < len),
+            KeyCode::Char('n') => {
+                self.contracts.select(Some(self.app.current_contract_idx));
+                self.mode = Mode::Contracts;
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                self.apply(Action::RemoveLastHand);
+                self.rows
+                    .select(self.app.session.historic.len().checked_sub(1));
+            }
+            KeyCode::Char('f') => self.mode = Mode::ConfirmFinish,
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            _ => {}
+        }
+    }
+
+    /// Archives the game on `y`, any other key keeping it.
+    fn confirm_finish_key(&mut self, code: KeyCode) {
+        if code != KeyCode::Char('y') {
+            return;
+        }
+        if self.app.finish_game() {
+            self.rows.select(None);
+        } else {
+            self.status = "No hand to archive".to_owned();
+        }
+    }
+
+    fn contracts_key(&mut self, code: KeyCode) {
+        let len = self.app.session.contracts.len();
+        if len == 0 {
+            self.status = "No contract to choose from".to_owned();
+            return;
+        }
+        let selected = self.contracts.selected().unwrap_or_default();
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.contracts.select(Some((selected + len - 1) % len));
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.contracts.select(Some((selected + 1) % len));
+            }
+            KeyCode::Enter => {
+                // As the New hand button.
+                self.app.current_contract_idx = selected;
+                if self.apply(Action::StartHand(selected)).is_some() {
+                    self.mode = Mode::Wizard(Field::Contractors);
+                }
+                return;
+            }
+            KeyCode::Esc => return,
+            _ => {}
+        }
+        self.mode = Mode::Contracts;
+    }
+
+    fn wizard_key(&mut self, mut field: Field, code: KeyCode) {
+        let Some(draft) = self.draft() else {
+            return;
+        };
+        let fields = wizard_fields(draft);
+        let (bid, bid_range, tricks, points) = (
+            draft.bid(),
+            draft.bid_range(),
+            draft.tricks(),
+            draft.points(),
+        );
+        let focus = fields.iter().position(|f| *f == field).unwrap_or_default();
+        let step = match code {
+            KeyCode::Left | KeyCode::Char('-') if field != Field::Contractors => -1,
+            KeyCode::Right | KeyCode::Char('+') => 1,
+            _ => 0,
+        };
+
+        match (code, field) {
+            (KeyCode::Esc, _) => {
+                self.apply(Action::CancelHand);
+                return;
+            }
+            (KeyCode::Enter, _) => {
+                if self.apply(Action::SubmitHand).is_some() {
+                    self.rows
+                        .select(self.app.session.historic.len().checked_sub(1));
+                    return;
+                }
+            }
+            (KeyCode::Tab | KeyCode::Down, _) => {
+                field = *fields.get(focus + 1).unwrap_or(&Field::Contractors);
+            }
+            (KeyCode::BackTab | KeyCode::Up, _) => {
+                field = *fields
+                    .get(focus.checked_sub(1).unwrap_or(fields.len() - 1))
+                    .expect("Within the fields");
+            }
+            (KeyCode::Char(c @ '1'..='4'), Field::Contractors) => {
+                self.apply(Action::ToggleContractor(usize::from(c as u8 - b'1')));
+            }
+            (_, Field::Bid) if step != 0 => {
+                let range = bid_range.unwrap_or(0..=0);
+                let bid = bid
+                    .saturating_add_signed(step)
+                    .clamp(*range.start(), *range.end());
+                self.apply(Action::SetBid(bid));
+            }
+            (_, Field::Tricks) if step != 0 => {
+                let tricks = tricks.saturating_add_signed(step).min(13);
+                self.apply(Action::SetTricks(tricks));
+            }
+            (_, Field::Points(idx)) => {
+                if let Some(mut points) = points
+                    && let Some(point) = points.get_mut(idx)
+                {
+                    *point = edit_points(*point, code);
+                    self.apply(Action::SetPoints(points));
+                }
+            }
+            _ => {}
+        }
+        self.mode = Mode::Wizard(field);
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame<'_>) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .areas(frame.area());
+        frame.render_widget(Line::from("Whist Points").bold(), header);
+
+        let help = if let PlayersState::Building(builder) = &self.app.session.players_state {
+            let mut lines = vec![
+                Line::from(format!("Add a new player: {}_", self.app.player_field)),
+                Line::default(),
+            ];
+            lines.extend(
+                builder
+                    .players
+                    .iter()
+                    .map(|player| Line::from(format!("Player: {}", player.name))),
+            );
+            let known = self.known_players();
+            if !known.is_empty() {
+                lines.push(Line::default());
+                lines.push(Line::from(format!("Known players: {}", known.join(", "))));
+            }
+            frame.render_widget(
+                Paragraph::new(lines).block(Block::bordered().title(" Players ")),
+                body,
+            );
+            "Enter add · Tab known players · Esc quit"
+        } else {
+            self.draw_table(frame, body);
+            match self.mode {
+                Mode::Table => {
+                    "n new hand · ↑↓ select · Enter details · d remove last · f finish game · q quit"
+                }
+                Mode::Contracts => {
+                    self.draw_contracts(frame, body);
+                    "↑↓ select · Enter choose · Esc cancel"
+                }
+                Mode::Wizard(field) => {
+                    self.draw_wizard(frame, body, field);
+                    "1-4 contractors · Tab next · ←→ change · Enter ok · Esc cancel"
+                }
+                Mode::ConfirmFinish => "Archive the game and start a new one? y yes · any key no",
+            }
+        };
+        if self.app.hand_detail.is_some() {
+            self.draw_detail(frame, body);
+        }
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(self.status.as_str()).red(),
+                Line::from(help).dim(),
+            ]),
+            footer,
+        );
+    }
+
+    fn draw_table(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let Some(players) = self.app.session.players_state.players() else {
+            return;
+        };
+        let names = players.names();
+        let historic = &self.app.session.historic;
+        let header = Row::new(
+            ["#".to_owned(), "Contract".to_owned()].into_iter().chain(
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(seat, name)| historic.seat_header(seat, name)),
+            ),
+        )
+        .bold();
+        let rows = historic
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (hand, scores))| {
+                Row::new(
+                    [(idx + 1).to_string(), hand.gamemode_name.clone()]
+                        .into_iter()
+                        .chain(scores.iter().map(i16::to_string)),
+                )
+            });
+        let dealer = names.get(historic.len() % 4).expect("Always 4 players");
+        let contract = self
+            .app
+            .session
+            .contracts
+            .get(self.app.current_contract_idx)
+            .map_or("", |contract| contract.name.as_str());
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(4),
+                Constraint::Length(12),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::new().reversed())
+        .block(Block::bordered().title(format!(
+            " Dealer: {dealer} · Game played: {} · Gamemode: {contract} ",
+            historic.len()
+        )));
+        frame.render_stateful_widget(table, area, &mut self.rows);
+    }
+
+    fn draw_contracts(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let names: Vec<&str> = self
+            .app
+            .session
+            .contracts
+            .iter()
+            .map(|contract| contract.name.as_str())
+            .collect();
+        let area = popup(area, 30, u16::try_from(names.len() + 2).unwrap_or(u16::MAX));
+        let list = List::new(names)
+            .highlight_style(Style::new().reversed())
+            .block(Block::bordered().title(" Select gamemode "));
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.contracts);
+    }
+
+    fn draw_wizard(&self, frame: &mut Frame<'_>, area: Rect, field: Field) {
+        let (Some(draft), Some(players)) = (self.draft(), self.app.session.players_state.players())
+        else {
+            return;
+        };
+        let names = players.names();
+        let focused = |f: Field, line: Line<'static>| {
+            if f == field { line.reversed() } else { line }
+        };
+        let range = draft.contractors_range();
+        let mut lines = vec![focused(
+            Field::Contractors,
+            Line::from(format!(
+                "Select contractors ({} to {})",
+                range.start(),
+                range.end()
+            )),
+        )];
+        for (seat, name) in names.iter().enumerate() {
+            let mark = if draft.is_contractor(seat) { "x" } else { " " };
+            lines.push(Line::from(format!("  [{mark}] {} {name}", seat + 1)));
+        }
+        lines.push(Line::default());
+        if draft.bid_range().is_some() {
+            lines.push(focused(
+                Field::Bid,
+                Line::from(format!("Tricks to win ? {}", draft.bid())),
+            ));
+        }
+        lines.push(focused(
+            Field::Tricks,
+            Line::from(format!("Tricks number {}", draft.tricks())),
+        ));
+        if let Some(points) = draft.points() {
+            lines.push(Line::default());
+            lines.push(Line::from("Custom points input"));
+            for (idx, (&seat, point)) in draft.contractors().iter().zip(points).enumerate() {
+                let name = names.get(seat).map_or("", String::as_str);
+                lines.push(focused(
+                    Field::Points(idx),
+                    Line::from(format!("  {name} {point}")),
+                ));
+            }
+        }
+        let title = format!(" {} ", draft.contract().name);
+        let area = popup(area, 40, u16::try_from(lines.len() + 2).unwrap_or(u16::MAX));
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            area,
+        );
+    }
+
+    fn draw_detail(&self, frame: &mut Frame<'_>, area: Rect) {
+        let (Some(row_idx), Some(players)) = (
+            self.app.hand_detail,
+            self.app.session.players_state.players(),
+        ) else {
+            return;
+        };
+        let historic = &self.app.session.historic;
+        let Some(hand) = historic.get(row_idx) else {
+            return;
+        };
+        let names = historic.seat_names_at(row_idx, &players.names());
+        let mut lines = vec![Line::from(format!("Mode: {}", hand.gamemode_name))];
+        if let Some(bid) = hand.bid {
+            lines.push(Line::from(format!("Bid: {bid}")));
+        }
+        for sub in historic
+            .substitutions()
+            .iter()
+            .filter(|sub| sub.from_hand == row_idx)
+        {
+            lines.push(Line::from(format!(
+                "{} replaces {}",
+                sub.name, sub.previous
+            )));
+        }
+        lines.push(Line::from("Tricks:"));
+        for (id, tricks) in &hand.contractors_tricks {
+            let name = names.get(id.idx()).map_or("", String::as_str);
+            lines.push(Line::from(format!("  {name} {tricks}")));
+        }
+        let area = popup(area, 40, u16::try_from(lines.len() + 2).unwrap_or(u16::MAX));
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(format!(" Hand {} ", row_idx + 1))),
+            area,
+        );
+    }
+}
+
+/// Fields of the wizard in focus order.
+fn wizard_fields(draft: &HandDraft) -> Vec<Field> {
+    let mut fields = vec![Field::Contractors];
+    if draft.bid_range().is_some() {
+        fields.push(Field::Bid);
+    }
+    fields.push(Field::Tricks);
+    if draft.points().is_some() {
+        fields.extend((0..3).map(Field::Points));
+    }
+    fields
+}
+
+/// Custom points after a key, typed digit by digit within the app's range.
+fn edit_points(point: i16, code: KeyCode) -> i16 {
+    let point = match code {
+        KeyCode::Char(c @ '0'..='9') => {
+            let digit = i16::from(c as u8 - b'0');
+            let magnitude = point.abs().saturating_mul(10).saturating_add(digit);
+            if point < 0 { -magnitude } else { magnitude }
+        }
+        KeyCode::Char('-') => -point,
+        KeyCode::Backspace => point / 10,
+        KeyCode::Left => point - 1,
+        KeyCode::Right | KeyCode::Char('+') => point + 1,
+        _ => point,
+    };
+    point.clamp(-240, 240)
+}
+
+/// Area of `width` by `height` in the middle of `area`.
+fn popup(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
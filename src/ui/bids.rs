@@ -0,0 +1,112 @@
+use whist_game::contracts::Contract;
+
+use crate::{
+    archive::ArchivedGame,
+    bids::{BidReport, BidStats},
+    heatmap::Scope,
+    historic::HandsHistoric,
+    league::Season,
+    ui::heatmap::scope_ui,
+};
+
+/// Window of the bid statistics per player, see [`BidReport`].
+#[derive(Debug, Default)]
+pub struct BidsView {
+    scope: Scope,
+}
+
+impl BidsView {
+    /// Returns `false` once the window is closed.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        historic: &HandsHistoric,
+        names: &[String],
+        archive: &[ArchivedGame],
+        seasons: &[Season],
+        contracts: &[Contract],
+    ) -> bool {
+        let report = BidReport::new(self.scope, historic, names, archive, seasons, contracts);
+        let mut open = true;
+        egui::Window::new("Bids").open(&mut open).show(ctx, |ui| {
+            scope_ui(ui, &mut self.scope, seasons);
+            ui.separator();
+            if report.is_empty() {
+                ui.label("No bid yet");
+            } else {
+                egui::ScrollArea::horizontal().show(ui, |ui| grid_ui(ui, &report));
+            }
+        });
+        open
+    }
+}
+
+fn grid_ui(ui: &mut egui::Ui, report: &BidReport) {
+    egui::Grid::new("bids_grid").striped(true).show(ui, |ui| {
+        for title in [
+            "Player",
+            "Bids",
+            "Made %",
+            "Over",
+            "Under",
+            "Max bid %",
+            "Aggressiveness",
+            "Tricks vs bid",
+        ] {
+            ui.strong(title);
+        }
+        ui.end_row();
+        for stats in &report.players {
+            ui.label(&stats.name);
+            ui.label(format!("{}", stats.bids));
+            ui.label(percent(stats.success_rate()));
+            ui.label(mean(stats.mean_overtricks()));
+            ui.label(mean(stats.mean_undertricks()));
+            ui.label(percent(stats.max_bid_rate()));
+            aggressiveness_ui(ui, stats);
+            ui.label(distribution(stats));
+            ui.end_row();
+        }
+    });
+}
+
+fn percent(rate: Option<f64>) -> String {
+    rate.map_or_else(|| "—".to_owned(), |rate| format!("{:.0}", rate * 100.0))
+}
+
+fn mean(value: Option<f64>) -> String {
+    value.map_or_else(|| "—".to_owned(), |value| format!("{value:.1}"))
+}
+
+fn aggressiveness_ui(ui: &mut egui::Ui, stats: &BidStats) {
+    let value = stats.aggressiveness().unwrap_or_default();
+    let label = if value >= 2.0 / 3.0 {
+        "Bold"
+    } else if value >= 1.0 / 3.0 {
+        "Balanced"
+    } else {
+        "Cautious"
+    };
+    ui.add(
+        egui::ProgressBar::new(value as f32)
+            .desired_width(80.0)
+            .text(label),
+    )
+    .on_hover_text("Position of the bids within the allowed range");
+}
+
+/// Bids by tricks collected minus tricks bid, e.g. `-1×2 0×5 +2×1`.
+fn distribution(stats: &BidStats) -> String {
+    stats
+        .distribution
+        .iter()
+        .map(|(difference, count)| {
+            if *difference == 0 {
+                format!("0×{count}")
+            } else {
+                format!("{difference:+}×{count}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
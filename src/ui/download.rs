@@ -0,0 +1,36 @@
+//! Files handed to the user: written to the app folder on desktop, downloaded
+//! by the browser on the web.
+
+/// Saves `bytes` as `name`, returns where they went.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(name: &str, bytes: &[u8]) -> Result<String, String> {
+    let dir = eframe::storage_dir("Whist Points").unwrap_or_else(|| std::path::PathBuf::from("."));
+    let path = dir.join(name);
+    std::fs::create_dir_all(&dir)
+        .and_then(|()| std::fs::write(&path, bytes))
+        .map_err(|e| e.to_string())?;
+    Ok(format!("Saved to {}", path.display()))
+}
+
+/// Saves `bytes` as `name`, returns where they went.
+#[cfg(target_arch = "wasm32")]
+pub fn save(name: &str, bytes: &[u8]) -> Result<String, String> {
+    use eframe::wasm_bindgen::{JsCast as _, JsValue};
+
+    let js_error = |e: JsValue| format!("{e:?}");
+    let parts = web_sys::js_sys::Array::of1(&web_sys::js_sys::Uint8Array::from(bytes));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts).map_err(js_error)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+    let anchor = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("No document to download from")?
+        .create_element("a")
+        .map_err(js_error)?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|_element| "Not a link element".to_owned())?;
+    anchor.set_href(&url);
+    anchor.set_download(name);
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url).map_err(js_error)?;
+    Ok(format!("Downloaded {name}"))
+}
@@ -0,0 +1,110 @@
+use egui::Color32;
+
+use crate::{
+    archive::ArchivedGame,
+    heatmap::{ContractCell, Heatmap, Scope},
+    historic::HandsHistoric,
+    league::Season,
+};
+
+const LOST: Color32 = Color32::from_rgb(200, 60, 60);
+const WON: Color32 = Color32::from_rgb(60, 160, 60);
+
+/// Window crossing players with the contracts they call, see [`Heatmap`].
+#[derive(Debug, Default)]
+pub struct HeatmapView {
+    scope: Scope,
+}
+
+impl HeatmapView {
+    /// Returns `false` once the window is closed.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        historic: &HandsHistoric,
+        names: &[String],
+        archive: &[ArchivedGame],
+        seasons: &[Season],
+    ) -> bool {
+        let heatmap = Heatmap::new(self.scope, historic, names, archive, seasons);
+        let mut open = true;
+        egui::Window::new("Contracts")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                scope_ui(ui, &mut self.scope, seasons);
+                ui.label("Colour: success rate, from red to green. Strength: how often called.");
+                ui.separator();
+                if heatmap.is_empty() {
+                    ui.label("No contract called yet");
+                } else {
+                    egui::ScrollArea::horizontal().show(ui, |ui| grid_ui(ui, &heatmap));
+                }
+            });
+        open
+    }
+}
+
+/// Picks the games to analyse: the current one, a season or all of them.
+pub fn scope_ui(ui: &mut egui::Ui, selected: &mut Scope, seasons: &[Season]) {
+    let label = |scope: Scope| match scope {
+        Scope::CurrentGame => "Current game".to_owned(),
+        Scope::Season(idx) => seasons
+            .get(idx)
+            .map_or_else(String::new, |season| format!("Season {}", season.name)),
+        Scope::AllTime => "All time".to_owned(),
+    };
+    egui::ComboBox::from_label("Games")
+        .selected_text(label(*selected))
+        .show_ui(ui, |ui| {
+            let scopes = std::iter::once(Scope::CurrentGame)
+                .chain((0..seasons.len()).map(Scope::Season))
+                .chain(std::iter::once(Scope::AllTime));
+            for scope in scopes {
+                ui.selectable_value(selected, scope, label(scope));
+            }
+        });
+}
+
+fn grid_ui(ui: &mut egui::Ui, heatmap: &Heatmap) {
+    let max_calls = heatmap.max_calls();
+    egui::Grid::new("heatmap_grid").show(ui, |ui| {
+        ui.label("");
+        for contract in &heatmap.contracts {
+            ui.strong(contract);
+        }
+        ui.end_row();
+        for player in &heatmap.players {
+            ui.strong(player);
+            for contract in &heatmap.contracts {
+                match heatmap.cell(player, contract).filter(|cell| cell.calls > 0) {
+                    Some(cell) => cell_ui(ui, cell, max_calls),
+                    None => {
+                        ui.label("");
+                    }
+                }
+            }
+            ui.end_row();
+        }
+    });
+}
+
+fn cell_ui(ui: &mut egui::Ui, cell: &ContractCell, max_calls: usize) {
+    let rate = cell.success_rate().unwrap_or_default();
+    let strength = 0.25 + 0.75 * cell.calls as f32 / max_calls.max(1) as f32;
+    let fill = LOST
+        .lerp_to_gamma(WON, rate as f32)
+        .gamma_multiply(strength);
+    egui::Frame::new()
+        .fill(fill)
+        .inner_margin(4.0)
+        .show(ui, |ui| {
+            ui.label(format!(
+                "{} · {:.0}% · {:+.1}",
+                cell.calls,
+                rate * 100.0,
+                cell.average_delta().unwrap_or_default()
+            ))
+        })
+        .response
+        .on_hover_text("Calls · won · average points");
+}
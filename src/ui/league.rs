@@ -0,0 +1,154 @@
+use egui::{ModalResponse, pos2, vec2};
+
+use crate::league::{ArchiveSummary, ContractSummary, INITIAL_RATING, Leaderboard, LeagueEntry};
+
+/// Leaderboard modal. Clicking a player shows their rating history. The
+/// figures of the storage, if any, are shown below.
+pub fn leaderboard_ui(
+    ui: &egui::Ui,
+    board: &Leaderboard,
+    summary: Option<&ArchiveSummary>,
+    selected: &mut Option<String>,
+) -> ModalResponse<()> {
+    egui::Modal::new("leaderboard".into()).show(ui.ctx(), |ui| {
+        ui.heading("League");
+        ui.separator();
+        if board.entries.is_empty() {
+            ui.label("No finished game yet");
+        }
+        egui::Grid::new("leaderboard_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for title in [
+                    "#", "Player", "Rating", "Points", "Games", "Avg pos", "Win %",
+                ] {
+                    ui.strong(title);
+                }
+                ui.end_row();
+                for (rank, entry) in board.entries.iter().enumerate() {
+                    ui.label(format!("{}", rank + 1));
+                    let is_selected = selected.as_ref() == Some(&entry.name);
+                    if ui.selectable_label(is_selected, &entry.name).clicked() {
+                        *selected = (!is_selected).then(|| entry.name.clone());
+                    }
+                    ui.label(format!("{:.0}", entry.rating));
+                    ui.label(format!("{}", entry.total_points));
+                    ui.label(format!("{}", entry.games));
+                    ui.label(format!("{:.2}", entry.average_position()));
+                    ui.label(format!("{:.0}", entry.win_rate() * 100.0));
+                    ui.end_row();
+                }
+            });
+
+        if let Some(entry) = board
+            .entries
+            .iter()
+            .find(|e| Some(&e.name) == selected.as_ref())
+        {
+            ui.separator();
+            ui.label(format!("Rating history of {}", entry.name));
+            rating_graph(ui, entry);
+        }
+
+        if let Some(summary) = summary {
+            egui::CollapsingHeader::new("From the database").show(ui, |ui| {
+                egui::Grid::new("player_summaries")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for title in ["Player", "Points", "Games", "Wins", "Avg pos"] {
+                            ui.strong(title);
+                        }
+                        ui.end_row();
+                        for player in &summary.players {
+                            ui.label(&player.name);
+                            ui.label(format!("{}", player.points));
+                            ui.label(format!("{}", player.games));
+                            ui.label(format!("{}", player.wins));
+                            ui.label(format!("{:.2}", player.average_position));
+                            ui.end_row();
+                        }
+                    });
+            });
+        }
+
+        egui::Sides::new().show(
+            ui,
+            |_| {},
+            |ui| {
+                if ui.button("Ok").clicked() {
+                    ui.close();
+                }
+            },
+        );
+    })
+}
+
+/// How often each contract of the archive was played and made.
+pub fn contract_summaries_ui(ui: &mut egui::Ui, contracts: &[ContractSummary]) {
+    egui::Grid::new("contract_summaries")
+        .striped(true)
+        .show(ui, |ui| {
+            for title in ["Contract", "Hands", "Made"] {
+                ui.strong(title);
+            }
+            ui.end_row();
+            for contract in contracts {
+                ui.label(&contract.name);
+                ui.label(format!("{}", contract.hands));
+                ui.label(format!("{}", contract.won));
+                ui.end_row();
+            }
+        });
+}
+
+fn rating_graph(ui: &mut egui::Ui, entry: &LeagueEntry) {
+    let (response, painter) = ui.allocate_painter(vec2(300.0, 120.0), egui::Sense::hover());
+    let rect = response.rect;
+    painter.rect_stroke(
+        rect,
+        0.0,
+        ui.visuals().widgets.noninteractive.bg_stroke,
+        egui::StrokeKind::Inside,
+    );
+
+    let ratings: Vec<f64> = std::iter::once(INITIAL_RATING)
+        .chain(entry.history.iter().map(|(_, rating)| *rating))
+        .collect();
+    let min = ratings.iter().copied().fold(INITIAL_RATING, f64::min) - 10.0;
+    let max = ratings.iter().copied().fold(INITIAL_RATING, f64::max) + 10.0;
+    let steps = (ratings.len() - 1).max(1) as f32;
+    let points: Vec<egui::Pos2> = ratings
+        .iter()
+        .enumerate()
+        .map(|(i, rating)| {
+            let x = rect.left() + rect.width() * i as f32 / steps;
+            let y = rect.bottom() - rect.height() * ((rating - min) / (max - min)) as f32;
+            pos2(x, y)
+        })
+        .collect();
+
+    let baseline = rect.bottom() - rect.height() * ((INITIAL_RATING - min) / (max - min)) as f32;
+    painter.hline(
+        rect.x_range(),
+        baseline,
+        ui.visuals().widgets.noninteractive.bg_stroke,
+    );
+    painter.line(
+        points,
+        egui::Stroke::new(2.0, ui.visuals().selection.bg_fill),
+    );
+    painter.text(
+        rect.left_top(),
+        egui::Align2::LEFT_TOP,
+        format!("{max:.0}"),
+        egui::FontId::proportional(10.0),
+        ui.visuals().text_color(),
+    );
+    painter.text(
+        rect.left_bottom(),
+        egui::Align2::LEFT_BOTTOM,
+        format!("{min:.0}"),
+        egui::FontId::proportional(10.0),
+        ui.visuals().text_color(),
+    );
+}
@@ -1,41 +1,24 @@
+pub mod bids;
+pub mod download;
 pub mod hands;
+pub mod heatmap;
+pub mod league;
+pub mod partnership;
+pub mod players;
+pub mod qr;
+pub mod recovery;
+pub mod replay;
 pub mod requester;
-use std::error::Error;
-
-use egui::emath::Numeric;
+pub mod seasons;
+pub mod settlement;
+pub mod share;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod spectator;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod storage;
+#[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+pub mod sync;
+pub mod tournament;
+pub use crate::error::AppError;
 pub use hands::HandBuilderGUI;
-use thiserror::Error;
-use whist_game::Tricks;
-
-#[derive(Debug, Error)]
-pub enum AppError {
-    #[error("An error has occur: {0}")]
-    ImpossibleState(String),
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub struct TricksGui(Tricks);
-
-impl TricksGui {
-    pub fn new(num: u8) -> Result<Self, Box<dyn Error>> {
-        let tricks = Tricks::new(num)?;
-        Ok(Self(tricks))
-    }
-}
-
-impl Numeric for TricksGui {
-    const INTEGRAL: bool = true;
-    const MAX: Self = Self(Tricks::MAX_TRICKS);
-    const MIN: Self = Self(Tricks::MIN_TRICKS);
-
-    #[inline(always)]
-    fn to_f64(self) -> f64 {
-        self.0.get() as f64
-    }
-
-    #[inline(always)]
-    fn from_f64(num: f64) -> Self {
-        let tricks = Tricks::new(num as u8).unwrap_or(Tricks::MIN_TRICKS);
-        Self(tricks)
-    }
-}
+pub use players::{PlayersEditor, SubstitutionEditor};
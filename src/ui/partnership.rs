@@ -0,0 +1,75 @@
+use crate::partnership::Partnerships;
+
+/// Matrix of the pairs: hands played together, success rate and average
+/// combined points.
+pub fn pairs_ui(ui: &mut egui::Ui, partnerships: &Partnerships) {
+    let names = &partnerships.names;
+    egui::Grid::new("pairs_grid").striped(true).show(ui, |ui| {
+        ui.label("");
+        for name in names {
+            ui.strong(name);
+        }
+        ui.end_row();
+        for first in names {
+            ui.strong(first);
+            for second in names {
+                let pair = partnerships
+                    .pair(first, second)
+                    .filter(|pair| pair.hands > 0);
+                match pair {
+                    Some(pair) if first != second => {
+                        ui.label(format!(
+                            "{} · {:.0}% · {:+.1}",
+                            pair.hands,
+                            pair.success_rate().unwrap_or_default() * 100.0,
+                            pair.average_points().unwrap_or_default()
+                        ))
+                        .on_hover_text("Hands together · won · average points");
+                    }
+                    _ => {
+                        ui.label("—");
+                    }
+                }
+            }
+            ui.end_row();
+        }
+    });
+}
+
+/// How each player (rows) fares against the contracts of each opponent
+/// (columns).
+pub fn head_to_head_ui(ui: &mut egui::Ui, partnerships: &Partnerships) {
+    let names = &partnerships.names;
+    egui::Grid::new("head_to_head_grid")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Defender \\ Contractor");
+            for name in names {
+                ui.strong(name);
+            }
+            ui.end_row();
+            for defender in names {
+                ui.strong(defender);
+                for contractor in names {
+                    let entry = partnerships
+                        .head_to_head(defender, contractor)
+                        .filter(|entry| entry.hands > 0);
+                    match entry {
+                        Some(entry) => {
+                            ui.label(format!(
+                                "{} · {:.0}% · {:+.1}",
+                                entry.hands,
+                                entry.defence_rate().unwrap_or_default() * 100.0,
+                                entry.average_points().unwrap_or_default()
+                            ))
+                            .on_hover_text("Hands · contracts defeated · average points");
+                        }
+                        None => {
+                            ui.label("—");
+                        }
+                    }
+                }
+                ui.end_row();
+            }
+        });
+}
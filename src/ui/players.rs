@@ -0,0 +1,152 @@
+use egui::ModalResponse;
+
+/// Seats and names as edited in the players modal.
+///
+/// `origin[i]` is the seat the player now shown at seat `i` was sitting at
+/// when the editor was opened.
+#[derive(Debug, Clone)]
+pub struct PlayersEditor {
+    pub names: [String; 4],
+    pub origin: [usize; 4],
+}
+
+impl PlayersEditor {
+    pub fn new(names: &[String]) -> Self {
+        Self {
+            names: std::array::from_fn(|i| names.get(i).cloned().unwrap_or_default()),
+            origin: [0, 1, 2, 3],
+        }
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.names.swap(a, b);
+        self.origin.swap(a, b);
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.names.iter().enumerate().all(|(i, name)| {
+            !name.trim().is_empty() && !self.names.iter().skip(i + 1).any(|n| n == name)
+        })
+    }
+
+    /// Returns `true` once the user confirmed the edition.
+    pub fn ui(&mut self, ui: &egui::Ui) -> ModalResponse<bool> {
+        egui::Modal::new("edit_players".into()).show(ui.ctx(), |ui| {
+            ui.label("Edit players");
+            ui.separator();
+            egui::Grid::new("edit_players_grid").show(ui, |ui| {
+                for seat in 0..4 {
+                    ui.label(format!("Seat {}", seat + 1));
+                    ui.text_edit_singleline(self.names.get_mut(seat).expect("4 seats"));
+                    if ui.add_enabled(seat > 0, egui::Button::new("⏶")).clicked() {
+                        self.swap(seat, seat - 1);
+                    }
+                    if ui.add_enabled(seat < 3, egui::Button::new("⏷")).clicked() {
+                        self.swap(seat, seat + 1);
+                    }
+                    ui.end_row();
+                }
+            });
+
+            let (_, confirmed) = egui::Sides::new().show(
+                ui,
+                |ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+                },
+                |ui| {
+                    let clicked = ui
+                        .add_enabled(self.is_valid(), egui::Button::new("Ok"))
+                        .on_disabled_hover_text("Names must be unique and not empty")
+                        .clicked();
+                    if clicked {
+                        ui.close();
+                    }
+                    clicked
+                },
+            );
+            confirmed
+        })
+    }
+}
+
+/// Substitution being recorded in the substitution modal.
+#[derive(Debug, Clone)]
+pub struct SubstitutionEditor {
+    pub seat: usize,
+    pub name: String,
+    pub from_hand: usize,
+}
+
+impl SubstitutionEditor {
+    pub fn new(hands_played: usize) -> Self {
+        Self {
+            seat: 0,
+            name: String::new(),
+            from_hand: hands_played,
+        }
+    }
+
+    /// Returns `true` once the user confirmed the substitution.
+    pub fn ui(
+        &mut self,
+        ui: &egui::Ui,
+        names: &[String],
+        earliest: &[usize; 4],
+        hands_played: usize,
+    ) -> ModalResponse<bool> {
+        egui::Modal::new("substitution".into()).show(ui.ctx(), |ui| {
+            ui.label("Player substitution");
+            ui.separator();
+            egui::ComboBox::from_label("Leaving player")
+                .selected_text(names.get(self.seat).cloned().unwrap_or_default())
+                .show_ui(ui, |ui| {
+                    for (seat, name) in names.iter().enumerate() {
+                        ui.selectable_value(&mut self.seat, seat, name);
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.label("Replaced by");
+                ui.text_edit_singleline(&mut self.name);
+            });
+            let first = earliest.get(self.seat).copied().unwrap_or_default();
+            let available = first <= hands_played;
+            if available {
+                ui.horizontal(|ui| {
+                    ui.label("From hand");
+                    let mut hand_number = self.from_hand.clamp(first, hands_played) + 1;
+                    ui.add(
+                        egui::DragValue::new(&mut hand_number)
+                            .range(first + 1..=hands_played + 1)
+                            .speed(0.05),
+                    );
+                    self.from_hand = hand_number - 1;
+                });
+            } else {
+                ui.label("This seat was already substituted for the next hand");
+            }
+
+            let valid = available && !self.name.trim().is_empty() && !names.contains(&self.name);
+            let (_, confirmed) = egui::Sides::new().show(
+                ui,
+                |ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+                },
+                |ui| {
+                    let clicked = ui
+                        .add_enabled(valid, egui::Button::new("Ok"))
+                        .on_disabled_hover_text("The new player needs a new name")
+                        .clicked();
+                    if clicked {
+                        ui.close();
+                    }
+                    clicked
+                },
+            );
+            confirmed
+        })
+    }
+}
@@ -0,0 +1,77 @@
+use egui::{Color32, Rect, Sense, vec2};
+
+use crate::{qr::QrImage, ui::download};
+
+/// Pixels per module of the exported image.
+const EXPORT_SCALE: usize = 8;
+
+/// Window showing a link as a QR code.
+#[derive(Debug)]
+pub struct QrWindow {
+    code: Result<QrImage, String>,
+    status: Option<String>,
+}
+
+impl QrWindow {
+    pub fn new(link: &str) -> Self {
+        Self {
+            code: QrImage::new(link).map_err(|e| e.to_string()),
+            status: None,
+        }
+    }
+
+    /// Returns `false` once the window is closed.
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        let mut open = true;
+        egui::Window::new("QR code")
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                match &self.code {
+                    Ok(code) => {
+                        ui.label("Scan it with another device to open the game.");
+                        paint(ui, code);
+                        if ui.button("Export PNG").clicked() {
+                            let exported = code
+                                .to_png(EXPORT_SCALE)
+                                .map_err(|e| e.to_string())
+                                .and_then(|png| download::save(&file_name(), &png));
+                            self.status = Some(exported.unwrap_or_else(|e| e));
+                        }
+                    }
+                    Err(e) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e);
+                    }
+                }
+                if let Some(status) = &self.status {
+                    ui.label(status);
+                }
+            });
+        open
+    }
+}
+
+fn paint(ui: &mut egui::Ui, code: &QrImage) {
+    let side = ui.available_width().clamp(200.0, 400.0);
+    let (rect, _) = ui.allocate_exact_size(vec2(side, side), Sense::hover());
+    let painter = ui.painter_at(rect);
+    // Codes are always dark on light, whatever the theme.
+    painter.rect_filled(rect, 0.0, Color32::WHITE);
+    let module = side / code.size() as f32;
+    for y in 0..code.size() {
+        for x in 0..code.size() {
+            if code.is_dark(x, y) {
+                let min = rect.min + vec2(x as f32, y as f32) * module;
+                painter.rect_filled(
+                    Rect::from_min_size(min, vec2(module, module)),
+                    0.0,
+                    Color32::BLACK,
+                );
+            }
+        }
+    }
+}
+
+fn file_name() -> String {
+    format!("whist-game-{}.png", crate::date::Date::today())
+}
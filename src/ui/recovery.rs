@@ -0,0 +1,80 @@
+use crate::{
+    save::Quarantine,
+    storage::{Format, keys},
+    ui::download,
+};
+
+pub enum RecoveryAction {
+    Salvage,
+    Discard,
+}
+
+/// Screen shown instead of the game when the saved state could not be read.
+pub fn recovery_ui(
+    ui: &mut egui::Ui,
+    quarantine: &Quarantine,
+    salvage_error: Option<&str>,
+    export_status: &mut Option<String>,
+) -> Option<RecoveryAction> {
+    let mut action = None;
+    if quarantine.key == keys::APP {
+        ui.heading("The saved game could not be read");
+    } else {
+        ui.heading(format!("The saved {} could not be read", quarantine.key));
+    }
+    ui.label(
+        "It was kept aside instead of being replaced. Export it before salvaging or \
+         discarding it if you want to keep a copy.",
+    );
+    ui.separator();
+    ui.label(format!("Error: {}", quarantine.error));
+    ui.label(format!("Saved data: {} bytes", quarantine.raw.len()));
+    ui.separator();
+
+    ui.horizontal_wrapped(|ui| {
+        if ui.button("Copy raw data").clicked() {
+            ui.ctx().copy_text(quarantine.raw.clone());
+        }
+        if ui.button("Export to file").clicked() {
+            *export_status = Some(export(quarantine).unwrap_or_else(|e| e));
+        }
+    });
+    if let Some(status) = export_status {
+        ui.label(status.as_str());
+    }
+    ui.separator();
+
+    if quarantine.can_salvage() {
+        ui.label("Partial salvage keeps players and hand scores, contracts are reset.");
+    }
+    if let Some(e) = salvage_error {
+        ui.colored_label(ui.visuals().error_fg_color, format!("Salvage failed: {e}"));
+    }
+    ui.horizontal(|ui| {
+        if quarantine.can_salvage() {
+            if ui.button("Try partial salvage").clicked() {
+                action = Some(RecoveryAction::Salvage);
+            }
+            if ui.button("Discard and start a new game").clicked() {
+                action = Some(RecoveryAction::Discard);
+            }
+        } else if ui.button("Discard and continue").clicked() {
+            action = Some(RecoveryAction::Discard);
+        }
+    });
+    action
+}
+
+/// Saves the unreadable data as a file, returns where it went.
+fn export(quarantine: &Quarantine) -> Result<String, String> {
+    let extension = match quarantine.format {
+        Format::Ron => "ron",
+        Format::Json => "json",
+    };
+    let name = format!(
+        "unreadable-{}-{}.{extension}",
+        quarantine.key,
+        crate::date::Date::today()
+    );
+    download::save(&name, quarantine.raw.as_bytes())
+}
@@ -0,0 +1,175 @@
+use std::time::Duration;
+
+use crate::{date::Date, replay::Replay, scoreboard::Movement, ui::download};
+
+/// Seconds between two hands when playing automatically.
+const AUTOPLAY_INTERVAL: f64 = 1.5;
+
+/// Window stepping through a game, see [`Replay`].
+#[derive(Debug)]
+pub struct ReplayView {
+    replay: Replay,
+    title: String,
+    /// Time of the last step while playing automatically.
+    autoplay: Option<f64>,
+    status: Option<String>,
+}
+
+impl ReplayView {
+    pub fn new(title: String, replay: Replay) -> Self {
+        Self {
+            replay,
+            title,
+            autoplay: None,
+            status: None,
+        }
+    }
+
+    /// Returns `false` once the window is closed.
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        self.autoplay_step(ctx);
+        let mut open = true;
+        egui::Window::new(format!("Replay: {}", self.title))
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                self.controls_ui(ui);
+                ui.separator();
+                self.board_ui(ui);
+                ui.separator();
+                self.export_ui(ui);
+            });
+        open
+    }
+
+    fn autoplay_step(&mut self, ctx: &egui::Context) {
+        let Some(last) = self.autoplay else {
+            return;
+        };
+        let now = ctx.input(|i| i.time);
+        if now - last >= AUTOPLAY_INTERVAL {
+            self.replay.forward();
+            self.autoplay = Some(now);
+        }
+        if self.replay.is_at_end() {
+            self.autoplay = None;
+        } else {
+            ctx.request_repaint_after(Duration::from_secs_f64(AUTOPLAY_INTERVAL));
+        }
+    }
+
+    fn controls_ui(&mut self, ui: &mut egui::Ui) {
+        let replay = &mut self.replay;
+        ui.horizontal(|ui| {
+            let (at_start, at_end) = (replay.is_at_start(), replay.is_at_end());
+            if ui
+                .add_enabled(!at_start, egui::Button::new("⏮"))
+                .on_hover_text("First hand")
+                .clicked()
+            {
+                replay.seek(0);
+            }
+            if ui
+                .add_enabled(!at_start, egui::Button::new("◀"))
+                .on_hover_text("Previous hand")
+                .clicked()
+            {
+                replay.back();
+            }
+            let playing = self.autoplay.is_some();
+            if ui
+                .add_enabled(
+                    !at_end || playing,
+                    egui::Button::new(if playing { "⏸" } else { "▶" }),
+                )
+                .on_hover_text(if playing { "Pause" } else { "Play" })
+                .clicked()
+            {
+                self.autoplay = (!playing).then(|| ui.input(|i| i.time));
+            }
+            if ui
+                .add_enabled(!at_end, egui::Button::new("▶|"))
+                .on_hover_text("Next hand")
+                .clicked()
+            {
+                replay.forward();
+            }
+            if ui
+                .add_enabled(!at_end, egui::Button::new("⏭"))
+                .on_hover_text("Last hand")
+                .clicked()
+            {
+                replay.seek(replay.len());
+            }
+        });
+        let mut position = replay.position();
+        if ui
+            .add(egui::Slider::new(&mut position, 0..=replay.len()).text("hands"))
+            .changed()
+        {
+            replay.seek(position);
+        }
+    }
+
+    fn board_ui(&self, ui: &mut egui::Ui) {
+        let board = self.replay.board();
+        match &board.last_contract {
+            Some(contract) => ui.label(format!(
+                "Hand {} of {}: {contract}",
+                board.hands,
+                self.replay.len()
+            )),
+            None => ui.label("Before the first hand"),
+        };
+        egui::Grid::new("replay_board")
+            .striped(true)
+            .show(ui, |ui| {
+                for title in ["#", "Player", "Total", "Hand", ""] {
+                    ui.strong(title);
+                }
+                ui.end_row();
+                for row in board.ranked() {
+                    ui.label(format!("{}", row.rank));
+                    ui.label(&row.name);
+                    ui.label(format!("{}", row.total));
+                    match row.last_delta {
+                        Some(delta) if delta > 0 => {
+                            ui.colored_label(egui::Color32::DARK_GREEN, format!("{delta:+}"))
+                        }
+                        Some(delta) if delta < 0 => {
+                            ui.colored_label(ui.visuals().error_fg_color, format!("{delta:+}"))
+                        }
+                        Some(_) => ui.label("0"),
+                        None => ui.label(""),
+                    };
+                    if row.last_delta.is_some() && row.movement != Movement::Same {
+                        ui.label(row.movement.arrow());
+                    } else {
+                        ui.label("");
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn export_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Copy snapshot").clicked() {
+                ui.ctx().copy_text(self.replay.snapshot());
+                self.status = Some("Copied".to_owned());
+            }
+            if ui.button("Export snapshot").clicked() {
+                let name = format!(
+                    "whist-replay-{}-hand-{}.txt",
+                    Date::today(),
+                    self.replay.position()
+                );
+                let exported = download::save(&name, self.replay.snapshot().as_bytes());
+                self.status = Some(exported.unwrap_or_else(|e| e));
+            }
+        });
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use egui::ModalResponse;
+
+use crate::{
+    archive::ArchivedGame,
+    date::Date,
+    league::{Season, StandingsRule},
+};
+
+pub enum SeasonAction {
+    Create(Season),
+    Close(usize),
+}
+
+/// Seasons modal: standings of the selected season and season management.
+#[derive(Debug)]
+pub struct SeasonsView {
+    pub selected: usize,
+    name: String,
+    start: String,
+    rule: StandingsRule,
+}
+
+impl SeasonsView {
+    pub fn new(seasons: &[Season]) -> Self {
+        let start = seasons
+            .last()
+            .and_then(|season| season.end)
+            .map_or_else(Date::today, |end| end.add_days(1));
+        Self {
+            selected: seasons.len().saturating_sub(1),
+            name: format!("Season {}", seasons.len() + 1),
+            start: start.to_string(),
+            rule: StandingsRule::default(),
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &egui::Ui,
+        seasons: &[Season],
+        games: &[ArchivedGame],
+    ) -> ModalResponse<Option<SeasonAction>> {
+        egui::Modal::new("seasons".into()).show(ui.ctx(), |ui| {
+            let mut action = None;
+            ui.heading("Seasons");
+            ui.separator();
+
+            if let Some(season) = seasons.get(self.selected) {
+                egui::ComboBox::from_label("Season")
+                    .selected_text(&season.name)
+                    .show_ui(ui, |ui| {
+                        for (idx, season) in seasons.iter().enumerate() {
+                            ui.selectable_value(&mut self.selected, idx, &season.name);
+                        }
+                    });
+                let end = season
+                    .end
+                    .map_or("running".to_owned(), |end| end.to_string());
+                ui.label(format!("{} → {end}", season.start));
+                ui.label(format!("{}", season.rule));
+                standings_grid(ui, season, games);
+                if !season.is_closed() && ui.button("Close season").clicked() {
+                    action = Some(SeasonAction::Close(self.selected));
+                }
+            } else {
+                ui.label("No season yet");
+            }
+
+            ui.separator();
+            let running = seasons.iter().any(|season| !season.is_closed());
+            if running {
+                ui.label("Close the running season to start a new one");
+            } else if let Some(create) = self.new_season_ui(ui) {
+                action = Some(create);
+            }
+
+            egui::Sides::new().show(
+                ui,
+                |_| {},
+                |ui| {
+                    if ui.button("Ok").clicked() {
+                        ui.close();
+                    }
+                },
+            );
+            action
+        })
+    }
+
+    fn new_season_ui(&mut self, ui: &mut egui::Ui) -> Option<SeasonAction> {
+        ui.label("New season");
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut self.name);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Start (YYYY-MM-DD)");
+            ui.text_edit_singleline(&mut self.start);
+        });
+
+        let kinds = [
+            StandingsRule::default(),
+            StandingsRule::RawTotals,
+            StandingsRule::BestEvenings(5),
+        ];
+        egui::ComboBox::from_label("Scoring")
+            .selected_text(format!("{}", self.rule))
+            .show_ui(ui, |ui| {
+                for kind in kinds {
+                    let same_kind =
+                        std::mem::discriminant(&kind) == std::mem::discriminant(&self.rule);
+                    if ui.selectable_label(same_kind, format!("{kind}")).clicked() && !same_kind {
+                        self.rule = kind;
+                    }
+                }
+            });
+        match &mut self.rule {
+            StandingsRule::PositionPoints(points) => {
+                ui.horizontal(|ui| {
+                    for point in points {
+                        ui.add(egui::DragValue::new(point).range(-20..=20).speed(0.05));
+                    }
+                });
+            }
+            StandingsRule::RawTotals => {}
+            StandingsRule::BestEvenings(n) => {
+                ui.horizontal(|ui| {
+                    ui.label("Evenings counted");
+                    ui.add(egui::DragValue::new(n).range(1..=52).speed(0.05));
+                });
+            }
+        }
+
+        let start = self.start.parse::<Date>();
+        if let Err(e) = &start {
+            ui.label(format!("{e}"));
+        }
+        let valid = start.is_ok() && !self.name.trim().is_empty();
+        if ui
+            .add_enabled(valid, egui::Button::new("Start season"))
+            .clicked()
+        {
+            let start = start.expect("Checked above");
+            return Some(SeasonAction::Create(Season::new(
+                self.name.trim().to_owned(),
+                start,
+                self.rule,
+            )));
+        }
+        None
+    }
+}
+
+fn standings_grid(ui: &mut egui::Ui, season: &Season, games: &[ArchivedGame]) {
+    egui::Grid::new("standings_grid")
+        .striped(true)
+        .show(ui, |ui| {
+            for title in ["#", "Player", "Score", "Games", "Evenings"] {
+                ui.strong(title);
+            }
+            ui.end_row();
+            for (rank, standing) in season.standings(games).iter().enumerate() {
+                ui.label(format!("{}", rank + 1));
+                ui.label(&standing.name);
+                ui.label(format!("{}", standing.score));
+                ui.label(format!("{}", standing.games));
+                ui.label(format!("{}", standing.evenings));
+                ui.end_row();
+            }
+        });
+}
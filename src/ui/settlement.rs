@@ -0,0 +1,78 @@
+use egui::ModalResponse;
+
+use crate::settlement::{Settlement, SettlementConfig};
+
+/// Settlement modal, the configuration is edited in place.
+pub fn settlement_ui(
+    ui: &egui::Ui,
+    config: &mut SettlementConfig,
+    totals: &[i16; 4],
+    names: &[String],
+) -> ModalResponse<()> {
+    egui::Modal::new("settlement".into()).show(ui.ctx(), |ui| {
+        ui.heading("Settlement");
+        ui.separator();
+        egui::Grid::new("settlement_config").show(ui, |ui| {
+            ui.label("Cents per point");
+            ui.add(egui::DragValue::new(&mut config.cents_per_point).range(0..=10_000));
+            ui.end_row();
+            ui.label("Coin (cents)");
+            ui.add(egui::DragValue::new(&mut config.coin).range(1..=500));
+            ui.end_row();
+            ui.label("Minimum payout (cents)");
+            ui.add(egui::DragValue::new(&mut config.min_payout).range(0..=100_000));
+            ui.end_row();
+            let mut capped = config.max_payout.is_some();
+            ui.checkbox(&mut capped, "Maximum payout (cents)");
+            let mut max = config.max_payout.unwrap_or(2_000);
+            ui.add_enabled(capped, egui::DragValue::new(&mut max).range(0..=100_000));
+            config.max_payout = capped.then_some(max);
+            ui.end_row();
+            ui.label("Currency");
+            ui.text_edit_singleline(&mut config.currency);
+            ui.end_row();
+        });
+        ui.separator();
+
+        let settlement = Settlement::new(totals, config);
+        egui::Grid::new("settlement_balances")
+            .striped(true)
+            .show(ui, |ui| {
+                for (seat, balance) in settlement.balances.iter().enumerate() {
+                    ui.label(names.get(seat).map(String::as_str).unwrap_or_default());
+                    ui.label(format!("{}", totals.get(seat).expect("4 seats")));
+                    ui.label(config.format(*balance));
+                    ui.end_row();
+                }
+            });
+        ui.separator();
+        for transfer in &settlement.transfers {
+            ui.label(format!(
+                "{} → {}: {}",
+                names
+                    .get(transfer.from)
+                    .map(String::as_str)
+                    .unwrap_or_default(),
+                names
+                    .get(transfer.to)
+                    .map(String::as_str)
+                    .unwrap_or_default(),
+                config.format(transfer.cents)
+            ));
+        }
+
+        egui::Sides::new().show(
+            ui,
+            |ui| {
+                if ui.button("Copy as text").clicked() {
+                    ui.ctx().copy_text(settlement.to_text(names, config));
+                }
+            },
+            |ui| {
+                if ui.button("Ok").clicked() {
+                    ui.close();
+                }
+            },
+        );
+    })
+}
@@ -0,0 +1,144 @@
+use egui::ModalResponse;
+
+use crate::share::SharedGame;
+
+pub enum SharedAction {
+    Import,
+    Close,
+}
+
+/// Read-only view of a game opened from a link.
+pub fn shared_game_ui(
+    ui: &mut egui::Ui,
+    shared: &SharedGame,
+    missing: &[&str],
+) -> Option<SharedAction> {
+    let mut action = None;
+    ui.heading("Shared game");
+    ui.label(format!("Read only, {} hands played", shared.historic.len()));
+    if !missing.is_empty() {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!(
+                "Unknown contracts, their hands keep their scores: {}",
+                missing.join(", ")
+            ),
+        );
+    }
+    ui.horizontal(|ui| {
+        if ui.button("Import as a new game").clicked() {
+            action = Some(SharedAction::Import);
+        }
+        if ui.button("Close").clicked() {
+            action = Some(SharedAction::Close);
+        }
+    });
+    ui.label("Importing replaces the current game, finish it first to archive it.");
+    ui.separator();
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("shared_game").striped(true).show(ui, |ui| {
+            ui.strong("Contract");
+            for name in &shared.names {
+                ui.strong(name);
+            }
+            ui.end_row();
+            for (hand, scores) in &shared.historic {
+                ui.label(&hand.gamemode_name);
+                for score in scores {
+                    ui.label(format!("{score}"));
+                }
+                ui.end_row();
+            }
+        });
+    });
+    action
+}
+
+pub enum ShareAction {
+    /// Open a received link.
+    Open(String),
+    ShowQrCode,
+    /// The address of the web app links point to was changed.
+    SetWebUrl(String),
+}
+
+/// Dialog giving the link of the current game and opening received links.
+#[derive(Debug, Default)]
+pub struct ShareDialog {
+    pub link: Option<String>,
+    web_url: String,
+    received: String,
+    pub error: Option<String>,
+}
+
+impl ShareDialog {
+    pub fn new(link: Option<String>, web_url: &str) -> Self {
+        Self {
+            link,
+            web_url: web_url.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    pub fn ui(&mut self, ui: &egui::Ui) -> ModalResponse<Option<ShareAction>> {
+        egui::Modal::new("share".into()).show(ui.ctx(), |ui| {
+            let mut action = None;
+            ui.heading("Share");
+            ui.separator();
+            if cfg!(not(target_arch = "wasm32")) {
+                ui.horizontal(|ui| {
+                    ui.label("Web app address:");
+                    if ui.text_edit_singleline(&mut self.web_url).lost_focus() {
+                        action = Some(ShareAction::SetWebUrl(self.web_url.trim().to_owned()));
+                    }
+                });
+            }
+            match &self.link {
+                Some(link) => {
+                    ui.label("Open this link on another device to see the game:");
+                    ui.add(
+                        egui::Label::new(egui::RichText::new(link).monospace().size(10.0))
+                            .truncate(),
+                    );
+                    ui.horizontal(|ui| {
+                        if ui.button("Copy link").clicked() {
+                            ui.ctx().copy_text(link.clone());
+                        }
+                        if ui.button("Show QR code").clicked() {
+                            action = Some(ShareAction::ShowQrCode);
+                        }
+                    });
+                }
+                None if cfg!(not(target_arch = "wasm32")) && self.web_url.trim().is_empty() => {
+                    ui.label("Set the web app address to get a link other devices can open.");
+                }
+                None => {
+                    ui.label("No game to share yet.");
+                }
+            }
+            ui.separator();
+            ui.label("Open a received link:");
+            ui.text_edit_singleline(&mut self.received);
+            if let Some(e) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, e);
+            }
+            egui::Sides::new().show(
+                ui,
+                |_| {},
+                |ui| {
+                    if ui.button("Close").clicked() {
+                        ui.close();
+                    }
+                    if ui
+                        .add_enabled(!self.received.trim().is_empty(), egui::Button::new("Open"))
+                        .clicked()
+                    {
+                        action = Some(ShareAction::Open(self.received.clone()));
+                    }
+                },
+            );
+            action
+        })
+    }
+}
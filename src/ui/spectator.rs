@@ -0,0 +1,40 @@
+use egui::RichText;
+
+use crate::scoreboard::{Movement, Scoreboard};
+
+/// Large-font scoreboard without any editing control.
+pub fn scoreboard_ui(ui: &mut egui::Ui, board: &Scoreboard) {
+    let size = (ui.available_height() / 10.0).clamp(24.0, 96.0);
+    ui.vertical_centered(|ui| {
+        ui.label(RichText::new(format!("Hand {}", board.hands + 1)).size(size * 0.6));
+        ui.label(RichText::new(format!("Dealer: {}", board.dealer)).size(size * 0.6));
+        if let Some(contract) = &board.last_contract {
+            ui.label(RichText::new(format!("Last hand: {contract}")).size(size * 0.5));
+        }
+    });
+    ui.separator();
+    egui::Grid::new("spectator_scoreboard")
+        .striped(true)
+        .spacing([size, size * 0.3])
+        .show(ui, |ui| {
+            for row in board.ranked() {
+                let colour = match row.movement {
+                    Movement::Up => egui::Color32::from_rgb(0x2e, 0xa0, 0x43),
+                    Movement::Down => ui.visuals().error_fg_color,
+                    Movement::Same => ui.visuals().weak_text_color(),
+                };
+                ui.label(RichText::new(format!("{}", row.rank)).size(size));
+                ui.label(RichText::new(row.movement.arrow()).size(size).color(colour));
+                ui.label(RichText::new(&row.name).size(size).strong());
+                ui.label(RichText::new(format!("{}", row.total)).size(size).strong());
+                ui.label(
+                    RichText::new(
+                        row.last_delta
+                            .map_or(String::new(), |delta| format!("{delta:+}")),
+                    )
+                    .size(size * 0.6),
+                );
+                ui.end_row();
+            }
+        });
+}
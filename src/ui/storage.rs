@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use egui::ModalResponse;
+
+use crate::storage::StorageChoice;
+
+/// Dialog choosing where games are saved.
+#[derive(Debug, Default)]
+pub struct StorageView {
+    folder: String,
+    #[cfg(feature = "sqlite")]
+    database: String,
+}
+
+impl StorageView {
+    pub fn new(current: &StorageChoice) -> Self {
+        let path = current
+            .path()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        match current {
+            StorageChoice::AppStorage => Self::default(),
+            StorageChoice::Folder(_) => Self {
+                folder: path,
+                ..Default::default()
+            },
+            #[cfg(feature = "sqlite")]
+            StorageChoice::Database(_) => Self {
+                database: path,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &egui::Ui,
+        current: &StorageChoice,
+    ) -> ModalResponse<Option<StorageChoice>> {
+        egui::Modal::new("storage".into()).show(ui.ctx(), |ui| {
+            let mut choice = None;
+            ui.heading("Storage");
+            ui.separator();
+            ui.label(match current {
+                StorageChoice::AppStorage => "Saving to the application storage".to_owned(),
+                StorageChoice::Folder(dir) => format!("Saving to the folder {}", dir.display()),
+                #[cfg(feature = "sqlite")]
+                StorageChoice::Database(file) => {
+                    format!("Saving to the database {}", file.display())
+                }
+            });
+            ui.label(
+                "If the chosen location already holds a save, it is opened. \
+                 Otherwise the current game is moved there.",
+            );
+            egui::Grid::new("storage_locations").show(ui, |ui| {
+                ui.label("Folder");
+                ui.text_edit_singleline(&mut self.folder);
+                let folder = self.folder.trim();
+                if ui
+                    .add_enabled(!folder.is_empty(), egui::Button::new("Use"))
+                    .clicked()
+                {
+                    choice = Some(StorageChoice::Folder(PathBuf::from(folder)));
+                }
+                ui.end_row();
+                #[cfg(feature = "sqlite")]
+                {
+                    ui.label("Database file");
+                    ui.text_edit_singleline(&mut self.database);
+                    let database = self.database.trim();
+                    if ui
+                        .add_enabled(!database.is_empty(), egui::Button::new("Use"))
+                        .clicked()
+                    {
+                        choice = Some(StorageChoice::Database(PathBuf::from(database)));
+                    }
+                    ui.end_row();
+                }
+            });
+            egui::Sides::new().show(
+                ui,
+                |ui| {
+                    if ui
+                        .add_enabled(
+                            *current != StorageChoice::AppStorage,
+                            egui::Button::new("Use app storage"),
+                        )
+                        .clicked()
+                    {
+                        choice = Some(StorageChoice::AppStorage);
+                    }
+                },
+                |ui| {
+                    if ui.button("Close").clicked() {
+                        ui.close();
+                    }
+                },
+            );
+            choice
+        })
+    }
+}
@@ -0,0 +1,131 @@
+use egui::ModalResponse;
+
+use crate::sync::{Status, SyncSession};
+
+pub enum SyncAction {
+    Connect {
+        url: String,
+        room: String,
+    },
+    Disconnect,
+    /// Adds a conflicting hand after the others.
+    KeepConflict(usize),
+    DropConflict(usize),
+}
+
+/// Dialog joining a room on a relay, and showing how the sync goes.
+#[derive(Debug)]
+pub struct SyncDialog {
+    url: String,
+    room: String,
+}
+
+impl Default for SyncDialog {
+    fn default() -> Self {
+        Self {
+            url: "ws://".to_owned(),
+            room: String::new(),
+        }
+    }
+}
+
+impl SyncDialog {
+    pub fn ui(
+        &mut self,
+        ui: &egui::Ui,
+        session: Option<&SyncSession>,
+    ) -> ModalResponse<Option<SyncAction>> {
+        egui::Modal::new("sync".into()).show(ui.ctx(), |ui| {
+            let mut action = None;
+            ui.heading("Sync devices");
+            ui.separator();
+            match session {
+                Some(session) => {
+                    ui.label(format!(
+                        "Room \"{}\" on {}",
+                        session.replica.room(),
+                        session.url()
+                    ));
+                    match session.status() {
+                        Status::Connecting => {
+                            ui.label("Connecting…");
+                        }
+                        Status::Online => {
+                            ui.label("Online");
+                        }
+                        Status::Offline(reason) => {
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                format!("Offline, retrying: {reason}"),
+                            );
+                        }
+                    }
+                    if session.replica.pending() > 0 {
+                        ui.label(format!(
+                            "{} changes waiting for the relay",
+                            session.replica.pending()
+                        ));
+                    }
+                    let conflicts = session.replica.conflicts();
+                    if !conflicts.is_empty() {
+                        ui.separator();
+                        ui.label("Hands entered here that another device entered first:");
+                        egui::Grid::new("sync_conflicts").show(ui, |ui| {
+                            for (idx, hand) in conflicts.iter().enumerate() {
+                                ui.label(&hand.gamemode_name);
+                                ui.label(format!("{:?}", hand.scores));
+                                if ui.button("Add anyway").clicked() {
+                                    action = Some(SyncAction::KeepConflict(idx));
+                                }
+                                if ui.button("Drop").clicked() {
+                                    action = Some(SyncAction::DropConflict(idx));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    }
+                }
+                None => {
+                    egui::Grid::new("sync_form").show(ui, |ui| {
+                        ui.label("Relay:");
+                        ui.text_edit_singleline(&mut self.url);
+                        ui.end_row();
+                        ui.label("Room:");
+                        ui.text_edit_singleline(&mut self.room);
+                        ui.end_row();
+                    });
+                    ui.label(
+                        "Joining a room with a game in progress replaces the hands of this \
+                         device, which are kept aside.",
+                    );
+                }
+            }
+            egui::Sides::new().show(
+                ui,
+                |_| {},
+                |ui| {
+                    if ui.button("Close").clicked() {
+                        ui.close();
+                    }
+                    if session.is_some() {
+                        if ui.button("Disconnect").clicked() {
+                            action = Some(SyncAction::Disconnect);
+                        }
+                    } else if ui
+                        .add_enabled(
+                            !self.room.trim().is_empty() && self.url.len() > "ws://".len(),
+                            egui::Button::new("Connect"),
+                        )
+                        .clicked()
+                    {
+                        action = Some(SyncAction::Connect {
+                            url: self.url.trim().to_owned(),
+                            room: self.room.trim().to_owned(),
+                        });
+                    }
+                },
+            );
+            action
+        })
+    }
+}
@@ -0,0 +1,218 @@
+use log::error;
+
+use crate::{
+    session::Action,
+    tournament::{TableGame, Tournament},
+    ui::HandBuilderGUI,
+};
+
+/// Local state of the tournament screen.
+#[derive(Debug, Default)]
+pub struct TournamentView {
+    player_field: String,
+    table: usize,
+    contract_idx: usize,
+    /// Modal of the hand being entered at the table shown, tables cannot be
+    /// switched meanwhile.
+    hand_ui: HandBuilderGUI,
+}
+
+impl TournamentView {
+    /// Returns `true` when the user asked to leave the tournament screen.
+    pub fn ui(&mut self, ui: &mut egui::Ui, tournament: &mut Tournament) -> bool {
+        let mut leave = false;
+        ui.horizontal(|ui| {
+            ui.heading("Tournament");
+            if ui.button("Back to single table").clicked() {
+                leave = true;
+            }
+        });
+        ui.separator();
+
+        if tournament.rounds.is_empty() {
+            self.registration_ui(ui, tournament);
+        }
+
+        let next_round = tournament.rounds.len() + 1;
+        if ui
+            .add_enabled(
+                tournament.can_start_round(),
+                egui::Button::new(format!("Start round {next_round}")),
+            )
+            .on_disabled_hover_text(
+                "Needs a multiple of 4 players and every table of the round finished",
+            )
+            .clicked()
+        {
+            match tournament.start_round() {
+                Ok(()) => self.table = 0,
+                Err(e) => error!("{e}"),
+            }
+        }
+
+        let players = tournament.players.clone();
+        if let Some(round) = tournament.rounds.last_mut() {
+            ui.label(format!("Round {}", next_round - 1));
+            // The hand being entered belongs to the table shown.
+            let pending = round
+                .tables
+                .get(self.table)
+                .is_some_and(|table| table.session.draft.is_some());
+            ui.horizontal_wrapped(|ui| {
+                for (idx, table) in round.tables.iter().enumerate() {
+                    let label = if table.finished {
+                        format!("Table {} ✔", idx + 1)
+                    } else {
+                        format!("Table {}", idx + 1)
+                    };
+                    if ui
+                        .add_enabled(!pending, egui::Button::selectable(self.table == idx, label))
+                        .clicked()
+                    {
+                        self.table = idx;
+                    }
+                }
+            });
+            if let Some(table) = round.tables.get_mut(self.table) {
+                ui.separator();
+                self.table_ui(ui, table, &players);
+            }
+        }
+
+        if !tournament.rounds.is_empty() {
+            ui.separator();
+            standings_grid(ui, tournament);
+        }
+        leave
+    }
+
+    fn registration_ui(&mut self, ui: &mut egui::Ui, tournament: &mut Tournament) {
+        ui.horizontal(|ui| {
+            ui.label("Register a player:");
+            let response = ui.text_edit_singleline(&mut self.player_field);
+            let enter_pressed =
+                response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if enter_pressed || ui.button("Add").clicked() {
+                let name = std::mem::take(&mut self.player_field);
+                if let Err(e) = tournament.register(&name) {
+                    error!("{e}");
+                }
+                response.request_focus();
+            }
+        });
+        let mut removed = None;
+        egui::Grid::new("tournament_players")
+            .striped(true)
+            .show(ui, |ui| {
+                for (idx, name) in tournament.players.iter().enumerate() {
+                    ui.label(name);
+                    if ui.small_button("Remove").clicked() {
+                        removed = Some(idx);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(idx) = removed {
+            tournament.unregister(idx);
+        }
+        ui.label(format!("{} players registered", tournament.players.len()));
+        ui.separator();
+    }
+
+    fn table_ui(&mut self, ui: &mut egui::Ui, table: &mut TableGame, players: &[String]) {
+        let names: Vec<String> = table
+            .seats
+            .iter()
+            .map(|&idx| players.get(idx).cloned().unwrap_or_default())
+            .collect();
+        egui::Grid::new(("tournament_table", self.table))
+            .striped(true)
+            .show(ui, |ui| {
+                for name in &names {
+                    ui.strong(name);
+                }
+                ui.end_row();
+                for (_, scores) in &table.session.historic {
+                    for score in scores {
+                        ui.label(format!("{score}"));
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if table.finished {
+            if ui.button("Reopen table").clicked() {
+                table.finished = false;
+            }
+            return;
+        }
+
+        let dealer = names
+            .get(table.session.historic.len() % 4)
+            .expect("Always 4 players");
+        ui.label(format!("Dealer: {dealer}"));
+        let contracts = &table.session.contracts;
+        egui::ComboBox::from_label("Select gamemode")
+            .selected_text(
+                contracts
+                    .get(self.contract_idx)
+                    .map(|contract| contract.name.clone())
+                    .unwrap_or_default(),
+            )
+            .show_ui(ui, |ui| {
+                for (idx, contract) in contracts.iter().enumerate() {
+                    ui.selectable_value(&mut self.contract_idx, idx, contract.name.clone());
+                }
+            });
+        let mut actions = vec![];
+        ui.horizontal(|ui| {
+            if ui.button("New hand").clicked() {
+                actions.push(Action::StartHand(self.contract_idx));
+            }
+            if ui.button("Remove last hand").clicked() {
+                actions.push(Action::RemoveLastHand);
+            }
+            if ui
+                .add_enabled(
+                    table.session.draft.is_none(),
+                    egui::Button::new("Table finished"),
+                )
+                .clicked()
+            {
+                table.finished = true;
+            }
+        });
+        for action in actions {
+            if let Err(e) = table.session.apply(action) {
+                error!("{e}");
+            }
+        }
+
+        self.hand_ui.record_ui(ui, &mut table.session);
+    }
+}
+
+fn standings_grid(ui: &mut egui::Ui, tournament: &Tournament) {
+    ui.label("Overall ranking");
+    egui::Grid::new("tournament_standings")
+        .striped(true)
+        .show(ui, |ui| {
+            for title in ["#", "Player", "Points", "Wins", "Positions", "Best"] {
+                ui.strong(title);
+            }
+            ui.end_row();
+            for (rank, standing) in tournament.standings().iter().enumerate() {
+                ui.label(format!("{}", rank + 1));
+                ui.label(&standing.name);
+                ui.label(format!("{}", standing.points));
+                ui.label(format!("{}", standing.table_wins));
+                ui.label(format!("{}", standing.positions_sum));
+                ui.label(
+                    standing
+                        .best_table
+                        .map_or("-".to_owned(), |best| best.to_string()),
+                );
+                ui.end_row();
+            }
+        });
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "session")]
+
+mod common;
+
+use common::{contracted, names, players};
+use whist_game::{HandRecap, Players, Tricks, contracts::Contract};
+use whist_points::{
+    bids::BidReport,
+    historic::HandsHistoric,
+    session::{self, HandDraft},
+};
+
+/// A contract with a bid, and its lowest and highest bids.
+fn bid_contract(contracts: &[Contract]) -> (&Contract, u8, u8) {
+    contracts
+        .iter()
+        .find_map(|contract| {
+            let range = HandDraft::new(contract.clone()).bid_range()?;
+            (range.end() - range.start() >= 3).then(|| (contract, *range.start(), *range.end()))
+        })
+        .expect("A contract has bids")
+}
+
+fn hand(
+    players: &Players,
+    contract: &Contract,
+    contractor: &str,
+    bid: u8,
+    tricks: u8,
+) -> HandRecap {
+    HandRecap {
+        bid: Some(Tricks::new(bid).expect("Within range")),
+        ..contracted(players, &contract.name, &[contractor], tricks, [0; 4])
+    }
+}
+
+#[test]
+fn bids_against_tricks() {
+    let contracts = session::contracts();
+    let (contract, min, max) = bid_contract(&contracts);
+    let players = players();
+    let mut historic = HandsHistoric::default();
+    for recap in [
+        hand(&players, contract, "Ann", max, max),
+        hand(&players, contract, "Ann", min, min + 2),
+        hand(&players, contract, "Ann", max, max - 3),
+        hand(&players, contract, "Bob", min, min),
+    ] {
+        historic.push(recap).expect("Totals within range");
+    }
+    let report = BidReport::from_historic(&historic, &names(), &contracts);
+
+    let ann = report.player("Ann").expect("Ann bid");
+    assert_eq!(ann.bids, 3, "Three bids");
+    assert_eq!(ann.made, 2, "Two made");
+    assert_eq!(
+        ann.mean_overtricks(),
+        Some(1.0),
+        "Two overtricks in two bids"
+    );
+    assert_eq!(ann.mean_undertricks(), Some(3.0), "Three tricks missing");
+    assert_eq!(ann.at_max, 2, "Twice at the maximum");
+    assert_eq!(ann.max_bid_rate(), Some(2.0 / 3.0), "Share at the maximum");
+    assert_eq!(
+        ann.distribution
+            .iter()
+            .map(|(d, n)| (*d, *n))
+            .collect::<Vec<_>>(),
+        [(-3, 1), (0, 1), (2, 1)],
+        "Results around the bid"
+    );
+    assert_eq!(ann.aggressiveness(), Some(2.0 / 3.0), "Mostly bold");
+
+    let bob = report.player("Bob").expect("Bob bid");
+    assert_eq!(bob.aggressiveness(), Some(0.0), "Always the minimum");
+    assert_eq!(bob.mean_undertricks(), None, "Never failed");
+    assert!(report.player("Cleo").is_none(), "Cleo never bid");
+}
+
+#[test]
+fn hands_without_bids_are_ignored() {
+    let contracts = session::contracts();
+    let players = players();
+    let id = players.get_id("Ann").expect("Seated player");
+    let mut historic = HandsHistoric::default();
+    historic
+        .push(HandRecap {
+            scores: [21, -7, -7, -7],
+            gamemode_name: "Queens".to_owned(),
+            contractors_tricks: vec![(id, Tricks::new(4).expect("Within range"))],
+            bid: None,
+        })
+        .expect("Totals within range");
+    let report = BidReport::from_historic(&historic, &names(), &contracts);
+    assert!(report.is_empty(), "No bid");
+}
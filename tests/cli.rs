@@ -0,0 +1,245 @@
+#![cfg(all(feature = "cli", not(target_arch = "wasm32")))]
+
+use std::path::{Path, PathBuf};
+
+use whist_points::{
+    PlayersState,
+    cli::{self, CliError},
+    state::AppState,
+    storage::{AppFileStorage, DirectoryStorage, StorageRead as _},
+};
+
+/// Fresh save folder for one test.
+fn save_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("whist-points-cli-{}-{test}", std::process::id()));
+    let _: std::io::Result<()> = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn run(dir: &Path, args: &[&str]) -> Result<String, CliError> {
+    let save = dir.display().to_string();
+    cli::run(
+        ["whist-points-cli", "--save", &save]
+            .into_iter()
+            .chain(args.iter().copied()),
+    )
+}
+
+/// The save, as the app opens it.
+fn load(dir: &Path) -> AppState {
+    AppState::load(&DirectoryStorage::new(dir).expect("The folder exists"))
+}
+
+fn start(dir: &Path) {
+    run(dir, &["new", "Ann", "Bob", "Cleo", "Dan"]).expect("Distinct names");
+}
+
+#[test]
+fn hands_are_saved_for_the_app() {
+    let dir = save_dir("saved");
+    start(&dir);
+    let table = run(
+        &dir,
+        &[
+            "add",
+            "--contract",
+            "queens",
+            "--contractors",
+            "ann",
+            "--tricks",
+            "4",
+        ],
+    )
+    .expect("Valid hand");
+    assert!(table.contains("Queens"), "The hand is shown: {table}");
+
+    let app = load(&dir);
+    let PlayersState::Playing(players) = &app.session.players_state else {
+        panic!("The players are saved");
+    };
+    assert_eq!(players.names(), ["Ann", "Bob", "Cleo", "Dan"]);
+    assert_eq!(app.session.historic.len(), 1, "The hand is saved");
+    assert_eq!(
+        app.session.historic.totals().iter().sum::<i16>(),
+        0,
+        "Scores always sum to zero"
+    );
+    assert_eq!(
+        run(&dir, &["show"]).expect("Readable save"),
+        table,
+        "The table is the same after reloading"
+    );
+}
+
+#[test]
+fn the_app_storage_file_is_shared() {
+    let dir = save_dir("app-file");
+    std::fs::create_dir_all(&dir).expect("Temporary folder");
+    let file = dir.join("app.ron");
+    std::fs::write(&file, r#"{"window": "(maximized: false)"}"#).expect("Temporary file");
+    start(&file);
+    run(
+        &file,
+        &[
+            "add",
+            "--contract",
+            "Queens",
+            "--contractors",
+            "Ann",
+            "--tricks",
+            "4",
+        ],
+    )
+    .expect("Valid hand");
+
+    let storage = AppFileStorage::open(&file).expect("eframe's storage");
+    let app = AppState::load(&storage);
+    assert_eq!(app.session.historic.len(), 1, "The hand is saved");
+    assert_eq!(
+        storage.get("window").as_deref(),
+        Some("(maximized: false)"),
+        "eframe's own entries are kept"
+    );
+}
+
+#[test]
+fn three_contractors_score_custom_points() {
+    let dir = save_dir("custom");
+    start(&dir);
+    run(
+        &dir,
+        &[
+            "add",
+            "--contract",
+            "Queens",
+            "--contractors",
+            "Dan",
+            "Ann",
+            "Cleo",
+            "--points",
+            "10,-4,6",
+        ],
+    )
+    .expect("Valid hand");
+    assert_eq!(
+        load(&dir).session.historic.totals(),
+        [10, -12, -4, 6],
+        "Points follow the seat order, the fourth player takes the rest"
+    );
+}
+
+#[test]
+fn undo_removes_the_last_hand() {
+    let dir = save_dir("undo");
+    start(&dir);
+    for tricks in ["4", "0"] {
+        run(
+            &dir,
+            &[
+                "add",
+                "--contract",
+                "Queens",
+                "--contractors",
+                "Bob",
+                "--tricks",
+                tricks,
+            ],
+        )
+        .expect("Valid hand");
+    }
+    run(&dir, &["undo"]).expect("Readable save");
+    let app = load(&dir);
+    assert_eq!(
+        app.session.historic.len(),
+        1,
+        "Only the last hand is removed"
+    );
+    let PlayersState::Playing(players) = &app.session.players_state else {
+        panic!("The players are saved");
+    };
+    let players = serde_json::to_value(players).expect("Players are saved");
+    let scores: Vec<i64> = players["players"]
+        .as_array()
+        .expect("Saved as a list of players")
+        .iter()
+        .map(|player| player["score"].as_i64().expect("Numeric score"))
+        .collect();
+    assert_eq!(
+        scores,
+        app.session.historic.totals().map(i64::from),
+        "Scores of the removed hand are undone"
+    );
+
+    let csv = run(&dir, &["export", "--format", "csv"]).expect("Readable save");
+    assert_eq!(csv.lines().count(), 2, "Header and one hand: {csv}");
+}
+
+#[test]
+fn invalid_hands_are_refused() {
+    let dir = save_dir("invalid");
+    assert!(
+        matches!(
+            run(
+                &dir,
+                &["add", "--contract", "Queens", "--contractors", "Ann"]
+            ),
+            Err(CliError::NoGame)
+        ),
+        "Players come first"
+    );
+    start(&dir);
+    assert!(
+        matches!(
+            run(
+                &dir,
+                &["add", "--contract", "Poker", "--contractors", "Ann"]
+            ),
+            Err(CliError::UnknownContract(_))
+        ),
+        "Only the app's contracts are known"
+    );
+    assert!(
+        matches!(
+            run(
+                &dir,
+                &["add", "--contract", "Queens", "--contractors", "Eve"]
+            ),
+            Err(CliError::Hand(_))
+        ),
+        "Contractors are players"
+    );
+    assert_eq!(load(&dir).session.historic.len(), 0, "Nothing is saved");
+}
+
+#[test]
+fn only_finish_archives_the_game() {
+    let dir = save_dir("finish");
+    let queens = [
+        "add",
+        "--contract",
+        "Queens",
+        "--contractors",
+        "Ann",
+        "--tricks",
+        "4",
+    ];
+    start(&dir);
+    run(&dir, &queens).expect("Valid hand");
+    start(&dir);
+    let app = load(&dir);
+    assert!(app.archive.is_empty(), "new replaces the game as is");
+    assert!(app.session.historic.is_empty(), "The new game has no hand");
+
+    run(&dir, &queens).expect("Valid hand");
+    run(&dir, &["finish"]).expect("A hand was played");
+    let app = load(&dir);
+    assert_eq!(app.archive.len(), 1, "The game is archived");
+    assert!(
+        matches!(app.session.players_state, PlayersState::Building(_)),
+        "The next game waits for its players"
+    );
+    assert!(
+        matches!(run(&dir, &["finish"]), Err(CliError::NothingToArchive)),
+        "An empty game is not archived"
+    );
+}
@@ -0,0 +1,102 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code, reason = "Each test crate uses a subset of the fixtures")]
+
+use whist_game::{HandRecap, Players, PlayersBuilder, Tricks};
+use whist_points::{
+    archive::ArchivedGame,
+    date::Date,
+    historic::HandsHistoric,
+    league::{Season, StandingsRule},
+    session::{Action, Session},
+    settlement::SettlementConfig,
+    state::AppState,
+};
+
+/// The players of most tests, in seat order.
+pub fn names() -> [String; 4] {
+    ["Ann", "Bob", "Cleo", "Dan"].map(str::to_owned)
+}
+
+/// Session with [`names`] seated in this order.
+pub fn started() -> Session {
+    let mut session = Session::default();
+    for name in names() {
+        session
+            .apply(Action::AddPlayer(name))
+            .expect("Distinct names");
+    }
+    session
+}
+
+/// Players seated in the given order.
+pub fn seat(names: [&str; 4]) -> Players {
+    let mut builder = PlayersBuilder::default();
+    for name in names {
+        builder.add_player(name).expect("Distinct names");
+    }
+    builder.build().expect("4 players")
+}
+
+/// [`names`] seated in order.
+pub fn players() -> Players {
+    seat(["Ann", "Bob", "Cleo", "Dan"])
+}
+
+/// A hand without contractors.
+pub fn hand(gamemode_name: &str, scores: [i16; 4]) -> HandRecap {
+    HandRecap {
+        scores,
+        gamemode_name: gamemode_name.to_owned(),
+        contractors_tricks: vec![],
+        bid: None,
+    }
+}
+
+/// A hand called by `contractors`, who made `tricks` each.
+pub fn contracted(
+    players: &Players,
+    gamemode_name: &str,
+    contractors: &[&str],
+    tricks: u8,
+    scores: [i16; 4],
+) -> HandRecap {
+    HandRecap {
+        contractors_tricks: contractors
+            .iter()
+            .map(|name| {
+                let id = players.get_id(name).expect("Seated player");
+                (id, Tricks::new(tricks).expect("Within range"))
+            })
+            .collect(),
+        ..hand(gamemode_name, scores)
+    }
+}
+
+/// State with a game won by Ann in the archive, a season and a payout cap,
+/// the current game not being started.
+pub fn archived() -> AppState {
+    let mut historic = HandsHistoric::default();
+    historic
+        .push(contracted(
+            &players(),
+            "Queens",
+            &["Ann"],
+            4,
+            [30, -10, -5, -15],
+        ))
+        .expect("Totals within range");
+    let day = |day| Date::from_ymd(2024, 10, day).expect("Valid date");
+    AppState {
+        archive: vec![ArchivedGame::new(&historic, &names(), day(4))],
+        seasons: vec![Season::new(
+            "Spring".to_owned(),
+            day(1),
+            StandingsRule::BestEvenings(3),
+        )],
+        settlement: SettlementConfig {
+            max_payout: Some(500),
+            ..SettlementConfig::default()
+        },
+        ..AppState::default()
+    }
+}
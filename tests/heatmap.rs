@@ -0,0 +1,53 @@
+#![cfg(feature = "session")]
+
+mod common;
+
+use common::{contracted, names, players};
+use whist_points::{heatmap::Heatmap, historic::HandsHistoric};
+
+fn heatmap() -> Heatmap {
+    let players = players();
+    let mut historic = HandsHistoric::default();
+    for recap in [
+        contracted(&players, "Solo", &["Ann"], 0, [30, -10, -10, -10]),
+        contracted(&players, "Solo", &["Ann"], 0, [-60, 20, 20, 20]),
+        contracted(&players, "Queens", &["Bob"], 0, [-7, 21, -7, -7]),
+        contracted(&players, "Queens", &["Ann"], 0, [-15, 5, 5, 5]),
+    ] {
+        historic.push(recap).expect("Totals within range");
+    }
+    Heatmap::from_historic(&historic, &names())
+}
+
+#[test]
+fn contracts_called_by_each_player() {
+    let heatmap = heatmap();
+    assert_eq!(
+        heatmap.players,
+        ["Ann", "Bob"],
+        "Only contractors are listed"
+    );
+    assert_eq!(
+        heatmap.contracts,
+        ["Solo", "Queens"],
+        "Contracts by first call"
+    );
+
+    let solo = heatmap.cell("Ann", "Solo").expect("Known cell");
+    assert_eq!(solo.calls, 2, "Ann called Solo twice");
+    assert_eq!(solo.won, 1, "And won once");
+    assert_eq!(solo.success_rate(), Some(0.5), "Half won");
+    assert_eq!(solo.average_delta(), Some(-15.0), "Points per call");
+
+    let never = heatmap.cell("Bob", "Solo").expect("Known cell");
+    assert_eq!(never.calls, 0, "Bob never called Solo");
+    assert_eq!(never.average_delta(), None, "Nothing to average");
+    assert_eq!(heatmap.max_calls(), 2, "Busiest cell");
+}
+
+#[test]
+fn empty_game() {
+    let heatmap = Heatmap::from_historic(&HandsHistoric::default(), &names());
+    assert!(heatmap.is_empty(), "No contract called");
+    assert_eq!(heatmap.cell("Ann", "Solo"), None, "Unknown player");
+}
@@ -0,0 +1,155 @@
+#![cfg(feature = "session")]
+
+mod common;
+
+use common::{contracted, players, seat};
+use whist_game::{PlayerId, Players};
+use whist_points::historic::{HandsHistoric, Substitution};
+
+fn ids(players: &Players, names: [&str; 4]) -> [PlayerId; 4] {
+    names.map(|name| players.get_id(name).expect("Seated player"))
+}
+
+fn substitution(seat: usize, from_hand: usize, previous: &str, name: &str) -> Substitution {
+    Substitution {
+        seat,
+        from_hand,
+        previous: previous.to_owned(),
+        name: name.to_owned(),
+    }
+}
+
+/// Two hands won by Ann then Dan.
+fn game() -> HandsHistoric {
+    let players = players();
+    let mut historic = HandsHistoric::default();
+    for (contractor, scores) in [("Ann", [21, -7, -7, -7]), ("Dan", [-7, -7, -7, 21])] {
+        historic
+            .push(contracted(&players, "Queens", &[contractor], 4, scores))
+            .expect("Totals within range");
+    }
+    historic
+}
+
+#[test]
+fn reseating_moves_scores_contractors_and_substitutions() {
+    let mut historic = game();
+    historic
+        .add_substitution(substitution(2, 1, "Cleo", "Eve"))
+        .expect("Within the game");
+    let seated = ["Bob", "Eve", "Ann", "Dan"];
+    let players = seat(seated);
+    historic.reseat(&[1, 2, 0, 3], &ids(&players, seated));
+
+    let first = historic.get(0).expect("Two hands");
+    assert_eq!(first.scores, [-7, -7, 21, -7], "Scores follow the seats");
+    let contractors: Vec<usize> = first
+        .contractors_tricks
+        .iter()
+        .map(|(id, _)| id.idx())
+        .collect();
+    assert_eq!(contractors, [2], "Ann's contract follows her to seat 3");
+    assert_eq!(historic.totals(), [-14, -14, 14, 14], "Totals follow");
+    let seats: Vec<usize> = historic.substitutions().iter().map(|s| s.seat).collect();
+    assert_eq!(seats, [1], "Eve's seat moved with her");
+}
+
+#[test]
+fn substitutions_tell_who_sat_where() {
+    let mut historic = game();
+    assert!(
+        historic
+            .add_substitution(substitution(4, 1, "Cleo", "Eve"))
+            .is_err(),
+        "Four seats"
+    );
+    assert!(
+        historic
+            .add_substitution(substitution(2, 3, "Cleo", "Eve"))
+            .is_err(),
+        "Not after the next hand"
+    );
+    historic
+        .add_substitution(substitution(2, 1, "Cleo", "Eve"))
+        .expect("Within the game");
+    assert!(
+        historic
+            .add_substitution(substitution(2, 1, "Eve", "Finn"))
+            .is_err(),
+        "After the previous substitution of the seat"
+    );
+    let current = ["Ann", "Bob", "Eve", "Dan"].map(str::to_owned);
+    assert_eq!(historic.occupant(2, 0, "Eve"), "Cleo", "Cleo played hand 1");
+    assert_eq!(historic.occupant(2, 1, "Eve"), "Eve", "Eve played hand 2");
+    assert_eq!(
+        historic.seat_names_at(0, &current),
+        ["Ann", "Bob", "Cleo", "Dan"],
+        "Seats of hand 1"
+    );
+    assert_eq!(historic.seat_header(2, "Eve"), "Cleo → Eve (#2)", "Header");
+    let stats = historic.person_stats(&current);
+    let hands: Vec<(&str, usize)> = stats.iter().map(|s| (s.name.as_str(), s.hands)).collect();
+    assert_eq!(
+        hands,
+        [("Ann", 2), ("Bob", 2), ("Cleo", 1), ("Dan", 2), ("Eve", 1)],
+        "Hands credited to whoever played them"
+    );
+}
+
+#[test]
+fn undoing_a_hand_keeps_the_substitute_seated() {
+    let mut historic = game();
+    historic
+        .add_substitution(substitution(2, 1, "Cleo", "Eve"))
+        .expect("Within the game");
+    historic
+        .add_substitution(substitution(3, 2, "Dan", "Finn"))
+        .expect("From the next hand");
+    let current = ["Ann", "Bob", "Eve", "Finn"].map(str::to_owned);
+
+    historic.remove_last();
+    assert_eq!(historic.totals(), [21, -7, -7, -7], "First hand left");
+    let starts: Vec<(&str, usize)> = historic
+        .substitutions()
+        .iter()
+        .map(|s| (s.name.as_str(), s.from_hand))
+        .collect();
+    assert_eq!(
+        starts,
+        [("Eve", 1), ("Finn", 1)],
+        "Finn now starts at the next hand"
+    );
+    assert_eq!(
+        historic.seat_names_at(0, &current),
+        ["Ann", "Bob", "Cleo", "Dan"],
+        "The first hand stays credited to the previous players"
+    );
+    let names: Vec<String> = historic
+        .person_stats(&current)
+        .into_iter()
+        .map(|stats| stats.name)
+        .collect();
+    assert_eq!(names, ["Ann", "Bob", "Cleo", "Dan"], "Only they played");
+
+    historic.remove_last();
+    assert!(historic.is_empty(), "Every hand removed");
+    assert!(
+        historic.substitutions().iter().all(|s| s.from_hand == 0),
+        "The substitutes are seated from the first hand"
+    );
+    assert_eq!(
+        historic.seat_names_at(0, &current),
+        current,
+        "The next hand is credited to the substitutes"
+    );
+}
+
+#[test]
+fn renames_reach_the_substitutions() {
+    let mut historic = game();
+    historic
+        .add_substitution(substitution(2, 1, "Cleo", "Eev"))
+        .expect("Within the game");
+    historic.rename(&[("Eev".to_owned(), "Eve".to_owned())]);
+    assert_eq!(historic.seat_header(2, "Eve"), "Cleo → Eve (#2)", "Renamed");
+}
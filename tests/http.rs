@@ -0,0 +1,101 @@
+#![cfg(all(feature = "http", not(target_arch = "wasm32")))]
+
+mod common;
+
+use std::{
+    io::{Read as _, Write as _},
+    net::TcpStream,
+};
+
+use common::hand;
+use whist_points::{http::ScoreServer, scoreboard::Scoreboard, state::AppState};
+
+/// Players, one of them with markup the pages must escape.
+fn names() -> Vec<String> {
+    ["Ann", "Bob", "Cleo", "<Dan>"].map(str::to_owned).to_vec()
+}
+
+/// Raw GET, returning the status line, headers and body as one string.
+fn get(server: &ScoreServer, path: &str) -> String {
+    let mut stream =
+        TcpStream::connect(("127.0.0.1", server.port())).expect("Server should accept");
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+    )
+    .expect("Request should be sent");
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .expect("Response should be read");
+    response
+}
+
+fn body(response: &str) -> &str {
+    response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body)
+        .expect("Response has a body")
+}
+
+#[test]
+fn serves_scores_as_json_and_html() {
+    let server = ScoreServer::start(("127.0.0.1", 0)).expect("Any free port");
+    let waiting = get(&server, "/scores.json");
+    assert_eq!(body(&waiting), "null", "Nothing before the players are set");
+
+    let mut app = AppState::default();
+    app.session
+        .historic
+        .push(hand("Queens", [21, -7, -7, -7]))
+        .expect("Totals within range");
+    app.session
+        .historic
+        .push(hand("Solo", [-30, 30, 0, 0]))
+        .expect("Totals within range");
+    let board = Scoreboard::new(&names(), &app.session.historic);
+    server.update(board.clone());
+
+    let json = get(&server, "/scores.json");
+    assert!(json.starts_with("HTTP/1.1 200"), "{json}");
+    assert!(json.contains("application/json"), "JSON content type");
+    let served: serde_json::Value = serde_json::from_str(body(&json)).expect("Valid JSON");
+    assert_eq!(
+        served,
+        serde_json::to_value(&board).expect("Scoreboard serializes"),
+        "The last scoreboard is served"
+    );
+    let contracts: Vec<&str> = served["history"]
+        .as_array()
+        .expect("Hands are listed")
+        .iter()
+        .filter_map(|hand| hand["contract"].as_str())
+        .collect();
+    assert_eq!(contracts, ["Queens", "Solo"], "Every hand is served");
+    assert_eq!(
+        served["history"][1]["scores"],
+        serde_json::json!([-30, 30, 0, 0]),
+        "Points of each seat"
+    );
+
+    let html = get(&server, "/");
+    assert!(html.contains("text/html"), "HTML content type");
+    assert!(
+        html.contains("http-equiv=\"refresh\""),
+        "The page reloads itself"
+    );
+    assert!(html.contains("Last hand: Solo"), "Last hand is shown");
+    assert!(
+        html.contains("<tr><td>1</td><td>Queens</td><td class=\"n\">+21</td>"),
+        "{html}"
+    );
+    assert!(
+        html.contains("<tr><td>2</td><td>Solo</td><td class=\"n\">-30</td>"),
+        "{html}"
+    );
+    assert!(html.contains("&lt;Dan&gt;"), "Names are escaped");
+    assert!(!html.contains("<Dan>"), "Names are escaped");
+
+    let missing = get(&server, "/admin");
+    assert!(missing.starts_with("HTTP/1.1 404"), "{missing}");
+}
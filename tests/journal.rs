@@ -0,0 +1,160 @@
+#![cfg(feature = "session")]
+
+mod common;
+
+use common::{hand, names};
+use whist_points::{
+    session::Action,
+    state::AppState,
+    storage::{MemoryStorage, StorageBackend, StorageError, StorageRead, keys},
+};
+
+const HANDS: [[i16; 4]; 3] = [[21, -7, -7, -7], [-7, 21, -7, -7], [-5, -5, 15, -5]];
+
+/// Seats the players, which writes a snapshot, then journals `hands`.
+fn play(storage: &mut dyn StorageBackend, hands: &[[i16; 4]]) -> AppState {
+    let mut app = AppState::default();
+    for name in names() {
+        app.apply(Action::AddPlayer(name)).expect("Distinct names");
+    }
+    app.persist(storage);
+    for scores in hands {
+        app.apply(Action::RecordHand(hand("Queens", *scores)))
+            .expect("Totals within range");
+        app.persist(storage);
+    }
+    app
+}
+
+/// Memory storage counting how often it is flushed.
+#[derive(Default)]
+struct Flushes {
+    storage: MemoryStorage,
+    count: usize,
+}
+
+impl StorageRead for Flushes {
+    fn get(&self, key: &str) -> Option<String> {
+        self.storage.get(key)
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.storage.keys()
+    }
+}
+
+impl StorageBackend for Flushes {
+    fn set(&mut self, key: &str, value: String) -> Result<(), StorageError> {
+        self.storage.set(key, value)
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+        self.storage.remove(key)
+    }
+
+    fn flush(&mut self) -> Result<(), StorageError> {
+        self.count += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn every_hand_is_flushed() {
+    let mut storage = Flushes::default();
+    let mut app = play(&mut storage, &[]);
+    for scores in HANDS {
+        let before = storage.count;
+        app.apply(Action::RecordHand(hand("Queens", scores)))
+            .expect("Totals within range");
+        app.persist(&mut storage);
+        assert_eq!(storage.count - before, 1, "One flush per hand");
+    }
+    assert_eq!(
+        storage
+            .get(keys::JOURNAL)
+            .map(|journal| journal.lines().count()),
+        Some(HANDS.len()),
+        "Hands are journaled, not snapshotted"
+    );
+}
+
+#[test]
+fn hands_are_replayed_after_a_crash() {
+    let mut storage = MemoryStorage::default();
+    let app = play(&mut storage, &HANDS);
+    let journal = storage.get(keys::JOURNAL).expect("Hands are journaled");
+    assert_eq!(journal.lines().count(), HANDS.len(), "One line per hand");
+
+    let loaded = AppState::load(&storage);
+    assert_eq!(
+        loaded.session.historic.len(),
+        HANDS.len(),
+        "Every hand is back"
+    );
+    assert_eq!(
+        loaded.session.historic.totals(),
+        app.session.historic.totals(),
+        "Totals are back"
+    );
+    assert_eq!(loaded.journal_seq, app.journal_seq, "Sequence is restored");
+    assert!(!loaded.snapshot_requested, "The journal is sound");
+}
+
+#[test]
+fn a_truncated_entry_is_dropped() {
+    let mut storage = MemoryStorage::default();
+    play(&mut storage, &HANDS);
+    let journal = storage.get(keys::JOURNAL).expect("Hands are journaled");
+    let last = journal.trim_end().rfind('\n').expect("Several lines") + 1;
+    let cut = last + (journal.len() - last) / 2;
+    storage
+        .set(
+            keys::JOURNAL,
+            journal.get(..cut).expect("Within the journal").to_owned(),
+        )
+        .expect("Memory storage never fails");
+
+    let mut loaded = AppState::load(&storage);
+    assert_eq!(
+        loaded.session.historic.len(),
+        HANDS.len() - 1,
+        "Hands before the truncated one are back"
+    );
+    assert!(loaded.snapshot_requested, "The broken journal is replaced");
+
+    loaded.persist(&mut storage);
+    assert!(
+        storage.get(keys::JOURNAL).is_none(),
+        "The snapshot includes the journal"
+    );
+    loaded
+        .apply(Action::RecordHand(hand("Queens", [21, -7, -7, -7])))
+        .expect("Totals within range");
+    loaded.persist(&mut storage);
+    assert_eq!(
+        AppState::load(&storage).session.historic.len(),
+        HANDS.len(),
+        "Hands journaled after the crash are replayed"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn folders_append_to_the_journal() {
+    use whist_points::storage::DirectoryStorage;
+
+    let dir = std::env::temp_dir().join(format!("whist-points-journal-{}", std::process::id()));
+    let _: std::io::Result<()> = std::fs::remove_dir_all(&dir);
+    let mut storage = DirectoryStorage::new(&dir).expect("Temporary folder");
+    let app = play(&mut storage, &HANDS);
+
+    let journal = storage.get(keys::JOURNAL).expect("Hands are journaled");
+    assert_eq!(journal.lines().count(), HANDS.len(), "One line per hand");
+    let loaded = AppState::load(&storage);
+    assert_eq!(
+        loaded.session.historic.totals(),
+        app.session.historic.totals(),
+        "Totals are back"
+    );
+    let _: std::io::Result<()> = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "session")]
+
+use whist_points::{
+    archive::ArchivedGame,
+    date::Date,
+    league::{INITIAL_RATING, Leaderboard, Season, StandingsRule},
+};
+
+fn seated(finished_on: Date, players: [&str; 4], totals: [i16; 4]) -> ArchivedGame {
+    ArchivedGame {
+        finished_on,
+        players: players.map(str::to_owned),
+        totals,
+        hands: vec![],
+    }
+}
+
+fn game(finished_on: Date, totals: [i16; 4]) -> ArchivedGame {
+    seated(finished_on, ["Ann", "Bob", "Cleo", "Dan"], totals)
+}
+
+fn day(day: u32) -> Date {
+    Date::from_ymd(2026, 3, day).expect("Valid date")
+}
+
+fn rating(board: &Leaderboard, name: &str) -> f64 {
+    board
+        .entries
+        .iter()
+        .find(|entry| entry.name == name)
+        .map(|entry| entry.rating)
+        .expect("Ranked player")
+}
+
+#[test]
+fn ratings_are_exchanged_not_created() {
+    let games = [
+        game(day(1), [30, -10, -5, -15]),
+        seated(day(2), ["Eve", "Ann", "Bob", "Cleo"], [-20, 40, 0, -20]),
+        seated(day(3), ["Dan", "Eve", "Cleo", "Ann"], [5, 5, -5, -5]),
+    ];
+    let board = Leaderboard::new(&games);
+    let sum: f64 = board.entries.iter().map(|entry| entry.rating).sum();
+    let expected = INITIAL_RATING * 5.0;
+    assert!((sum - expected).abs() < 1e-9, "{sum} points for 5 players");
+    let ratings: Vec<f64> = board.entries.iter().map(|entry| entry.rating).collect();
+    assert!(
+        ratings.windows(2).all(|pair| pair.first() >= pair.get(1)),
+        "Best rating first: {ratings:?}"
+    );
+}
+
+#[test]
+fn ratings_do_not_depend_on_the_seats() {
+    let board = Leaderboard::new(&[game(day(1), [30, -10, -10, -10])]);
+    let swapped = Leaderboard::new(&[seated(
+        day(1),
+        ["Dan", "Cleo", "Bob", "Ann"],
+        [-10, -10, -10, 30],
+    )]);
+    for name in ["Ann", "Bob", "Cleo", "Dan"] {
+        assert!(
+            (rating(&board, name) - rating(&swapped, name)).abs() < 1e-9,
+            "Same rating for {name} whatever the seat"
+        );
+    }
+    assert!(
+        (rating(&board, "Bob") - rating(&board, "Dan")).abs() < 1e-9,
+        "Tied players get the same rating"
+    );
+    let gain = rating(&board, "Ann") - INITIAL_RATING;
+    let loss = INITIAL_RATING - rating(&board, "Bob");
+    assert!(gain > 0.0, "The winner gains");
+    assert!(
+        (gain - 3.0 * loss).abs() < 1e-9,
+        "Losers pay for the winner"
+    );
+}
+
+#[test]
+fn standings_rank_by_the_season_rule() {
+    let archive = [
+        game(day(1), [30, -10, -5, -15]),
+        game(day(2), [-20, 40, 0, -20]),
+        game(day(2), [-20, 10, 0, 10]),
+    ];
+    let ranking = |rule| -> Vec<(String, i32)> {
+        Season::new("Spring".to_owned(), day(1), rule)
+            .standings(&archive)
+            .into_iter()
+            .map(|standing| (standing.name, standing.score))
+            .collect()
+    };
+    let expected =
+        |ranking: [(&str, i32); 4]| ranking.map(|(name, score)| (name.to_owned(), score));
+    assert_eq!(
+        ranking(StandingsRule::default()),
+        expected([("Bob", 9), ("Ann", 5), ("Cleo", 5), ("Dan", 5)]),
+        "Points for each position, ties sharing the best one, then by name"
+    );
+    assert_eq!(
+        ranking(StandingsRule::RawTotals),
+        expected([("Bob", 40), ("Cleo", -5), ("Ann", -10), ("Dan", -25)]),
+        "Sum of the totals"
+    );
+    assert_eq!(
+        ranking(StandingsRule::BestEvenings(1)),
+        expected([("Bob", 50), ("Ann", 30), ("Cleo", 0), ("Dan", -10)]),
+        "Best evening of each player"
+    );
+}
+
+#[test]
+fn a_closed_season_is_frozen() {
+    let day = Date::from_ymd(2026, 3, 1).expect("Valid date");
+    let mut season = Season::new("Spring".to_owned(), day, StandingsRule::RawTotals);
+    let mut archive = vec![game(day, [30, -10, -5, -15])];
+    season.close(day, archive.len());
+    archive.push(game(day, [-40, 40, 0, 0]));
+
+    let standings = season.standings(&archive);
+    let ann = standings.first().expect("Ann played");
+    assert_eq!(
+        (ann.name.as_str(), ann.score, ann.games),
+        ("Ann", 30, 1),
+        "A game finished after the closing, on the last day, is left out"
+    );
+}
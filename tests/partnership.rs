@@ -0,0 +1,64 @@
+#![cfg(feature = "session")]
+
+mod common;
+
+use common::{contracted, names, players};
+use whist_points::{historic::HandsHistoric, partnership::Partnerships};
+
+fn game() -> Partnerships {
+    let players = players();
+    let mut historic = HandsHistoric::default();
+    for recap in [
+        contracted(&players, "Queens", &["Ann", "Bob"], 0, [10, 10, -10, -10]),
+        contracted(&players, "Queens", &["Ann", "Bob"], 0, [-15, -15, 15, 15]),
+        contracted(&players, "Queens", &["Cleo"], 0, [-5, -5, 15, -5]),
+    ] {
+        historic.push(recap).expect("Totals within range");
+    }
+    Partnerships::new(&historic, &names())
+}
+
+#[test]
+fn pairs_of_contractors() {
+    let partnerships = game();
+    let pair = partnerships.pair("Ann", "Bob").expect("Known people");
+    assert_eq!(pair.hands, 2, "Two hands together");
+    assert_eq!(pair.won, 1, "One of them won");
+    assert_eq!(pair.points, -10, "Combined points");
+    assert_eq!(pair.success_rate(), Some(0.5), "Half won");
+    assert_eq!(pair.average_points(), Some(-5.0), "Points per hand");
+    assert_eq!(
+        partnerships.pair("Bob", "Ann"),
+        Some(pair),
+        "Pairs are symmetric"
+    );
+
+    let never = partnerships.pair("Cleo", "Dan").expect("Known people");
+    assert_eq!(never.hands, 0, "Never contracted together");
+    assert_eq!(never.success_rate(), None, "No rate without hands");
+    assert_eq!(partnerships.pair("Ann", "Eve"), None, "Unknown person");
+}
+
+#[test]
+fn defenders_against_each_contractor() {
+    let partnerships = game();
+    let cleo = partnerships
+        .head_to_head("Cleo", "Ann")
+        .expect("Known people");
+    assert_eq!(cleo.hands, 2, "Cleo defended twice against Ann");
+    assert_eq!(cleo.defended, 1, "Ann lost once");
+    assert_eq!(cleo.points, 5, "Points of Cleo");
+    assert_eq!(cleo.defence_rate(), Some(0.5), "Half defeated");
+
+    let ann = partnerships
+        .head_to_head("Ann", "Cleo")
+        .expect("Known people");
+    assert_eq!(ann.hands, 1, "Ann defended once against Cleo");
+    assert_eq!(ann.defended, 0, "Cleo won");
+    assert_eq!(ann.average_points(), Some(-5.0), "Points of Ann");
+
+    let partner = partnerships
+        .head_to_head("Bob", "Ann")
+        .expect("Known people");
+    assert_eq!(partner.hands, 0, "Partners do not defend");
+}
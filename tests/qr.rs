@@ -0,0 +1,43 @@
+#![cfg(feature = "gui")]
+
+use whist_points::qr::{QrError, QrImage};
+
+#[test]
+fn code_has_quiet_zone_and_finder_patterns() {
+    let code = QrImage::new("https://example.org/#whist=1.abc").expect("Short link fits");
+    let size = code.size();
+    assert!(size >= 21 + 8, "Smallest code plus quiet zone, got {size}");
+    assert!(!code.is_dark(0, 0), "Quiet zone is light");
+    assert!(
+        code.is_dark(4, 4),
+        "Top left finder pattern starts after the quiet zone"
+    );
+    assert!(
+        code.is_dark(size - 5, 4),
+        "Top right finder pattern ends before the quiet zone"
+    );
+    assert!(!code.is_dark(size, size), "Out of range modules are light");
+}
+
+#[test]
+fn exports_png() {
+    let code = QrImage::new("whist").expect("Short text fits");
+    let png = code.to_png(3).expect("Image should encode");
+    assert_eq!(
+        png.get(..8),
+        Some(&b"\x89PNG\r\n\x1a\n"[..]),
+        "PNG signature"
+    );
+    let width = png.get(16..20).expect("IHDR holds the width");
+    let side = u32::try_from(code.size() * 3).expect("Small image");
+    assert_eq!(width, side.to_be_bytes(), "Three pixels per module");
+}
+
+#[test]
+fn rejects_too_long_games() {
+    let text = "x".repeat(8000);
+    assert!(
+        matches!(QrImage::new(&text), Err(QrError::TooLong(_))),
+        "Nothing fits 8000 bytes"
+    );
+}
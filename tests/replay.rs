@@ -0,0 +1,90 @@
+#![cfg(feature = "session")]
+
+mod common;
+
+use common::{hand, names};
+use whist_points::{historic::HandsHistoric, replay::Replay, scoreboard::Movement};
+
+fn game() -> Replay {
+    let mut historic = HandsHistoric::default();
+    for (contract, scores) in [
+        ("Queens", [21, -7, -7, -7]),
+        ("Solo", [-30, 30, 0, 0]),
+        ("Queens", [-5, -5, 15, -5]),
+    ] {
+        historic
+            .push(hand(contract, scores))
+            .expect("Totals within range");
+    }
+    Replay::new(&names(), &historic)
+}
+
+#[test]
+fn steps_through_the_hands() {
+    let mut replay = game();
+    assert_eq!(replay.len(), 3, "Every hand is replayed");
+    assert!(replay.is_at_start(), "Replays start before the first hand");
+    assert!(!replay.back(), "Nothing before the start");
+
+    assert!(replay.forward(), "First hand");
+    assert!(replay.forward(), "Second hand");
+    assert_eq!(replay.position(), 2, "Two hands played");
+    assert!(replay.back(), "Back to the first hand");
+    assert_eq!(replay.position(), 1, "One hand played");
+
+    replay.seek(10);
+    assert!(replay.is_at_end(), "Positions stop at the last hand");
+    assert!(!replay.forward(), "Nothing after the end");
+}
+
+#[test]
+fn shows_the_standings_at_each_hand() {
+    let mut replay = game();
+    let board = replay.board();
+    assert!(board.last_contract.is_none(), "No hand played yet");
+    assert!(
+        board.rows.iter().all(|row| row.total == 0 && row.rank == 1),
+        "Everyone starts tied"
+    );
+
+    replay.seek(2);
+    let board = replay.board();
+    assert_eq!(board.hands, 2, "Two hands played");
+    assert_eq!(
+        board.last_contract.as_deref(),
+        Some("Solo"),
+        "Contract of the last hand"
+    );
+    let totals: Vec<i16> = board.rows.iter().map(|row| row.total).collect();
+    assert_eq!(totals, [-9, 23, -7, -7], "Totals after two hands");
+    let deltas: Vec<Option<i16>> = board.rows.iter().map(|row| row.last_delta).collect();
+    assert_eq!(
+        deltas,
+        [Some(-30), Some(30), Some(0), Some(0)],
+        "Who gained or lost in the last hand"
+    );
+    let bob = board.rows.get(1).expect("4 seats");
+    assert_eq!(bob.rank, 1, "Bob leads");
+    assert_eq!(bob.movement, Movement::Up, "Bob climbed");
+    let ann = board.rows.first().expect("4 seats");
+    assert_eq!(ann.movement, Movement::Down, "Ann fell");
+}
+
+#[test]
+fn snapshots_describe_the_position() {
+    let mut replay = game();
+    replay.seek(2);
+    let snapshot = replay.snapshot();
+    assert!(
+        snapshot.starts_with("After hand 2 of 3: Solo"),
+        "Position and contract: {snapshot}"
+    );
+    let lines: Vec<&str> = snapshot.lines().skip(1).collect();
+    assert_eq!(lines.len(), 4, "One line per player");
+    assert!(
+        lines.first().is_some_and(|line| line.starts_with("1. Bob")
+            && line.contains("23")
+            && line.contains("(+30)")),
+        "Leader first: {snapshot}"
+    );
+}
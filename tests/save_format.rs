@@ -0,0 +1,171 @@
+#![cfg(feature = "session")]
+
+mod common;
+
+use common::archived;
+use whist_points::{
+    save::{self, SAVE_VERSION, SaveError},
+    state::AppState,
+    storage::{Format, MemoryStorage, StorageBackend, StorageError, StorageRead, keys},
+};
+
+/// The eframe storage file of 0.2.4, holding the bare app state without
+/// envelope. Its contracts are left out and replaced by the defaults.
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn loads_unversioned_save() {
+    use whist_points::{PlayersState, session::Session, storage::AppFileStorage};
+
+    let path = format!(
+        "{}/tests/fixtures/app_0.2.4.ron",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let storage = AppFileStorage::open(path).expect("eframe storage file");
+    let raw = storage.get(keys::APP).expect("The app state is stored");
+    assert_eq!(
+        save::version(&raw, Format::Ron),
+        0,
+        "Saves before 0.3 carry no version"
+    );
+
+    let app = AppState::load(&storage);
+    assert!(app.quarantine.is_none(), "Version 0 save should migrate");
+    let PlayersState::Playing(players) = &app.session.players_state else {
+        panic!("The players were seated");
+    };
+    assert_eq!(
+        players.names(),
+        ["Ann", "Bob", "Cleo", "Dan"],
+        "Players are kept"
+    );
+    assert_eq!(app.player_field, "Eve", "Pending input is kept");
+    assert_eq!(
+        app.session.contracts.len(),
+        Session::default().contracts.len(),
+        "Missing contracts are the default ones"
+    );
+    assert_eq!(app.session.historic.len(), 2, "Hands are kept");
+    assert_eq!(
+        app.session.historic.totals(),
+        [11, 3, -17, 3],
+        "Cumulative scores are rebuilt from the hands"
+    );
+    assert!(app.archive.is_empty(), "Version 0 had no archive");
+}
+
+#[test]
+fn current_save_roundtrips() {
+    let raw =
+        save::to_string(&AppState::default(), Format::Ron).expect("Default app should encode");
+    assert_eq!(
+        save::version(&raw, Format::Ron),
+        SAVE_VERSION,
+        "Saves use the current version"
+    );
+    let app = save::load(&raw, Format::Ron).expect("Fresh save should load");
+    assert_eq!(
+        app.session.contracts.len(),
+        AppState::default().session.contracts.len(),
+        "Contracts survive a roundtrip"
+    );
+}
+
+#[test]
+fn rejects_newer_version() {
+    let raw = format!("(version:{},app:())", SAVE_VERSION + 1);
+    assert!(
+        matches!(
+            save::load(&raw, Format::Ron),
+            Err(SaveError::UnsupportedVersion(_))
+        ),
+        "A newer save must not be replaced by an empty game"
+    );
+}
+
+#[test]
+fn snapshot_splits_documents() {
+    let mut app = archived();
+    app.roster = vec!["Ann".to_owned(), "Bob".to_owned()];
+    let mut storage = MemoryStorage::default();
+    storage
+        .set(keys::JOURNAL, "stale".to_owned())
+        .expect("Memory storage never fails");
+    app.save_snapshot(&mut storage);
+
+    assert!(
+        storage.get(keys::JOURNAL).is_none(),
+        "The snapshot includes the journal"
+    );
+    for key in [keys::APP, keys::ARCHIVE, keys::SETTINGS, keys::ROSTER] {
+        assert!(storage.get(key).is_some(), "{key} is written");
+    }
+    let raw = storage.get(keys::APP).expect("Just written");
+    let inline = save::load(&raw, Format::Ron).expect("Snapshot should load");
+    assert!(inline.archive.is_empty(), "The archive is no longer inline");
+
+    let loaded = AppState::load(&storage);
+    assert!(loaded.quarantine.is_none(), "Every document is readable");
+    assert_eq!(loaded.archive.len(), 1, "Archive is read from its document");
+    assert_eq!(loaded.seasons.len(), 1, "Seasons are read from the archive");
+    assert_eq!(loaded.settlement.max_payout, Some(500), "Settings are kept");
+    assert_eq!(loaded.roster, ["Ann", "Bob"], "Roster is kept");
+}
+
+#[test]
+fn json_backend_roundtrips() {
+    struct Json(MemoryStorage);
+    impl StorageRead for Json {
+        fn get(&self, key: &str) -> Option<String> {
+            self.0.get(key)
+        }
+        fn keys(&self) -> Vec<String> {
+            self.0.keys()
+        }
+        fn format(&self) -> Format {
+            Format::Json
+        }
+    }
+    impl StorageBackend for Json {
+        fn set(&mut self, key: &str, value: String) -> Result<(), StorageError> {
+            self.0.set(key, value)
+        }
+        fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+            self.0.remove(key)
+        }
+        fn flush(&mut self) -> Result<(), StorageError> {
+            Ok(())
+        }
+    }
+
+    let mut app = archived();
+    let mut storage = Json(MemoryStorage::default());
+    app.save_snapshot(&mut storage);
+    let raw = storage.get(keys::APP).expect("Just written");
+    assert_eq!(
+        save::version(&raw, Format::Json),
+        SAVE_VERSION,
+        "JSON envelope carries the version"
+    );
+
+    let loaded = AppState::load(&storage);
+    assert!(loaded.quarantine.is_none(), "JSON documents are readable");
+    assert_eq!(loaded.archive.len(), 1, "Archive survives JSON");
+}
+
+#[test]
+fn unreadable_document_is_quarantined() {
+    let mut storage = MemoryStorage::default();
+    AppState::default().save_snapshot(&mut storage);
+    storage
+        .set(keys::ARCHIVE, "(games:[(".to_owned())
+        .expect("Memory storage never fails");
+
+    let loaded = AppState::load(&storage);
+    let quarantine = loaded.quarantine.expect("Archive cannot be read");
+    assert_eq!(
+        quarantine.key,
+        keys::ARCHIVE,
+        "The unreadable document is named"
+    );
+    assert!(!quarantine.can_salvage(), "Only games can be salvaged");
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "session")]
+
+mod common;
+
+use common::{hand, names};
+use whist_points::{
+    scoreboard::{Movement, Scoreboard},
+    state::AppState,
+};
+
+#[test]
+fn empty_game() {
+    let app = AppState::default();
+    let board = Scoreboard::new(&names(), &app.session.historic);
+    assert_eq!(board.hands, 0, "No hand played");
+    assert_eq!(board.dealer, "Ann", "First seat deals first");
+    assert!(board.last_contract.is_none(), "No last hand");
+    assert!(
+        board
+            .rows
+            .iter()
+            .all(|row| row.rank == 1 && row.movement == Movement::Same && row.last_delta.is_none()),
+        "Everyone is tied"
+    );
+}
+
+#[test]
+fn ranks_follow_the_last_hand() {
+    let mut app = AppState::default();
+    app.session
+        .historic
+        .push(hand("Queens", [21, -7, -7, -7]))
+        .expect("Totals within range");
+    app.session
+        .historic
+        .push(hand("Solo", [-30, 30, 0, 0]))
+        .expect("Totals within range");
+    let board = Scoreboard::new(&names(), &app.session.historic);
+
+    assert_eq!(board.hands, 2, "Hands are counted");
+    assert_eq!(board.dealer, "Cleo", "The deal moves clockwise");
+    assert_eq!(board.last_contract.as_deref(), Some("Solo"));
+
+    let bob = board.rows.get(1).expect("4 seats");
+    assert_eq!((bob.total, bob.rank), (23, 1), "Bob leads");
+    assert_eq!(bob.last_delta, Some(30), "Last hand points are shown");
+    assert_eq!(bob.movement, Movement::Up, "Bob was tied second");
+
+    let ann = board.rows.first().expect("4 seats");
+    assert_eq!((ann.total, ann.rank), (-9, 4), "Ann is last");
+    assert_eq!(ann.movement, Movement::Down, "Ann led before");
+
+    let cleo = board.rows.get(2).expect("4 seats");
+    assert_eq!(cleo.rank, 2, "Cleo and Dan share second place");
+    assert_eq!(cleo.movement, Movement::Same, "Cleo was already second");
+
+    let ranked: Vec<&str> = board.ranked().iter().map(|row| row.name.as_str()).collect();
+    assert_eq!(ranked, ["Bob", "Cleo", "Dan", "Ann"], "Leader first");
+}
@@ -0,0 +1,184 @@
+#![cfg(feature = "session")]
+//! Score invariants over random games. A failing case is shrunk and printed,
+//! and its seed saved under `proptest-regressions/` to be replayed first on
+//! the next runs.
+
+mod common;
+
+use common::started;
+use proptest::{prelude::*, sample::subsequence};
+use whist_game::Players;
+use whist_points::{
+    PlayersState,
+    session::{self, Action, POINTS_RANGE, Session},
+};
+
+#[derive(Debug, Clone)]
+enum Step {
+    /// A hand scored by its contract.
+    Classical {
+        contract: usize,
+        contractors: Vec<usize>,
+        bid: u8,
+        tricks: u8,
+    },
+    /// Three contractors scoring custom points.
+    Custom {
+        contract: usize,
+        contractors: Vec<usize>,
+        points: [i16; 3],
+    },
+    RemoveLast,
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    let contracts = session::contracts().len();
+    prop_oneof![
+        4 => (0..contracts, subsequence(vec![0, 1, 2, 3], 1..=3), 0..=13_u8, 0..=13_u8)
+            .prop_map(|(contract, contractors, bid, tricks)| Step::Classical {
+                contract,
+                contractors,
+                bid,
+                tricks,
+            }),
+        3 => (
+            0..contracts,
+            subsequence(vec![0, 1, 2, 3], 3),
+            prop::array::uniform3(POINTS_RANGE),
+        )
+            .prop_map(|(contract, contractors, points)| Step::Custom {
+                contract,
+                contractors,
+                points,
+            }),
+        1 => Just(Step::RemoveLast),
+    ]
+}
+
+/// Plays `step`, dropping the hand when the session refuses it.
+fn play(session: &mut Session, step: &Step) {
+    let (contract, contractors) = match step {
+        Step::Classical {
+            contract,
+            contractors,
+            ..
+        }
+        | Step::Custom {
+            contract,
+            contractors,
+            ..
+        } => (*contract, contractors),
+        Step::RemoveLast => {
+            let _: Result<_, _> = session.apply(Action::RemoveLastHand);
+            return;
+        }
+    };
+    session
+        .apply(Action::StartHand(contract))
+        .expect("Listed contract");
+    let mut actions: Vec<Action> = contractors
+        .iter()
+        .map(|&seat| Action::ToggleContractor(seat))
+        .collect();
+    match step {
+        Step::Classical { bid, tricks, .. } => {
+            let bids = session.draft.as_ref().and_then(|draft| draft.bid_range());
+            if let Some(bids) = bids {
+                actions.push(Action::SetBid((*bid).clamp(*bids.start(), *bids.end())));
+            }
+            actions.push(Action::SetTricks(*tricks));
+        }
+        Step::Custom { points, .. } => actions.push(Action::SetPoints(*points)),
+        Step::RemoveLast => {}
+    }
+    actions.push(Action::SubmitHand);
+    for action in actions {
+        if session.apply(action).is_err() {
+            session.apply(Action::CancelHand).expect("Hand started");
+            return;
+        }
+    }
+}
+
+/// Scores kept by `players`, read from their saved form.
+fn player_scores(players: &Players) -> Vec<i64> {
+    let value = serde_json::to_value(players).expect("Players are saved");
+    value["players"]
+        .as_array()
+        .expect("Saved as a list of players")
+        .iter()
+        .map(|player| player["score"].as_i64().expect("Numeric score"))
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn hands_sum_to_zero(steps in prop::collection::vec(step(), 0..40)) {
+        let mut session = started();
+        for step in &steps {
+            play(&mut session, step);
+        }
+        for (idx, (hand, _)) in (&session.historic).into_iter().enumerate() {
+            let sum: i32 = hand.scores.iter().copied().map(i32::from).sum();
+            prop_assert_eq!(sum, 0, "Hand {} scores {:?}", idx, hand.scores);
+        }
+    }
+
+    #[test]
+    fn totals_are_the_sum_of_the_hands(steps in prop::collection::vec(step(), 0..40)) {
+        let mut session = started();
+        for step in &steps {
+            play(&mut session, step);
+            let mut expected = [0_i32; 4];
+            for (hand, totals) in &session.historic {
+                for (total, score) in expected.iter_mut().zip(hand.scores) {
+                    *total += i32::from(score);
+                }
+                prop_assert_eq!(totals.map(i32::from), expected, "Running totals");
+            }
+            prop_assert_eq!(session.historic.totals().map(i32::from), expected, "Totals");
+        }
+    }
+
+    #[test]
+    fn players_follow_the_hands(steps in prop::collection::vec(step(), 0..40)) {
+        let mut session = started();
+        for step in &steps {
+            play(&mut session, step);
+            let PlayersState::Playing(players) = &session.players_state else {
+                panic!("Players are seated");
+            };
+            let totals: Vec<i64> = session.historic.totals().into_iter().map(i64::from).collect();
+            prop_assert_eq!(player_scores(players), totals, "After {:?}", step);
+        }
+    }
+
+    /// Hands of extreme custom points, until the totals would overflow.
+    #[test]
+    fn totals_never_overflow(
+        points in prop::array::uniform3(prop_oneof![
+            Just(*POINTS_RANGE.start()),
+            Just(*POINTS_RANGE.end()),
+        ]),
+        hands in 40..200_usize,
+    ) {
+        let mut session = started();
+        let contract = session
+            .contracts
+            .iter()
+            .position(|contract| contract.name == "Queens")
+            .expect("Queens is a contract");
+        let step = Step::Custom { contract, contractors: vec![0, 1, 2], points };
+        let mut expected = [0_i32; 4];
+        for _ in 0..hands {
+            let recorded = session.historic.len();
+            play(&mut session, &step);
+            if let Some(hand) = session.historic.get(recorded) {
+                for (total, score) in expected.iter_mut().zip(hand.scores) {
+                    *total += i32::from(score);
+                }
+            }
+            prop_assert_eq!(session.historic.totals().map(i32::from), expected, "Totals");
+        }
+    }
+}
@@ -0,0 +1,378 @@
+#![cfg(feature = "session")]
+
+mod common;
+
+use common::started;
+use whist_points::{
+    PlayersState,
+    session::{Action, Outcome, Session, SessionError},
+};
+
+fn contract_idx(session: &Session, name: &str) -> usize {
+    session
+        .contracts
+        .iter()
+        .position(|contract| contract.name == name)
+        .expect("Known contract")
+}
+
+fn bid_contract_idx(session: &Session) -> usize {
+    session
+        .contracts
+        .iter()
+        .position(|contract| contract.max_bid.is_some())
+        .expect("A contract has bids")
+}
+
+#[test]
+fn the_game_starts_with_the_fourth_player() {
+    let mut session = Session::default();
+    for (seated, name) in ["Ann", "Bob", "Cleo"].into_iter().enumerate() {
+        assert_eq!(
+            session.apply(Action::AddPlayer(name.to_owned())).ok(),
+            Some(Outcome::PlayerAdded(seated + 1)),
+            "Players are counted"
+        );
+    }
+    assert!(
+        matches!(
+            session.apply(Action::AddPlayer("Ann".to_owned())),
+            Err(SessionError::InvalidPlayer(_))
+        ),
+        "Names are unique"
+    );
+    assert_eq!(
+        session.apply(Action::AddPlayer("Dan".to_owned())).ok(),
+        Some(Outcome::GameStarted),
+        "Four players make a game"
+    );
+    assert!(
+        matches!(session.players_state, PlayersState::Playing(_)),
+        "Players are set"
+    );
+    assert!(
+        matches!(
+            session.apply(Action::AddPlayer("Eve".to_owned())),
+            Err(SessionError::PlayersSet)
+        ),
+        "No fifth player"
+    );
+}
+
+#[test]
+fn hands_wait_for_the_players() {
+    let mut session = Session::default();
+    let queens = contract_idx(&session, "Queens");
+    assert!(
+        matches!(
+            session.apply(Action::StartHand(queens)),
+            Err(SessionError::NoGame)
+        ),
+        "Players come first"
+    );
+    assert!(
+        matches!(
+            session.apply(Action::ToggleContractor(0)),
+            Err(SessionError::NoDraft)
+        ),
+        "No hand started"
+    );
+}
+
+#[test]
+fn contractors_follow_the_contract() {
+    let mut session = started();
+    let queens = contract_idx(&session, "Queens");
+    assert!(
+        matches!(
+            session.apply(Action::StartHand(session.contracts.len())),
+            Err(SessionError::NoContract(_))
+        ),
+        "Only listed contracts"
+    );
+    session
+        .apply(Action::StartHand(queens))
+        .expect("Game started");
+    assert!(
+        matches!(
+            session.apply(Action::SubmitHand),
+            Err(SessionError::ContractorsNumber { min: 1, max: 3 })
+        ),
+        "A contractor is needed"
+    );
+    for seat in [3, 0, 2] {
+        session
+            .apply(Action::ToggleContractor(seat))
+            .expect("Up to three contractors");
+    }
+    assert!(
+        matches!(
+            session.apply(Action::ToggleContractor(1)),
+            Err(SessionError::ContractorsNumber { .. })
+        ),
+        "No fourth contractor"
+    );
+    assert!(
+        matches!(
+            session.apply(Action::ToggleContractor(4)),
+            Err(SessionError::NoSeat(4))
+        ),
+        "Four seats"
+    );
+    let draft = session.draft.as_ref().expect("Hand started");
+    assert_eq!(draft.contractors(), [0, 2, 3], "Seat order");
+    assert_eq!(
+        draft.points(),
+        Some([0; 3]),
+        "Three contractors score by hand"
+    );
+
+    session
+        .apply(Action::ToggleContractor(2))
+        .expect("Contractors can be unselected");
+    let draft = session.draft.as_ref().expect("Hand started");
+    assert_eq!(draft.points(), None, "Two contractors score by the rules");
+    assert!(
+        matches!(
+            session.apply(Action::SetPoints([1, 2, 3])),
+            Err(SessionError::Points)
+        ),
+        "Points need three contractors"
+    );
+}
+
+#[test]
+fn three_contractors_score_custom_points() {
+    let mut session = started();
+    let queens = contract_idx(&session, "Queens");
+    session
+        .apply(Action::StartHand(queens))
+        .expect("Game started");
+    for seat in [3, 0, 2] {
+        session
+            .apply(Action::ToggleContractor(seat))
+            .expect("Up to three contractors");
+    }
+    session
+        .apply(Action::SetPoints([10, -4, 6]))
+        .expect("Three contractors");
+    assert_eq!(
+        session.apply(Action::SubmitHand).ok(),
+        Some(Outcome::HandRecorded(0)),
+        "First hand"
+    );
+    assert_eq!(
+        session.historic.totals(),
+        [10, -12, -4, 6],
+        "Points follow the seat order, the fourth player takes the rest"
+    );
+    assert!(session.draft.is_none(), "The hand is done");
+}
+
+#[test]
+fn bids_and_tricks_are_checked() {
+    let mut session = started();
+    let idx = bid_contract_idx(&session);
+    session.apply(Action::StartHand(idx)).expect("Game started");
+    let bids = session
+        .draft
+        .as_ref()
+        .and_then(|draft| draft.bid_range())
+        .expect("Contract with bids");
+    assert!(
+        matches!(
+            session.apply(Action::SetBid(bids.end() + 1)),
+            Err(SessionError::Bid(_))
+        ),
+        "Bids stop at the contract's maximum"
+    );
+    assert!(
+        matches!(
+            session.apply(Action::SetTricks(14)),
+            Err(SessionError::Tricks(14))
+        ),
+        "13 tricks in a hand"
+    );
+    let draft = session.draft.as_ref().expect("Hand started");
+    assert_eq!(draft.tricks(), 0, "Refused values are not kept");
+}
+
+#[test]
+fn submitted_hands_are_scored() {
+    let mut session = started();
+    let queens = contract_idx(&session, "Queens");
+    session
+        .apply(Action::StartHand(queens))
+        .expect("Game started");
+    session
+        .apply(Action::ToggleContractor(1))
+        .expect("One contractor");
+    session.apply(Action::SetTricks(4)).expect("Within a hand");
+    session.apply(Action::SubmitHand).expect("Valid hand");
+
+    let hand = session.historic.get(0).expect("Recorded");
+    assert_eq!(hand.gamemode_name, "Queens", "Contract of the hand");
+    assert_eq!(
+        session.historic.totals().iter().sum::<i16>(),
+        0,
+        "Scores add up to zero"
+    );
+}
+
+#[test]
+fn hands_can_be_cancelled_and_removed() {
+    let mut session = started();
+    let queens = contract_idx(&session, "Queens");
+    session
+        .apply(Action::StartHand(queens))
+        .expect("Game started");
+    assert_eq!(
+        session.apply(Action::CancelHand).ok(),
+        Some(Outcome::HandCancelled),
+        "Hand dropped"
+    );
+    assert!(
+        matches!(
+            session.apply(Action::SubmitHand),
+            Err(SessionError::NoDraft)
+        ),
+        "Nothing to submit"
+    );
+    assert!(
+        matches!(
+            session.apply(Action::RemoveLastHand),
+            Err(SessionError::NoHand)
+        ),
+        "Nothing to remove"
+    );
+
+    session
+        .apply(Action::StartHand(queens))
+        .expect("Game started");
+    session
+        .apply(Action::ToggleContractor(0))
+        .expect("One contractor");
+    session.apply(Action::SetTricks(4)).expect("Within a hand");
+    session.apply(Action::SubmitHand).expect("Valid hand");
+    assert_eq!(
+        session.apply(Action::RemoveLastHand).ok(),
+        Some(Outcome::HandRemoved),
+        "Hand removed"
+    );
+    assert!(session.historic.is_empty(), "No hand left");
+    assert_eq!(session.historic.totals(), [0; 4], "Scores are back to zero");
+}
+
+/// `started` after one Queens hand won by Bob.
+fn one_hand() -> Session {
+    let mut session = started();
+    let queens = contract_idx(&session, "Queens");
+    session
+        .apply(Action::StartHand(queens))
+        .expect("Game started");
+    session
+        .apply(Action::ToggleContractor(1))
+        .expect("One contractor");
+    session.apply(Action::SetTricks(4)).expect("Within a hand");
+    session.apply(Action::SubmitHand).expect("Valid hand");
+    session
+}
+
+fn names(session: &Session) -> Vec<String> {
+    let PlayersState::Playing(players) = &session.players_state else {
+        panic!("Game started");
+    };
+    players.names()
+}
+
+#[test]
+fn reseated_players_keep_their_scores() {
+    let mut session = one_hand();
+    let [ann, bob, cleo, dan] = session.historic.totals();
+    assert!(
+        matches!(
+            session.apply(Action::SetPlayers {
+                names: ["Bob", "Ann", "Cleo", "Dan"].map(str::to_owned),
+                origin: [1, 1, 2, 3],
+            }),
+            Err(SessionError::Seating(_))
+        ),
+        "Every seat is taken once"
+    );
+    assert_eq!(
+        session
+            .apply(Action::SetPlayers {
+                names: ["Bob", "Ann", "Cleo", "Dana"].map(str::to_owned),
+                origin: [1, 0, 2, 3],
+            })
+            .ok(),
+        Some(Outcome::PlayersChanged),
+        "Players reseated"
+    );
+    assert_eq!(names(&session), ["Bob", "Ann", "Cleo", "Dana"], "New seats");
+    assert_eq!(
+        session.historic.totals(),
+        [bob, ann, cleo, dan],
+        "Scores follow the players"
+    );
+}
+
+#[test]
+fn substitutions_start_within_the_game() {
+    let mut session = one_hand();
+    let totals = session.historic.totals();
+    assert!(
+        matches!(
+            session.apply(Action::Substitute {
+                seat: 2,
+                name: "Eve".to_owned(),
+                from_hand: 2,
+            }),
+            Err(SessionError::Substitution(_))
+        ),
+        "Not after the next hand"
+    );
+    assert_eq!(names(&session), ["Ann", "Bob", "Cleo", "Dan"], "Untouched");
+    assert_eq!(
+        session
+            .apply(Action::Substitute {
+                seat: 2,
+                name: "Eve".to_owned(),
+                from_hand: 1,
+            })
+            .ok(),
+        Some(Outcome::PlayersChanged),
+        "Substituted from the next hand"
+    );
+    assert_eq!(names(&session), ["Ann", "Bob", "Eve", "Dan"], "Eve seated");
+    assert_eq!(
+        session.historic.totals(),
+        totals,
+        "The seat keeps its score"
+    );
+    assert_eq!(session.historic.substitutions().len(), 1, "Recorded");
+}
+
+#[test]
+fn renamed_substitutes_keep_their_history() {
+    let mut session = one_hand();
+    session
+        .apply(Action::Substitute {
+            seat: 2,
+            name: "Eev".to_owned(),
+            from_hand: 1,
+        })
+        .expect("From the next hand");
+    session
+        .apply(Action::SetPlayers {
+            names: ["Ann", "Bob", "Eve", "Dan"].map(str::to_owned),
+            origin: [0, 1, 2, 3],
+        })
+        .expect("Typo fixed");
+    let substitution = session.historic.substitutions().first().expect("Kept");
+    assert_eq!(
+        (substitution.previous.as_str(), substitution.name.as_str()),
+        ("Cleo", "Eve"),
+        "The substitute is renamed"
+    );
+}
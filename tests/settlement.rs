@@ -0,0 +1,114 @@
+#![cfg(feature = "session")]
+
+use whist_points::settlement::{Settlement, SettlementConfig, Transfer};
+
+fn config(coin: i64) -> SettlementConfig {
+    SettlementConfig {
+        coin,
+        ..SettlementConfig::default()
+    }
+}
+
+#[test]
+fn amounts_are_rounded_to_the_coin() {
+    let settlement = Settlement::new(&[-13, -7, 4, 16], &config(50));
+    assert_eq!(
+        settlement.balances,
+        [-150, -50, 0, 200],
+        "Losses rounded to the nearest coin, the best winner takes the rest"
+    );
+    assert!(
+        settlement.balances.iter().all(|cents| cents % 50 == 0),
+        "Only whole coins change hands"
+    );
+}
+
+#[test]
+fn the_cap_holds_after_rounding() {
+    let config = SettlementConfig {
+        max_payout: Some(495),
+        ..config(10)
+    };
+    let settlement = Settlement::new(&[-50, -49, 40, 59], &config);
+    assert_eq!(
+        settlement.balances,
+        [-490, -490, 390, 590],
+        "A capped loss is rounded down"
+    );
+    assert_eq!(
+        settlement.balances.iter().sum::<i64>(),
+        0,
+        "Nothing is lost"
+    );
+}
+
+#[test]
+fn small_losses_are_not_paid() {
+    let config = SettlementConfig {
+        min_payout: 100,
+        ..config(10)
+    };
+    let settlement = Settlement::new(&[-5, -35, 20, 20], &config);
+    assert_eq!(
+        settlement.balances,
+        [0, -350, 170, 180],
+        "Only the loss above the minimum is shared"
+    );
+}
+
+#[test]
+fn matching_debts_settle_each_other() {
+    let settlement = Settlement::new(&[-10, -20, 10, 20], &config(10));
+    assert_eq!(
+        settlement.transfers,
+        [
+            Transfer {
+                from: 0,
+                to: 2,
+                cents: 100
+            },
+            Transfer {
+                from: 1,
+                to: 3,
+                cents: 200
+            },
+        ],
+        "One transfer per matching pair"
+    );
+}
+
+#[test]
+fn three_transfers_settle_any_game() {
+    let settlement = Settlement::new(&[-13, -7, 4, 16], &config(50));
+    assert_eq!(
+        settlement.transfers,
+        [
+            Transfer {
+                from: 0,
+                to: 3,
+                cents: 150
+            },
+            Transfer {
+                from: 1,
+                to: 3,
+                cents: 50
+            },
+        ],
+        "The biggest debtor pays the biggest creditor first"
+    );
+    let settlement = Settlement::new(&[-30, -10, 15, 25], &config(10));
+    assert!(settlement.transfers.len() <= 3, "At most three transfers");
+    let mut balances = [0; 4];
+    for transfer in &settlement.transfers {
+        if let Some(from) = balances.get_mut(transfer.from) {
+            *from -= transfer.cents;
+        }
+        if let Some(to) = balances.get_mut(transfer.to) {
+            *to += transfer.cents;
+        }
+    }
+    assert_eq!(
+        balances, settlement.balances,
+        "Transfers match the balances"
+    );
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "session")]
+
+mod common;
+
+use common::{hand, names};
+use whist_points::{
+    share::{self, SHARE_VERSION, ShareError},
+    state::AppState,
+};
+
+#[test]
+fn link_roundtrips() {
+    let mut app = AppState::default();
+    app.session
+        .historic
+        .push(hand("Queens", [21, -7, -7, -7]))
+        .expect("Totals within range");
+    app.session
+        .historic
+        .push(hand("Grand Slam Blitz", [-12, 4, 4, 4]))
+        .expect("Totals within range");
+    app.session
+        .historic
+        .push(hand("Queens", [-5, 5, 0, 0]))
+        .expect("Totals within range");
+
+    let link = share::link(
+        "https://example.org/whist/#old",
+        &names(),
+        &app.session.historic,
+    )
+    .expect("Hands should encode");
+    assert!(
+        link.starts_with(&format!(
+            "https://example.org/whist/#whist={SHARE_VERSION}."
+        )),
+        "The fragment replaces any previous one: {link}"
+    );
+
+    let shared = share::decode(&link).expect("Link should decode");
+    assert_eq!(shared.names, names(), "Players are kept");
+    assert_eq!(
+        shared.contracts,
+        ["Queens", "Grand Slam Blitz"],
+        "Contracts are listed once"
+    );
+    assert_eq!(shared.historic.len(), 3, "Hands are kept");
+    assert_eq!(
+        shared.historic.totals(),
+        [4, 2, -3, -3],
+        "Totals are rebuilt"
+    );
+
+    let missing = shared.missing_contracts(&app.session.contracts);
+    assert_eq!(
+        missing,
+        ["Grand Slam Blitz"],
+        "Only Queens is a known contract here"
+    );
+}
+
+#[test]
+fn fragment_alone_decodes() {
+    let app = AppState::default();
+    let fragment =
+        share::encode(&names(), &app.session.historic).expect("Empty game should encode");
+    assert!(
+        share::decode(&format!("#{fragment}")).is_ok(),
+        "A pasted fragment is enough"
+    );
+}
+
+#[test]
+fn rejects_foreign_and_newer_links() {
+    assert!(
+        matches!(
+            share::decode("https://example.org/#top"),
+            Err(ShareError::NotShared)
+        ),
+        "Links without a game are not mistaken for one"
+    );
+    assert!(
+        matches!(
+            share::decode(&format!("#whist={}.AAAA", SHARE_VERSION + 1)),
+            Err(ShareError::UnsupportedVersion(_))
+        ),
+        "Newer encodings are reported"
+    );
+    assert!(
+        share::decode(&format!("#whist={SHARE_VERSION}.not-a-game")).is_err(),
+        "Corrupted links are rejected"
+    );
+}
@@ -0,0 +1,141 @@
+#![cfg(all(feature = "sqlite", not(target_arch = "wasm32")))]
+
+mod common;
+
+use whist_points::{
+    league::Leaderboard,
+    save::{self, SaveError},
+    sqlite::SqliteStorage,
+    state::AppState,
+    storage::{Format, MemoryStorage, StorageBackend as _, StorageRead as _, keys},
+};
+
+/// eframe storage holding [`common::archived`].
+fn eframe_state() -> MemoryStorage {
+    let mut eframe = MemoryStorage::default();
+    common::archived().save_snapshot(&mut eframe);
+    eframe
+}
+
+#[test]
+fn imports_eframe_state() {
+    let mut db = SqliteStorage::in_memory().expect("SQLite is available");
+    let imported = db.import(&eframe_state()).expect("Current save imports");
+    assert_eq!(imported, 1, "The archived game is imported");
+
+    let raw = db.get(keys::APP).expect("The game is stored");
+    assert_eq!(
+        save::version(&raw, Format::Json),
+        save::SAVE_VERSION,
+        "Documents are stored in the current format"
+    );
+    let app = AppState::load(&db);
+    assert!(app.quarantine.is_none(), "Imported state is readable");
+    assert_eq!(app.archive.len(), 1, "Archive is kept");
+    assert_eq!(app.settlement.max_payout, Some(500), "Settings are kept");
+}
+
+#[test]
+fn statistics_run_in_sql() {
+    let mut db = SqliteStorage::in_memory().expect("SQLite is available");
+    db.import(&eframe_state()).expect("Current save imports");
+
+    let players = db.player_summaries().expect("Query is valid");
+    let names: Vec<&str> = players.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["Ann", "Cleo", "Bob", "Dan"], "Best totals first");
+    let ann = players.first().expect("Ann played");
+    assert_eq!((ann.games, ann.wins, ann.points), (1, 1, 30), "Ann won");
+
+    let contracts = db.contract_summaries().expect("Query is valid");
+    assert_eq!(contracts.len(), 1, "Only Queens was played");
+    let queens = contracts.first().expect("Queens was played");
+    assert_eq!((queens.hands, queens.won), (1, 1), "Ann made her Queens");
+}
+
+#[test]
+fn archive_is_synced_incrementally() {
+    let mut db = SqliteStorage::in_memory().expect("SQLite is available");
+    db.import(&eframe_state()).expect("Current save imports");
+    let mut app = AppState::load(&db);
+    app.archive.extend(app.archive.clone());
+    app.save_snapshot(&mut db);
+    app.save_snapshot(&mut db);
+
+    let ann = db.player_summaries().expect("Query is valid");
+    assert_eq!(
+        ann.first().map(|p| p.games),
+        Some(2),
+        "Games already stored are not inserted twice"
+    );
+}
+
+#[test]
+fn unreadable_state_is_not_imported() {
+    let mut eframe = MemoryStorage::default();
+    eframe
+        .set(keys::APP, "(version:1,app:(".to_owned())
+        .expect("Memory storage never fails");
+    let mut db = SqliteStorage::in_memory().expect("SQLite is available");
+    assert!(
+        matches!(db.import(&eframe), Err(SaveError::Unreadable(..))),
+        "A broken save is reported instead of importing an empty game"
+    );
+    assert!(db.keys().is_empty(), "Nothing was written");
+}
+
+#[test]
+fn replaced_games_are_rebuilt() {
+    let mut db = SqliteStorage::in_memory().expect("SQLite is available");
+    db.import(&eframe_state()).expect("Current save imports");
+    let mut app = AppState::load(&db);
+    let game = app.archive.first_mut().expect("Imported");
+    game.players = ["Eve", "Finn", "Gus", "Hal"].map(str::to_owned);
+    app.save_snapshot(&mut db);
+
+    let summary = db.archive_summary().expect("Computed by the database");
+    let mut names: Vec<&str> = summary.players.iter().map(|p| p.name.as_str()).collect();
+    names.sort_unstable();
+    assert_eq!(
+        names,
+        ["Eve", "Finn", "Gus", "Hal"],
+        "The rows of the changed game are replaced"
+    );
+    assert_eq!(summary.contracts.len(), 1, "Hands are not duplicated");
+    assert_eq!(
+        summary.contracts.first().map(|c| c.hands),
+        Some(1),
+        "Hands are not duplicated"
+    );
+}
+
+#[test]
+fn ratings_run_in_sql() {
+    let mut app = common::archived();
+    let mut rematch = app.archive.first().expect("One game").clone();
+    rematch.players.rotate_left(1);
+    app.archive.push(rematch);
+    let mut db = SqliteStorage::in_memory().expect("SQLite is available");
+    app.save_snapshot(&mut db);
+
+    let board = db.leaderboard().expect("Query is valid");
+    assert_eq!(
+        board.entries,
+        Leaderboard::new(&app.archive).entries,
+        "Same ratings as from the archive"
+    );
+}
+
+#[test]
+fn unreadable_archive_is_refused() {
+    let mut db = SqliteStorage::in_memory().expect("SQLite is available");
+    db.import(&eframe_state()).expect("Current save imports");
+    assert!(
+        db.set(keys::ARCHIVE, "{".to_owned()).is_err(),
+        "The tables cannot follow a broken archive"
+    );
+    assert_eq!(
+        AppState::load(&db).archive.len(),
+        1,
+        "The archive document is left as it was"
+    );
+}
@@ -0,0 +1,374 @@
+#![cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+
+mod common;
+
+use std::{
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use common::{hand, names};
+use whist_points::{
+    PlayersState,
+    relay::Relay,
+    session::Action,
+    state::AppState,
+    sync::{ClientMessage, Op, Origin, RelayMessage, Replica, SavedSync, Status, SyncSession},
+};
+
+fn relay() -> SocketAddr {
+    let relay = Relay::bind(("127.0.0.1", 0)).expect("Any free port");
+    let addr = relay.local_addr().expect("Bound");
+    relay.spawn();
+    addr
+}
+
+fn player_names(app: &AppState) -> Option<Vec<String>> {
+    match &app.session.players_state {
+        PlayersState::Playing(players) => Some(players.names()),
+        PlayersState::Building(_) => None,
+    }
+}
+
+fn contracts(app: &AppState) -> Vec<String> {
+    let historic = &app.session.historic;
+    historic
+        .into_iter()
+        .map(|(hand, _)| hand.gamemode_name.clone())
+        .collect()
+}
+
+type Devices<'a, const N: usize> = [(&'a mut SyncSession, &'a mut AppState); N];
+
+/// Steps every device until `done` holds. Reconnections wait up to a few
+/// seconds.
+fn pump<const N: usize>(devices: &mut Devices<'_, N>, done: impl Fn(&Devices<'_, N>) -> bool) {
+    let start = Instant::now();
+    loop {
+        for (session, app) in devices.iter_mut() {
+            session.step(app);
+        }
+        if done(devices) {
+            return;
+        }
+        assert!(
+            start.elapsed() < Duration::from_secs(20),
+            "Devices did not converge"
+        );
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn settled<const N: usize>(devices: &Devices<'_, N>) -> bool {
+    devices
+        .iter()
+        .all(|(session, _)| *session.status() == Status::Online && session.replica.pending() == 0)
+}
+
+/// Forwards connections to the relay, and can cut them to take a device
+/// offline.
+struct Proxy {
+    addr: SocketAddr,
+    down: Arc<AtomicBool>,
+    streams: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl Proxy {
+    fn new(upstream: SocketAddr) -> Self {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).expect("Any free port");
+        let addr = listener.local_addr().expect("Bound");
+        let down = Arc::new(AtomicBool::new(false));
+        let streams = Arc::new(Mutex::new(vec![]));
+        std::thread::spawn({
+            let down = Arc::clone(&down);
+            let streams = Arc::clone(&streams);
+            move || {
+                for client in listener.incoming().flatten() {
+                    if down.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    let Ok(server) = TcpStream::connect(upstream) else {
+                        continue;
+                    };
+                    for (mut from, mut to) in [
+                        (client.try_clone(), server.try_clone()),
+                        (server.try_clone(), client.try_clone()),
+                    ]
+                    .into_iter()
+                    .filter_map(|(from, to)| from.ok().zip(to.ok()))
+                    {
+                        std::thread::spawn(move || std::io::copy(&mut from, &mut to));
+                    }
+                    let mut streams = streams.lock().expect("Not poisoned");
+                    streams.extend([client, server]);
+                }
+            }
+        });
+        Self {
+            addr,
+            down,
+            streams,
+        }
+    }
+
+    fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    fn cut(&self) {
+        self.down.store(true, Ordering::Relaxed);
+        for stream in self.streams.lock().expect("Not poisoned").drain(..) {
+            let _: std::io::Result<()> = stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    fn restore(&self) {
+        self.down.store(false, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn hands_reach_every_device() {
+    let relay = relay();
+    let url = format!("ws://{relay}");
+    let mut ann = AppState::default();
+    ann.apply(Action::SetPlayers {
+        names: names(),
+        origin: [0, 1, 2, 3],
+    })
+    .expect("Distinct names");
+    ann.apply(Action::RecordHand(hand("Queens", [21, -7, -7, -7])))
+        .expect("Totals within range");
+    let mut ann_sync = SyncSession::connect(&url, "table", &ann);
+    pump(&mut [(&mut ann_sync, &mut ann)], settled);
+
+    let mut bob = AppState::default();
+    let mut bob_sync = SyncSession::connect(&url, "table", &bob);
+    let mut devices = [(&mut ann_sync, &mut ann), (&mut bob_sync, &mut bob)];
+    pump(&mut devices, |devices| {
+        settled(devices)
+            && devices
+                .iter()
+                .all(|(_, app)| app.session.historic.len() == 1)
+    });
+    let [(_, ann), (_, bob)] = &mut devices;
+    assert_eq!(
+        player_names(bob),
+        Some(names().to_vec()),
+        "Players of the room are taken"
+    );
+    assert_eq!(contracts(bob), ["Queens"], "Hands of the room are taken");
+
+    bob.apply(Action::RecordHand(hand("Solo", [-30, 10, 10, 10])))
+        .expect("Totals within range");
+    ann.apply(Action::RecordHand(hand("Misery", [12, -4, -4, -4])))
+        .expect("Totals within range");
+    pump(&mut devices, settled);
+    let [(ann_sync, ann), (bob_sync, bob)] = &mut devices;
+    assert_eq!(contracts(ann), contracts(bob), "Both devices agree");
+    assert_eq!(
+        ann.session.historic.totals(),
+        bob.session.historic.totals(),
+        "Both devices agree"
+    );
+    assert_eq!(
+        ann.session.historic.len()
+            + ann_sync.replica.conflicts().len()
+            + bob_sync.replica.conflicts().len(),
+        3,
+        "Both entered hand 2, one is kept and the other set aside"
+    );
+
+    bob.apply(Action::RemoveLastHand).expect("Bob has hands");
+    pump(&mut devices, |devices| {
+        settled(devices)
+            && devices
+                .iter()
+                .all(|(_, app)| app.session.historic.len() == 1)
+    });
+}
+
+#[test]
+fn offline_changes_are_sent_on_reconnection() {
+    let relay = relay();
+    let proxy = Proxy::new(relay);
+    let mut ann = AppState::default();
+    ann.apply(Action::SetPlayers {
+        names: names(),
+        origin: [0, 1, 2, 3],
+    })
+    .expect("Distinct names");
+    let mut ann_sync = SyncSession::connect(&format!("ws://{relay}"), "table", &ann);
+    let mut bob = AppState::default();
+    let mut bob_sync = SyncSession::connect(&proxy.url(), "table", &bob);
+    let mut devices = [(&mut ann_sync, &mut ann), (&mut bob_sync, &mut bob)];
+    pump(&mut devices, |devices| {
+        settled(devices) && devices.iter().all(|(_, app)| player_names(app).is_some())
+    });
+
+    proxy.cut();
+    pump(&mut devices, |[_, (bob_sync, _)]| {
+        matches!(bob_sync.status(), Status::Offline(_))
+    });
+    let [(_, ann), (_, bob)] = &mut devices;
+    ann.apply(Action::RecordHand(hand("Queens", [21, -7, -7, -7])))
+        .expect("Totals within range");
+    bob.apply(Action::RecordHand(hand("Solo", [-30, 10, 10, 10])))
+        .expect("Totals within range");
+    bob.apply(Action::RecordHand(hand("Misery", [12, -4, -4, -4])))
+        .expect("Totals within range");
+    pump(&mut devices, |[(ann_sync, _), _]| {
+        ann_sync.replica.pending() == 0
+    });
+
+    proxy.restore();
+    pump(&mut devices, settled);
+    let [(_, ann), (bob_sync, bob)] = &mut devices;
+    assert_eq!(
+        contracts(ann),
+        ["Queens", "Misery"],
+        "Bob's second hand still follows the first one"
+    );
+    assert_eq!(contracts(bob), contracts(ann), "Both devices agree");
+    let conflicts: Vec<String> = bob_sync
+        .replica
+        .conflicts()
+        .into_iter()
+        .map(|hand| hand.gamemode_name)
+        .collect();
+    assert_eq!(
+        conflicts,
+        ["Solo"],
+        "The relay got Ann's first hand first, Bob's is kept aside"
+    );
+
+    bob_sync.replica.keep_conflict(0, bob);
+    pump(&mut devices, |devices| {
+        settled(devices)
+            && devices
+                .iter()
+                .all(|(_, app)| app.session.historic.len() == 3)
+    });
+    let [(_, ann), (bob_sync, bob)] = &mut devices;
+    assert_eq!(
+        contracts(ann),
+        ["Queens", "Misery", "Solo"],
+        "Kept hand is sent"
+    );
+    assert_eq!(contracts(bob), contracts(ann), "Both devices agree");
+    assert!(bob_sync.replica.conflicts().is_empty(), "No conflict left");
+}
+
+#[test]
+fn pending_changes_survive_a_restart() {
+    let relay = relay();
+    let proxy = Proxy::new(relay);
+    let mut ann = AppState::default();
+    ann.apply(Action::SetPlayers {
+        names: names(),
+        origin: [0, 1, 2, 3],
+    })
+    .expect("Distinct names");
+    let mut ann_sync = SyncSession::connect(&format!("ws://{relay}"), "table", &ann);
+    let mut bob = AppState::default();
+    let mut bob_sync = SyncSession::connect(&proxy.url(), "table", &bob);
+    let mut devices = [(&mut ann_sync, &mut ann), (&mut bob_sync, &mut bob)];
+    pump(&mut devices, |devices| {
+        settled(devices) && devices.iter().all(|(_, app)| player_names(app).is_some())
+    });
+
+    proxy.cut();
+    pump(&mut devices, |[_, (bob_sync, _)]| {
+        matches!(bob_sync.status(), Status::Offline(_))
+    });
+    let [_, (_, bob)] = &mut devices;
+    bob.apply(Action::RecordHand(hand("Solo", [-30, 10, 10, 10])))
+        .expect("Totals within range");
+    pump(&mut devices, |[_, (bob_sync, _)]| {
+        bob_sync.replica.pending() == 1
+    });
+    let saved = ron::to_string(&bob_sync.saved()).expect("Serializable");
+    drop(bob_sync);
+
+    proxy.restore();
+    let saved: SavedSync = ron::from_str(&saved).expect("Saved above");
+    let mut bob_sync = SyncSession::resume(saved, &bob);
+    let mut devices = [(&mut ann_sync, &mut ann), (&mut bob_sync, &mut bob)];
+    pump(&mut devices, |devices| {
+        settled(devices)
+            && devices
+                .iter()
+                .all(|(_, app)| app.session.historic.len() == 1)
+    });
+    let [(_, ann), (_, bob)] = &devices;
+    assert_eq!(contracts(ann), ["Solo"], "The saved hand is sent");
+    assert_eq!(contracts(bob), contracts(ann), "Both devices agree");
+}
+
+#[test]
+fn a_missed_operation_makes_the_device_join_again() {
+    let mut app = AppState::default();
+    let mut replica = Replica::new("table", &app);
+    replica.join();
+    replica.receive(RelayMessage::Welcome { seq: 2 }, &mut app);
+    let origin = Origin { client: 0, id: 1 };
+    let push = |index, gamemode_name| Op::PushHand {
+        index,
+        hand: serde_json::to_value(hand(gamemode_name, [21, -7, -7, -7])).expect("Plain data"),
+    };
+    replica.receive(
+        RelayMessage::Applied {
+            seq: 1,
+            origin,
+            op: Op::SetPlayers {
+                names: names(),
+                origin: [0, 1, 2, 3],
+            },
+        },
+        &mut app,
+    );
+    replica.receive(
+        RelayMessage::Applied {
+            seq: 2,
+            origin,
+            op: push(0, "Queens"),
+        },
+        &mut app,
+    );
+    assert_eq!(contracts(&app), ["Queens"], "Hand of the room taken");
+    replica.take_outbox();
+
+    // Operation 3 never arrived.
+    replica.receive(
+        RelayMessage::Applied {
+            seq: 4,
+            origin,
+            op: push(2, "Solo"),
+        },
+        &mut app,
+    );
+    assert!(app.session.historic.is_empty(), "Hands dropped");
+    assert!(
+        matches!(
+            replica.take_outbox().as_slice(),
+            [ClientMessage::Join { since: 0, .. }]
+        ),
+        "Joins again from the start"
+    );
+    replica.receive(
+        RelayMessage::Applied {
+            seq: 5,
+            origin,
+            op: push(3, "Misery"),
+        },
+        &mut app,
+    );
+    assert!(
+        app.session.historic.is_empty(),
+        "Operations already on their way wait for the welcome"
+    );
+}
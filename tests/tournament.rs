@@ -0,0 +1,132 @@
+#![cfg(feature = "session")]
+
+use std::collections::HashSet;
+
+use whist_points::tournament::{Tournament, TournamentError};
+
+fn tournament(players: usize) -> Tournament {
+    let mut tournament = Tournament::default();
+    for idx in 0..players {
+        tournament
+            .register(&format!("Player {idx}"))
+            .expect("Distinct names before the first round");
+    }
+    tournament
+}
+
+/// Starts a round and marks all of its tables as finished.
+fn play_round(tournament: &mut Tournament) {
+    tournament.start_round().expect("Round can start");
+    let round = tournament.rounds.last_mut().expect("Round just started");
+    for table in &mut round.tables {
+        table.finished = true;
+    }
+}
+
+/// Every pair of players seated at the same table, in any round.
+fn pairs(tournament: &Tournament) -> Vec<(usize, usize)> {
+    tournament
+        .rounds
+        .iter()
+        .flat_map(|round| &round.tables)
+        .flat_map(|table| {
+            table.seats.iter().flat_map(move |&a| {
+                table
+                    .seats
+                    .iter()
+                    .filter(move |&&b| a < b)
+                    .map(move |&b| (a, b))
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn rounds_need_a_multiple_of_four_players() {
+    for players in [0, 3, 5, 6, 7, 9] {
+        let mut tournament = tournament(players);
+        assert!(
+            !tournament.can_start_round(),
+            "{players} players cannot fill full tables"
+        );
+        assert!(
+            tournament.start_round().is_err(),
+            "{players} players cannot fill full tables"
+        );
+        assert!(tournament.rounds.is_empty(), "No round was started");
+    }
+    for players in [4, 8, 12] {
+        let mut tournament = tournament(players);
+        tournament.start_round().expect("Full tables");
+        let round = tournament.rounds.first().expect("Round started");
+        assert_eq!(round.tables.len(), players / 4, "Four players per table");
+        let seated: HashSet<usize> = round.tables.iter().flat_map(|table| table.seats).collect();
+        assert_eq!(seated.len(), players, "Everyone seated exactly once");
+    }
+}
+
+#[test]
+fn rounds_wait_for_every_table() {
+    let mut tournament = tournament(8);
+    tournament.start_round().expect("Full tables");
+    assert!(
+        matches!(
+            tournament.register("Late"),
+            Err(TournamentError::RegistrationsClosed)
+        ),
+        "Registrations are closed"
+    );
+    assert!(
+        matches!(
+            tournament.start_round(),
+            Err(TournamentError::CannotStartRound)
+        ),
+        "Tables still playing"
+    );
+    tournament
+        .rounds
+        .first_mut()
+        .and_then(|round| round.tables.first_mut())
+        .expect("Two tables")
+        .finished = true;
+    assert!(!tournament.can_start_round(), "One table still playing");
+    for table in &mut tournament.rounds.first_mut().expect("Started").tables {
+        table.finished = true;
+    }
+    tournament.start_round().expect("Every table finished");
+    assert_eq!(tournament.rounds.len(), 2, "Second round started");
+}
+
+#[test]
+fn partners_do_not_meet_again() {
+    let mut tournament = tournament(16);
+    play_round(&mut tournament);
+    play_round(&mut tournament);
+    let pairs = pairs(&tournament);
+    let distinct: HashSet<&(usize, usize)> = pairs.iter().collect();
+    assert_eq!(pairs.len(), 2 * 4 * 6, "Six pairs per table");
+    assert_eq!(distinct.len(), pairs.len(), "No pair met twice");
+}
+
+#[test]
+fn tables_are_mixed_between_rounds() {
+    let mut tournament = tournament(8);
+    play_round(&mut tournament);
+    play_round(&mut tournament);
+    let [first, second] = [0, 1].map(|idx| tournament.rounds.get(idx).expect("Two rounds"));
+    let first_table: HashSet<usize> = first
+        .tables
+        .first()
+        .expect("Two tables")
+        .seats
+        .into_iter()
+        .collect();
+    for table in &second.tables {
+        let former = table
+            .seats
+            .iter()
+            .filter(|&seat| first_table.contains(seat))
+            .count();
+        assert_eq!(former, 2, "Two players of each former table");
+    }
+}
@@ -0,0 +1,185 @@
+#![cfg(all(feature = "tui", not(target_arch = "wasm32")))]
+
+use ratatui::{Terminal, backend::TestBackend, crossterm::event::KeyCode};
+use whist_points::{
+    PlayersState,
+    state::AppState,
+    storage::{DirectoryStorage, MemoryStorage},
+    tui::Tui,
+};
+
+fn press(tui: &mut Tui, keys: impl IntoIterator<Item = KeyCode>) {
+    for key in keys {
+        tui.handle_key(key.into());
+    }
+}
+
+fn type_text(tui: &mut Tui, text: &str) {
+    press(tui, text.chars().map(KeyCode::Char));
+}
+
+/// Screen content, one line per row.
+fn screen(tui: &mut Tui) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).expect("Test backend");
+    terminal
+        .draw(|frame| tui.draw(frame))
+        .expect("Test backend draws");
+    let buffer = terminal.backend().buffer();
+    buffer
+        .content()
+        .chunks(usize::from(buffer.area.width))
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn seat_players(tui: &mut Tui) {
+    for name in ["Ann", "Bob", "Cleo", "Dan"] {
+        type_text(tui, name);
+        press(tui, [KeyCode::Enter]);
+    }
+}
+
+/// Opens the new hand wizard on Queens, the last contract.
+fn new_queens_hand(tui: &mut Tui) {
+    press(tui, [KeyCode::Char('n'), KeyCode::Up, KeyCode::Enter]);
+}
+
+#[test]
+fn hands_are_recorded_from_the_keyboard() {
+    let mut tui = Tui::new(AppState::default(), Box::new(MemoryStorage::default()));
+    seat_players(&mut tui);
+    let PlayersState::Playing(players) = &tui.app().session.players_state else {
+        panic!("Four players start the game");
+    };
+    assert_eq!(players.names(), ["Ann", "Bob", "Cleo", "Dan"]);
+
+    new_queens_hand(&mut tui);
+    assert!(
+        screen(&mut tui).contains("Select contractors"),
+        "The wizard is open"
+    );
+    type_text(&mut tui, "2");
+    press(&mut tui, [KeyCode::Tab]);
+    press(&mut tui, [KeyCode::Right; 4]);
+    press(&mut tui, [KeyCode::Enter]);
+
+    let historic = &tui.app().session.historic;
+    assert_eq!(historic.len(), 1, "The hand is recorded");
+    let hand = historic.get(0).expect("One hand");
+    assert_eq!(hand.gamemode_name, "Queens");
+    assert_eq!(hand.scores.iter().sum::<i16>(), 0, "Scores sum to zero");
+    assert!(tui.app().session.draft.is_none(), "The wizard is closed");
+
+    press(&mut tui, [KeyCode::Enter]);
+    let screen = screen(&mut tui);
+    assert!(screen.contains("Mode: Queens"), "Hand detail:\n{screen}");
+    assert!(
+        screen.contains("Bob 4"),
+        "Tricks of the contractor:\n{screen}"
+    );
+}
+
+#[test]
+fn three_contractors_enter_custom_points() {
+    let mut tui = Tui::new(AppState::default(), Box::new(MemoryStorage::default()));
+    seat_players(&mut tui);
+    new_queens_hand(&mut tui);
+    type_text(&mut tui, "431");
+    // Contractors, then tricks, then the first points.
+    press(&mut tui, [KeyCode::Tab, KeyCode::Tab]);
+    type_text(&mut tui, "12");
+    press(&mut tui, [KeyCode::Tab]);
+    type_text(&mut tui, "5-");
+    press(&mut tui, [KeyCode::Enter]);
+
+    assert_eq!(
+        tui.app().session.historic.totals(),
+        [12, -7, -5, 0],
+        "Points follow the seat order, the fourth player takes the rest"
+    );
+}
+
+#[test]
+fn invalid_hands_keep_the_wizard_open() {
+    let mut tui = Tui::new(AppState::default(), Box::new(MemoryStorage::default()));
+    seat_players(&mut tui);
+    new_queens_hand(&mut tui);
+    press(&mut tui, [KeyCode::Enter]);
+    assert_eq!(
+        tui.app().session.historic.len(),
+        0,
+        "No contractor selected"
+    );
+    assert!(
+        screen(&mut tui).contains("Select 1 to 3 contractors"),
+        "The error is shown"
+    );
+
+    press(&mut tui, [KeyCode::Esc]);
+    assert!(tui.app().session.draft.is_none(), "The hand is cancelled");
+}
+
+#[test]
+fn contracts_cannot_be_browsed_when_there_are_none() {
+    let mut app = AppState::default();
+    app.session.contracts.clear();
+    let mut tui = Tui::new(app, Box::new(MemoryStorage::default()));
+    seat_players(&mut tui);
+    press(&mut tui, [KeyCode::Char('n'), KeyCode::Up]);
+    assert!(
+        screen(&mut tui).contains("No contract to choose from"),
+        "The error is shown"
+    );
+    press(
+        &mut tui,
+        [KeyCode::Char('n'), KeyCode::Down, KeyCode::Enter],
+    );
+    assert!(tui.app().session.draft.is_none(), "No hand was started");
+}
+
+#[test]
+fn games_are_saved_for_the_app() {
+    let dir = std::env::temp_dir().join(format!("whist-points-tui-{}", std::process::id()));
+    let _: std::io::Result<()> = std::fs::remove_dir_all(&dir);
+    let storage = DirectoryStorage::new(&dir).expect("Temporary folder");
+    let mut tui = Tui::new(AppState::default(), Box::new(storage));
+    seat_players(&mut tui);
+    new_queens_hand(&mut tui);
+    type_text(&mut tui, "1");
+    press(&mut tui, [KeyCode::Enter]);
+    press(&mut tui, [KeyCode::Char('q')]);
+    assert!(tui.should_quit(), "q quits");
+
+    let app = AppState::load(&DirectoryStorage::new(&dir).expect("Temporary folder"));
+    assert!(
+        matches!(app.session.players_state, PlayersState::Playing(_)),
+        "The players are saved"
+    );
+    assert_eq!(app.session.historic.len(), 1, "The hand is saved");
+}
+
+#[test]
+fn finishing_a_game_asks_first() {
+    let mut tui = Tui::new(AppState::default(), Box::new(MemoryStorage::default()));
+    seat_players(&mut tui);
+    new_queens_hand(&mut tui);
+    type_text(&mut tui, "1");
+    press(&mut tui, [KeyCode::Enter]);
+
+    press(&mut tui, [KeyCode::Char('f')]);
+    assert!(
+        screen(&mut tui).contains("Archive the game"),
+        "The question is shown"
+    );
+    press(&mut tui, [KeyCode::Char('n')]);
+    assert_eq!(tui.app().session.historic.len(), 1, "The game goes on");
+    assert!(tui.app().archive.is_empty(), "Nothing is archived");
+
+    press(&mut tui, [KeyCode::Char('f'), KeyCode::Char('y')]);
+    assert_eq!(tui.app().archive.len(), 1, "The game is archived");
+    assert!(
+        tui.app().session.historic.is_empty(),
+        "A new game is started"
+    );
+}
@@ -0,0 +1,219 @@
+#![cfg(all(feature = "gui", not(target_arch = "wasm32")))]
+
+use egui::accesskit::Role;
+use egui_kittest::{Harness, Node, kittest::Queryable as _};
+use whist_points::{PlayersState, WhistApp, state::AppState, storage::MemoryStorage};
+
+/// The app window without any storage, rendered headless.
+fn harness() -> Harness<'static, WhistApp> {
+    Harness::builder()
+        .with_size(egui::vec2(800.0, 1200.0))
+        .build_eframe(|cc| WhistApp::new(cc))
+}
+
+/// Widget other than a plain label named `label`, e.g. the contractor button
+/// of a player whose name also heads the table.
+fn widget<'a>(harness: &'a Harness<'_, WhistApp>, role: Role, label: &str) -> Node<'a> {
+    harness.get_by(|node| node.role() == role && node.label().as_deref() == Some(label))
+}
+
+fn add_players(harness: &mut Harness<'_, WhistApp>) {
+    for name in ["Ann", "Bob", "Cleo", "Dan"] {
+        harness.get_by_role(Role::TextInput).type_text(name);
+        harness.run();
+        widget(harness, Role::Button, "Add").click();
+        harness.run();
+    }
+}
+
+/// Selects `contract` in the gamemode list and opens the new hand modal.
+fn new_hand(harness: &mut Harness<'_, WhistApp>, contract: &str) {
+    harness.get_by_role(Role::ComboBox).click();
+    harness.run();
+    widget(harness, Role::Button, contract).click();
+    harness.run();
+    widget(harness, Role::Button, "New hand").click();
+    harness.run();
+}
+
+/// Types `value` in the number field labelled `label`.
+fn enter_number(harness: &mut Harness<'_, WhistApp>, label: &str, value: &str) {
+    harness
+        .get_by(|node| {
+            matches!(node.role(), Role::SpinButton | Role::TextInput)
+                && node.label().as_deref() == Some(label)
+        })
+        .type_text(value);
+    harness.run();
+    harness.key_press(egui::Key::Enter);
+    harness.run();
+}
+
+/// Whether the table shows `text`.
+fn shows(harness: &Harness<'_, WhistApp>, text: &str) -> bool {
+    harness.query_all_by_label(text).next().is_some()
+}
+
+#[test]
+fn four_players_start_the_game() {
+    let mut harness = harness();
+    add_players(&mut harness);
+
+    let PlayersState::Playing(players) = &harness.state().state.session.players_state else {
+        panic!("The fourth player starts the game");
+    };
+    assert_eq!(players.names(), ["Ann", "Bob", "Cleo", "Dan"], "Seat order");
+    assert!(shows(&harness, "Dealer: Ann"), "The first player deals");
+    assert!(shows(&harness, "Game played: 0"), "No hand yet");
+}
+
+#[test]
+fn hands_are_entered_in_the_modal() {
+    let mut harness = harness();
+    add_players(&mut harness);
+    new_hand(&mut harness, "Queens");
+    assert!(
+        harness.state().state.session.draft.is_some(),
+        "The modal is open"
+    );
+
+    widget(&harness, Role::Button, "Bob").click();
+    harness.run();
+    enter_number(&mut harness, "Tricks number", "4");
+    widget(&harness, Role::Button, "Ok").click();
+    harness.run();
+
+    let app = &harness.state().state;
+    assert!(app.session.draft.is_none(), "The modal is closed");
+    assert_eq!(app.session.historic.len(), 1, "The hand is recorded");
+    let hand = app.session.historic.get(0).expect("Recorded");
+    assert_eq!(hand.gamemode_name, "Queens", "Selected contract");
+    assert_eq!(
+        app.session.historic.totals().iter().sum::<i16>(),
+        0,
+        "Zero-sum"
+    );
+    let totals = app.session.historic.totals();
+    for total in totals {
+        assert!(
+            shows(&harness, &total.to_string()),
+            "{total} is in the table"
+        );
+    }
+    assert!(shows(&harness, "Dealer: Bob"), "The deal moves on");
+    assert!(shows(&harness, "Game played: 1"), "One hand");
+}
+
+#[test]
+fn three_contractors_enter_custom_points() {
+    let mut harness = harness();
+    add_players(&mut harness);
+    new_hand(&mut harness, "Queens");
+    for name in ["Dan", "Ann", "Cleo"] {
+        widget(&harness, Role::Button, name).click();
+        harness.run();
+    }
+    widget(&harness, Role::Button, "Ok").click();
+    harness.run();
+    assert!(shows(&harness, "Custom points input"), "Points are asked");
+
+    enter_number(&mut harness, "Ann", "10");
+    enter_number(&mut harness, "Cleo", "4");
+    enter_number(&mut harness, "Dan", "6");
+    // The points modal is shown above the new hand one, its Ok button last.
+    harness
+        .query_all(|node| node.role() == Role::Button && node.label().as_deref() == Some("Ok"))
+        .last()
+        .expect("Ok buttons")
+        .click();
+    harness.run();
+    widget(&harness, Role::Button, "Ok").click();
+    harness.run();
+
+    assert_eq!(
+        harness.state().state.session.historic.totals(),
+        [10, -20, 4, 6],
+        "Points follow the seat order, the fourth player takes the rest"
+    );
+    assert!(shows(&harness, "-20"), "Bob's total is in the table");
+
+    let mut storage = MemoryStorage::default();
+    harness.state_mut().state.persist(&mut storage);
+    let reloaded = AppState::load(&storage);
+    assert_eq!(
+        reloaded.session.historic.totals(),
+        [10, -20, 4, 6],
+        "The hand is saved"
+    );
+}
+
+#[test]
+fn the_last_hand_is_removed() {
+    let mut harness = harness();
+    add_players(&mut harness);
+    new_hand(&mut harness, "Queens");
+    widget(&harness, Role::Button, "Ann").click();
+    harness.run();
+    enter_number(&mut harness, "Tricks number", "3");
+    widget(&harness, Role::Button, "Ok").click();
+    harness.run();
+    assert_eq!(
+        harness.state().state.session.historic.len(),
+        1,
+        "The hand is recorded"
+    );
+
+    widget(&harness, Role::Button, "Remove last hand").click();
+    harness.run();
+
+    assert!(
+        harness.state().state.session.historic.is_empty(),
+        "No hand left"
+    );
+    assert_eq!(
+        harness.state().state.session.historic.totals(),
+        [0; 4],
+        "Scores reset"
+    );
+    let PlayersState::Playing(players) = &harness.state().state.session.players_state else {
+        panic!("The game started");
+    };
+    let players = serde_json::to_value(players).expect("Players are saved");
+    assert!(
+        players["players"]
+            .as_array()
+            .expect("Saved as a list of players")
+            .iter()
+            .all(|player| player["score"] == 0),
+        "Players' scores are undone too"
+    );
+    assert!(shows(&harness, "Game played: 0"), "The table is empty");
+
+    let mut storage = MemoryStorage::default();
+    harness.state_mut().state.persist(&mut storage);
+    assert!(
+        AppState::load(&storage).session.historic.is_empty(),
+        "The removal is saved"
+    );
+}
+
+#[test]
+fn sharing_waits_for_the_web_app_address() {
+    let mut harness = harness();
+    add_players(&mut harness);
+    widget(&harness, Role::Button, "Game").click();
+    harness.run();
+    widget(&harness, Role::Button, "Share").click();
+    harness.run();
+    assert!(
+        shows(
+            &harness,
+            "Set the web app address to get a link other devices can open."
+        ),
+        "No link without the address of the web app"
+    );
+    assert!(
+        harness.query_by_label("Show QR code").is_none(),
+        "No QR code of a link that opens nothing"
+    );
+}